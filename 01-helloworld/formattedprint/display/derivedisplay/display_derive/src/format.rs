@@ -0,0 +1,169 @@
+// format.rs
+// Plain string handling for the derive: reading the value of a string
+// literal, and rewriting the arguments of a format string. Nothing here
+// touches the `proc_macro` API, so it can be unit tested.
+
+// What a `{...}` placeholder refers to.
+#[derive(Debug, PartialEq)]
+pub enum Arg {
+    // `{0}`, and `{}` numbered in order the way `format!` does.
+    Index(usize),
+    // `{x}`.
+    Name(String),
+}
+
+// Replaces the argument of every placeholder with `rename(arg)`, keeping
+// any `:spec` and the `{{` and `}}` escapes as they are. Returns the first
+// error from `rename`, or a message for a malformed format string.
+pub fn rewrite<F>(format: &str, mut rename: F) -> Result<String, String>
+    where F: FnMut(Arg) -> Result<String, String>
+{
+    let mut out = String::new();
+    let mut next = 0;
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push_str("}}");
+            }
+            '}' => return Err("invalid format string: unmatched `}`".to_string()),
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inner.push(c),
+                        None => return Err("invalid format string: unmatched `{`".to_string()),
+                    }
+                }
+                let (arg, spec) = match inner.find(':') {
+                    Some(i) => (&inner[..i], &inner[i..]),
+                    None => (&inner[..], ""),
+                };
+                let arg = if arg.is_empty() {
+                    next += 1;
+                    Arg::Index(next - 1)
+                } else if arg.chars().all(|c| c.is_ascii_digit()) {
+                    match arg.parse() {
+                        Ok(i) => Arg::Index(i),
+                        Err(_) => return Err(format!("invalid argument `{}` in format string", arg)),
+                    }
+                } else if is_identifier(arg) {
+                    Arg::Name(arg.to_string())
+                } else {
+                    return Err(format!("invalid argument `{}` in format string", arg));
+                };
+                out.push('{');
+                out.push_str(&rename(arg)?);
+                out.push_str(spec);
+                out.push('}');
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+// The value of a string literal as written in source, such as `"a\tb"` or
+// `r#"a"b"#`, or `None` if it is not a plain or raw string literal.
+pub fn string_value(literal: &str) -> Option<String> {
+    if let Some(rest) = literal.strip_prefix('r') {
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        let body = &rest[hashes..];
+        let close = format!("\"{}", "#".repeat(hashes));
+        if body.len() > close.len() && body.starts_with('"') && body.ends_with(&close[..]) {
+            return Some(body[1..body.len() - close.len()].to_string());
+        }
+        return None;
+    }
+    if literal.len() < 2 || !literal.starts_with('"') || !literal.ends_with('"') {
+        return None;
+    }
+
+    let mut out = String::new();
+    let mut chars = literal[1..literal.len() - 1].chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            't' => out.push('\t'),
+            '0' => out.push('\0'),
+            '\\' => out.push('\\'),
+            '\'' => out.push('\''),
+            '"' => out.push('"'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let value = u8::from_str_radix(&hex, 16).ok()?;
+                if value > 0x7f {
+                    return None;
+                }
+                out.push(value as char);
+            }
+            'u' => {
+                if chars.next()? != '{' {
+                    return None;
+                }
+                let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let value = u32::from_str_radix(&hex.replace('_', ""), 16).ok()?;
+                out.push(::std::char::from_u32(value)?);
+            }
+            // A line continuation: skip the newline and the indentation.
+            '\n' => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(format: &str) -> Result<String, String> {
+        rewrite(format, |arg| match arg {
+            Arg::Index(i) => Ok(format!("_{}", i)),
+            Arg::Name(n) => Ok(n),
+        })
+    }
+
+    #[test]
+    fn rewrites_arguments() {
+        assert_eq!(names("({0}, {1})").unwrap(), "({_0}, {_1})");
+        assert_eq!(names("{} {} {0}").unwrap(), "{_0} {_1} {_0}");
+        assert_eq!(names("x: {x:>8.2}, {{y}}").unwrap(), "x: {x:>8.2}, {{y}}");
+        assert_eq!(names("{x").unwrap_err(), "invalid format string: unmatched `{`");
+        assert_eq!(names("x}").unwrap_err(), "invalid format string: unmatched `}`");
+        assert!(names("{a b}").is_err());
+    }
+
+    #[test]
+    fn literal_values() {
+        assert_eq!(string_value(r#""a\tb\u{e9}\x41""#), Some("a\tb\u{e9}A".to_string()));
+        assert_eq!(string_value(r###"r#"say "hi""#"###), Some("say \"hi\"".to_string()));
+        assert_eq!(string_value("\"one \\\n    two\""), Some("one two".to_string()));
+        assert_eq!(string_value("b\"bytes\""), None);
+        assert_eq!(string_value("12"), None);
+    }
+}
@@ -0,0 +1,572 @@
+/*
+`#[derive(Display)]`, so that the one line `write!` every `fmt::Display`
+example hand-writes can be an attribute instead:
+
+#[derive(Display)]
+#[display("({0}, {1})")]
+struct MinMax(i64, i64);
+
+#[derive(Display)]
+#[display("x: {x}, y: {y}")]
+struct Point2 {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Display)]
+enum Shape {
+    #[display("circle of radius {radius}")]
+    Circle { radius: f64 },
+    #[display("{0} by {1} rectangle")]
+    Rectangle(f64, f64),
+    // A unit variant with no attribute prints its own name.
+    Empty,
+}
+
+Placeholders take the same `:spec` as `format!` (`{x:>8.2}`), and `{}`
+counts through the tuple fields in order. Width and precision must be
+written out, not taken from another field with `$`. Generic parameters and
+`where` clauses are copied onto the impl as they are, so a type parameter
+that is printed needs a `Display` bound in the type itself.
+
+This is written against the `proc_macro` API only, with no `syn` or
+`quote`. The input is walked token by token, just enough to find the
+name, the generics, the fields and the `#[display]` attributes, and the
+impl is built as a string and parsed back into tokens. Mistakes, such as a
+placeholder naming a field that does not exist, become `compile_error!`
+invocations carrying the span of the offending token, so the compiler
+points at the attribute rather than at the `derive`.
+*/
+
+extern crate proc_macro;
+
+mod format;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use format::Arg;
+
+/// Derives `std::fmt::Display` from a `#[display("...")]` attribute.
+///
+/// ```
+/// #[macro_use]
+/// extern crate display_derive;
+///
+/// #[derive(Display)]
+/// #[display("{name} is {age:03}")]
+/// struct Person<'a> {
+///     name: &'a str,
+///     age: u8,
+/// }
+///
+/// fn main() {
+///     assert_eq!(Person { name: "Ann", age: 7 }.to_string(), "Ann is 007");
+/// }
+/// ```
+///
+/// A placeholder for a field that does not exist:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate display_derive;
+///
+/// #[derive(Display)]
+/// #[display("x: {x}, z: {z}")]
+/// struct Point2 { x: f64, y: f64 }
+///
+/// fn main() {}
+/// ```
+///
+/// A tuple index past the last field:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate display_derive;
+///
+/// #[derive(Display)]
+/// #[display("({0}, {1}, {2})")]
+/// struct MinMax(i64, i64);
+///
+/// fn main() {}
+/// ```
+///
+/// A variant with fields but no attribute:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate display_derive;
+///
+/// #[derive(Display)]
+/// enum Shape {
+///     #[display("circle of radius {0}")]
+///     Circle(f64),
+///     Square(f64),
+/// }
+///
+/// fn main() {}
+/// ```
+///
+/// An attribute that is not a string:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate display_derive;
+///
+/// #[derive(Display)]
+/// #[display(x, y)]
+/// struct Point2 { x: f64, y: f64 }
+///
+/// fn main() {}
+/// ```
+#[proc_macro_derive(Display, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    match parse_item(input).and_then(|item| expand(&item)) {
+        Ok(code) => code.parse().expect("derive(Display) generated invalid code"),
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    fn new(span: Span, message: &str) -> Error {
+        Error { span, message: message.to_string() }
+    }
+
+    // `compile_error!("message");` with every token given our span, which
+    // is where the compiler reports it.
+    fn to_compile_error(&self) -> TokenStream {
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+        let mut args = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+        args.set_span(self.span);
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        let mut semi = Punct::new(';', Spacing::Alone);
+        semi.set_span(self.span);
+        vec![
+            TokenTree::Ident(Ident::new("compile_error", self.span)),
+            TokenTree::Punct(bang),
+            TokenTree::Group(args),
+            TokenTree::Punct(semi),
+        ].into_iter().collect()
+    }
+}
+
+// A `#[display("...")]` attribute: the format string's value, and the
+// literal it came from for error spans.
+struct Attr {
+    format: String,
+    literal: Literal,
+}
+
+enum Fields {
+    Named(Vec<Ident>),
+    Unnamed(usize),
+    Unit,
+}
+
+struct Variant {
+    name: Ident,
+    attr: Option<Attr>,
+    fields: Fields,
+}
+
+enum Data {
+    Struct(Option<Attr>, Fields),
+    Enum(Vec<Variant>),
+}
+
+struct Item {
+    name: Ident,
+    // The tokens between `<` and `>`, and after `where`.
+    generics: Vec<TokenTree>,
+    where_clause: Vec<TokenTree>,
+    data: Data,
+}
+
+// Walks a token list front to back.
+struct Cursor {
+    tokens: Vec<TokenTree>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new<I: IntoIterator<Item = TokenTree>>(tokens: I) -> Cursor {
+        Cursor { tokens: tokens.into_iter().collect(), pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&TokenTree> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<TokenTree> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    // Where to report a problem with the next token.
+    fn span(&self) -> Span {
+        self.peek()
+            .or_else(|| self.tokens.last())
+            .map_or_else(Span::call_site, |t| t.span())
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        match self.peek() {
+            Some(TokenTree::Punct(p)) => p.as_char() == c,
+            _ => false,
+        }
+    }
+
+    fn is_ident(&self, word: &str) -> bool {
+        match self.peek() {
+            Some(TokenTree::Ident(i)) => i.to_string() == word,
+            _ => false,
+        }
+    }
+
+    fn is_group(&self, delimiter: Delimiter) -> bool {
+        match self.peek() {
+            Some(TokenTree::Group(g)) => g.delimiter() == delimiter,
+            _ => false,
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<Ident, Error> {
+        match self.peek() {
+            Some(TokenTree::Ident(i)) => {
+                let i = i.clone();
+                self.pos += 1;
+                Ok(i)
+            }
+            _ => Err(Error::new(self.span(), &format!("expected {}", what))),
+        }
+    }
+
+    // Outer attributes, keeping only `#[display(...)]`.
+    fn attrs(&mut self) -> Result<Option<Attr>, Error> {
+        let mut found = None;
+        while self.is_punct('#') {
+            self.pos += 1;
+            let group = match self.next() {
+                Some(TokenTree::Group(ref g)) if g.delimiter() == Delimiter::Bracket => g.clone(),
+                _ => return Err(Error::new(self.span(), "expected `[` after `#`")),
+            };
+            let mut inner = Cursor::new(group.stream());
+            if !inner.is_ident("display") {
+                continue;
+            }
+            let span = inner.span();
+            if found.is_some() {
+                return Err(Error::new(span, "duplicate `#[display]` attribute"));
+            }
+            found = Some(display_attr(&mut inner, span)?);
+        }
+        Ok(found)
+    }
+
+    // `pub`, `pub(crate)` and the like.
+    fn visibility(&mut self) {
+        if self.is_ident("pub") {
+            self.pos += 1;
+            if self.is_group(Delimiter::Parenthesis) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    // Tokens up to a `stop` token at the top level, not counting `<` `>`
+    // pairs such as the one in `HashMap<K, V>`.
+    fn until<F: Fn(&Cursor) -> bool>(&mut self, stop: F) -> Vec<TokenTree> {
+        let mut out = Vec::new();
+        let mut depth = 0usize;
+        while let Some(t) = self.peek().cloned() {
+            if depth == 0 && stop(self) {
+                break;
+            }
+            if let TokenTree::Punct(ref p) = t {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    // Not the `>` of `->`.
+                    '>' if !arrow(out.last()) => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            out.push(t);
+            self.pos += 1;
+        }
+        out
+    }
+}
+
+fn arrow(previous: Option<&TokenTree>) -> bool {
+    match previous {
+        Some(TokenTree::Punct(p)) => p.as_char() == '-' && p.spacing() == Spacing::Joint,
+        _ => false,
+    }
+}
+
+// The rest of `#[display("...")]`, after `display`.
+fn display_attr(inner: &mut Cursor, span: Span) -> Result<Attr, Error> {
+    let expected = "expected `#[display(\"format string\")]`";
+    inner.pos += 1;
+    let args = match inner.next() {
+        Some(TokenTree::Group(ref g)) if g.delimiter() == Delimiter::Parenthesis => g.clone(),
+        Some(t) => return Err(Error::new(t.span(), expected)),
+        None => return Err(Error::new(span, expected)),
+    };
+    let tokens: Vec<TokenTree> = args.stream().into_iter().collect();
+    if let [TokenTree::Literal(ref literal)] = tokens[..] {
+        if let Some(format) = format::string_value(&literal.to_string()) {
+            return Ok(Attr { format, literal: literal.clone() });
+        }
+    }
+    Err(Error::new(args.span(), expected))
+}
+
+fn parse_item(input: TokenStream) -> Result<Item, Error> {
+    let mut c = Cursor::new(input);
+    let attr = c.attrs()?;
+    c.visibility();
+    let keyword = c.ident("`struct` or `enum`")?;
+    let name = c.ident("a name")?;
+
+    let mut generics = Vec::new();
+    if c.is_punct('<') {
+        c.pos += 1;
+        generics = c.until(|c| c.is_punct('>'));
+        c.pos += 1;
+    }
+
+    let mut where_clause = Vec::new();
+    let mut take_where = |c: &mut Cursor| {
+        if c.is_ident("where") {
+            c.pos += 1;
+            where_clause = c.until(|c| c.is_punct(';') || c.is_group(Delimiter::Brace));
+        }
+    };
+
+    let data = match &keyword.to_string()[..] {
+        "struct" => {
+            let fields = if c.is_group(Delimiter::Parenthesis) {
+                let fields = tuple_fields(c.next());
+                take_where(&mut c);
+                fields
+            } else {
+                take_where(&mut c);
+                if c.is_group(Delimiter::Brace) {
+                    named_fields(c.next())?
+                } else {
+                    Fields::Unit
+                }
+            };
+            Data::Struct(attr, fields)
+        }
+        "enum" => {
+            if let Some(attr) = attr {
+                return Err(Error::new(attr.literal.span(),
+                                      "put `#[display(...)]` on each variant of an enum"));
+            }
+            take_where(&mut c);
+            match c.next() {
+                Some(TokenTree::Group(g)) => Data::Enum(variants(g)?),
+                _ => return Err(Error::new(name.span(), "expected the variants")),
+            }
+        }
+        _ => return Err(Error::new(keyword.span(), "`#[derive(Display)]` only works on a struct or an enum")),
+    };
+
+    Ok(Item { name, generics, where_clause, data })
+}
+
+fn group_stream(group: Option<TokenTree>) -> TokenStream {
+    match group {
+        Some(TokenTree::Group(g)) => g.stream(),
+        _ => TokenStream::new(),
+    }
+}
+
+fn tuple_fields(group: Option<TokenTree>) -> Fields {
+    let mut c = Cursor::new(group_stream(group));
+    let mut n = 0;
+    while c.peek().is_some() {
+        if !c.until(|c| c.is_punct(',')).is_empty() {
+            n += 1;
+        }
+        c.pos += 1;
+    }
+    Fields::Unnamed(n)
+}
+
+fn named_fields(group: Option<TokenTree>) -> Result<Fields, Error> {
+    let mut c = Cursor::new(group_stream(group));
+    let mut names = Vec::new();
+    while c.peek().is_some() {
+        let span = c.span();
+        if c.attrs()?.is_some() {
+            return Err(Error::new(span, "`#[display]` goes on the struct, not on its fields"));
+        }
+        c.visibility();
+        names.push(c.ident("a field name")?);
+        c.until(|c| c.is_punct(','));
+        c.pos += 1;
+    }
+    Ok(Fields::Named(names))
+}
+
+fn variants(group: Group) -> Result<Vec<Variant>, Error> {
+    let mut c = Cursor::new(group.stream());
+    let mut out = Vec::new();
+    while c.peek().is_some() {
+        let attr = c.attrs()?;
+        let name = c.ident("a variant name")?;
+        let fields = if c.is_group(Delimiter::Parenthesis) {
+            tuple_fields(c.next())
+        } else if c.is_group(Delimiter::Brace) {
+            named_fields(c.next())?
+        } else {
+            Fields::Unit
+        };
+        // Skip any `= discriminant`.
+        while c.peek().is_some() && !c.is_punct(',') {
+            c.pos += 1;
+        }
+        c.pos += 1;
+        out.push(Variant { name, attr, fields });
+    }
+    Ok(out)
+}
+
+fn tokens_to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+// The parameter list for `impl<...>` (defaults removed), and the argument
+// list for `Name<...>`.
+fn split_generics(generics: &[TokenTree]) -> (String, String) {
+    let mut c = Cursor::new(generics.to_vec());
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    while c.peek().is_some() {
+        let param = c.until(|c| c.is_punct(','));
+        c.pos += 1;
+        let mut p = Cursor::new(param);
+        let arg = if p.is_punct('\'') {
+            tokens_to_string(&p.tokens[..2])
+        } else {
+            if p.is_ident("const") {
+                p.pos += 1;
+            }
+            p.peek().map_or(String::new(), |t| t.to_string())
+        };
+        p.pos = 0;
+        params.push(tokens_to_string(&p.until(|c| c.is_punct('='))));
+        args.push(arg);
+    }
+    (params.join(", "), args.join(", "))
+}
+
+// One match arm: `Pattern => write!(...)`.
+fn arm(path: &str, fields: &Fields, attr: &Option<Attr>, unit_name: &Ident) -> Result<String, Error> {
+    let attr = match *attr {
+        Some(ref attr) => attr,
+        None => {
+            return match *fields {
+                Fields::Unit => Ok(format!("{} => __formatter.write_str({}),",
+                                           path, Literal::string(&unit_name.to_string()))),
+                _ => Err(Error::new(unit_name.span(),
+                                    &format!("`{}` needs a `#[display(\"...\")]` attribute", path))),
+            };
+        }
+    };
+
+    // Each placeholder is renamed to the binding of its field.
+    let mut used: Vec<String> = Vec::new();
+    let format = format::rewrite(&attr.format, |arg| {
+        let binding = match (arg, fields) {
+            (Arg::Index(i), &Fields::Unnamed(n)) if i < n => format!("__self_{}", i),
+            (Arg::Name(ref name), Fields::Named(names))
+                if names.iter().any(|f| field_name(f) == *name) => format!("__self_{}", name),
+            (Arg::Index(i), _) => return Err(format!("`{}` has no field `{}`", path, i)),
+            (Arg::Name(name), _) => return Err(format!("`{}` has no field `{}`", path, name)),
+        };
+        if !used.contains(&binding) {
+            used.push(binding.clone());
+        }
+        Ok(binding)
+    }).map_err(|message| Error::new(attr.literal.span(), &message))?;
+
+    let pattern = match *fields {
+        Fields::Unit => path.to_string(),
+        Fields::Unnamed(n) => {
+            let bindings: Vec<String> = (0..n)
+                .map(|i| format!("__self_{}", i))
+                .map(|b| if used.contains(&b) { format!("ref {}", b) } else { "_".to_string() })
+                .collect();
+            format!("{}({})", path, bindings.join(", "))
+        }
+        Fields::Named(ref names) => {
+            let bindings: Vec<String> = names
+                .iter()
+                .map(|f| (f, format!("__self_{}", field_name(f))))
+                .filter(|(_, b)| used.contains(b))
+                .map(|(f, b)| format!("{}: ref {}", f, b))
+                .collect();
+            format!("{} {{ {} .. }}", path, bindings.iter().map(|b| format!("{}, ", b)).collect::<String>())
+        }
+    };
+    let args: String = used.iter().map(|b| format!(", {} = {}", b, b)).collect();
+    Ok(format!("{} => write!(__formatter, {}{}),", pattern, Literal::string(&format), args))
+}
+
+// `r#type` is `type` inside a format string.
+fn field_name(field: &Ident) -> String {
+    let name = field.to_string();
+    match name.strip_prefix("r#") {
+        Some(raw) => raw.to_string(),
+        None => name,
+    }
+}
+
+fn expand(item: &Item) -> Result<String, Error> {
+    let name = item.name.to_string();
+    let arms = match item.data {
+        Data::Struct(ref attr, ref fields) => {
+            if attr.is_none() {
+                return Err(Error::new(item.name.span(),
+                                      &format!("`{}` needs a `#[display(\"...\")]` attribute", name)));
+            }
+            arm(&name, fields, attr, &item.name)?
+        }
+        Data::Enum(ref variants) => {
+            let mut arms = String::new();
+            for v in variants {
+                arms.push_str(&arm(&format!("{}::{}", name, v.name), &v.fields, &v.attr, &v.name)?);
+            }
+            arms
+        }
+    };
+
+    let (params, args) = split_generics(&item.generics);
+    let where_clause = if item.where_clause.is_empty() {
+        String::new()
+    } else {
+        format!("where {}", tokens_to_string(&item.where_clause))
+    };
+    Ok(format!(
+        "#[automatically_derived]
+        impl<{params}> ::std::fmt::Display for {name}<{args}> {where_clause} {{
+            #[allow(non_snake_case)]
+            fn fmt(&self, __formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
+                match *self {{
+                    {arms}
+                }}
+            }}
+        }}",
+        params = params, name = name, args = args, where_clause = where_clause, arms = arms))
+}
@@ -0,0 +1,106 @@
+/*
+The display example, and the others like it in 02-primitives/tuples and
+07-flowcontrol, implement `fmt::Display` by hand, each with a single
+`write!`. The `display_derive` crate beside this one turns that into an
+attribute:
+
+#[derive(Display)]
+#[display("({0}, {1})")]
+struct MinMax(i64, i64);
+
+A derive macro has to live in a crate of its own, marked `proc-macro =
+true` in its Cargo.toml, and is then used like any other dependency. How
+it works, and what it accepts, is described in display_derive/src/lib.rs.
+*/
+
+#[macro_use]
+extern crate display_derive;
+
+// From the display example.
+#[derive(Debug, Display)]
+#[display("({0}, {1})")]
+struct MinMax(i64, i64);
+
+#[derive(Debug, Display)]
+#[display("x: {x}, y: {y}")]
+struct Point2 {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, Display)]
+#[display("{real} + {imaginary}i")]
+struct Complex {
+    real: f64,
+    imaginary: f64,
+}
+
+// From the tuples example, with a format spec.
+#[derive(Display)]
+#[display("( {0:5.1}, {1:5.1} )\n( {2:5.1}, {3:5.1} )")]
+struct Matrix(f32, f32, f32, f32);
+
+// From the enum destructuring example. Each variant has its own format,
+// and the unit variants print their names.
+#[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Display)]
+enum Color {
+    Red,
+    Blue,
+    Green,
+    #[display("Red: {}, green: {}, and blue: {}!")]
+    RGB(u32, u32, u32),
+    #[display("Cyan: {c}, magenta: {m}, yellow: {y}, key (black): {k}!")]
+    CMYK { c: u32, m: u32, y: u32, k: u32 },
+}
+
+// Generic parameters are carried over to the impl.
+#[derive(Display)]
+#[display("[{label}: {value}]")]
+struct Labelled<'a, T: ::std::fmt::Display> {
+    label: &'a str,
+    value: T,
+}
+
+fn main() {
+    let minmax = MinMax(0, 14);
+    println!("Display: {}", minmax);
+    println!("Debug: {:?}", minmax);
+
+    let point = Point2 { x: 3.3, y: 7.2 };
+    println!("Display: {}", point);
+    println!("Debug: {:?}", point);
+
+    let complex = Complex { real: 4.0, imaginary: 6.0 };
+    println!("Display: {}", complex);
+    println!("Debug: {:?}", complex);
+
+    println!("{}", Matrix(1.1, 1.2, 2.1, 2.2));
+
+    for color in &[Color::Green, Color::RGB(122, 17, 40), Color::CMYK { c: 0, m: 10, y: 20, k: 5 }] {
+        println!("{}", color);
+    }
+
+    println!("{}", Labelled { label: "answer", value: 42 });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn structs() {
+        assert_eq!(MinMax(-3, 3).to_string(), "(-3, 3)");
+        assert_eq!(Point2 { x: 3.3, y: 7.2 }.to_string(), "x: 3.3, y: 7.2");
+        assert_eq!(Matrix(1.0, 2.0, 3.0, 4.0).to_string(), "(   1.0,   2.0 )\n(   3.0,   4.0 )");
+        assert_eq!(Labelled { label: "e", value: 2.5 }.to_string(), "[e: 2.5]");
+    }
+
+    #[test]
+    fn enums() {
+        assert_eq!(Color::Blue.to_string(), "Blue");
+        assert_eq!(Color::RGB(1, 2, 3).to_string(), "Red: 1, green: 2, and blue: 3!");
+        assert_eq!(format!("{}!", Color::Green), "Green!");
+    }
+}
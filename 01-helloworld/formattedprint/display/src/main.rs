@@ -1,5 +1,10 @@
+extern crate rbe;
+
 use std::fmt; // Import `fmt`
 
+// `Complex` and its `Display` are in rbe/src/complex.rs.
+use rbe::complex::Complex;
+
 // A structure holding two numbers. `Debug` will be derived so the results can
 // be contrasted with `Display`.
 #[derive(Debug)]
@@ -28,18 +33,6 @@ impl fmt::Display for Point2 {
     }
 }
 
-#[derive(Debug)]
-struct Complex {
-  real: f64,
-  imaginary: f64,
-}
-
-impl fmt::Display for Complex {
-  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{} + {}i", self.real, self.imaginary)
-  }
-}
-
 fn main() {
     let minmax = MinMax(0, 14);
 
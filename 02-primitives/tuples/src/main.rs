@@ -1,4 +1,4 @@
-use std::fmt; // Import `fmt`
+extern crate rbe;
 
 // Tuples can be used as function arguments and as return values
 fn reverse(pair: (i32, bool)) -> (bool, i32) {
@@ -9,23 +9,9 @@ fn reverse(pair: (i32, bool)) -> (bool, i32) {
 }
 
 
-// The following struct is for the activity.
-#[derive(Debug)]
-struct Matrix(f32, f32, f32, f32);
-
-// Transpose elements of a Matrix
-fn transpose(mi: Matrix) -> Matrix {
-    // 
-    Matrix(mi.0, mi.2, mi.1, mi.3)
-}
-
-// Implement `Display` for `Matrix`.
-impl fmt::Display for Matrix {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Use `self.number` to refer to each positional data point.
-        write!(f, "( {}, {} )\n( {}, {} )", self.0, self.1, self.2, self.3)
-    }
-}
+// The struct for the activity, `Matrix(f32, f32, f32, f32)`, its `Display`
+// and `transpose` now live in rbe/src/geometry.rs.
+use rbe::geometry::Matrix;
 
 fn main() {
     // A tuple with a bunch of different types
@@ -67,7 +53,7 @@ fn main() {
     println!("{}", "\n");
 
     println!("Matrix:\n{}", matrix);
-    println!("Transpose:\n{}", transpose(matrix));
+    println!("Transpose:\n{}", matrix.transpose());
 
 }
 
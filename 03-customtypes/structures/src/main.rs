@@ -8,6 +8,13 @@ struct keyword:
 
 */
 
+// The classic C structs used here, `Point` and `Rectangle`, come from the
+// rbe library shared by the examples, in rbe/src/geometry.rs. A `Rectangle`
+// reuses `Point` for its fields.
+extern crate rbe;
+
+use rbe::geometry::{Point, Rectangle};
+
 // A unit struct
 struct Nil;
 
@@ -15,20 +22,6 @@ struct Nil;
 #[derive(Debug)]
 struct Pair(i32, f64);
 
-// A struct with two fields
-#[derive(Debug)]
-struct Point {
-    x: f64,
-    y: f64,
-}
-
-// Structs can be reused as fields of another struct
-#[derive(Debug)]
-struct Rectangle {
-    p1: Point,
-    p2: Point,
-}
-
 fn main() {
     // Instantiate a `Point`
     let point: Point = Point { x: 0.3, y: 0.4 };
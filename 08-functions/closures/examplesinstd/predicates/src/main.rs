@@ -0,0 +1,73 @@
+/*
+The `iteratorany` and `iteratorfind` examples pass one-off closures such as
+`|&&x| x == 2`. That is fine once, but real filtering code tends to grow
+nested closures nobody can read, and which print nothing useful when a test
+fails.
+
+A `Predicate<T>` is a named test on a `&T` that can be combined with `and`,
+`or`, `xor` and `not`. The built-in constructors are:
+
+eq(v)            value == v
+in_range(r)      r.contains(value), for any range syntax
+one_of(vs)       value is one of vs
+matches(glob)    a glob with `?`, `*`, `[a-z]`, `[!a-z]` and `\` escapes
+from_fn(name, f) any closure, shown as `name`
+
+A composed predicate prints its expression with `{:?}`, and an indented tree
+with `{:#?}`.
+
+Iterator adaptors pass their element in one of three shapes, and there is a
+closure for each:
+
+to_fn()      Fn(&T)   filter/find on into_iter(); any/all/position on iter()
+to_ref_fn()  Fn(&&T)  filter/find on iter()
+to_val_fn()  Fn(T)    any/all/position on into_iter()
+*/
+
+mod predicate;
+
+use predicate::{eq, from_fn, in_range, matches, one_of, Compose, Predicate};
+
+fn main() {
+    let vec1 = vec![1, 2, 3];
+    let vec2 = vec![4, 5, 6];
+
+    // The `iteratorany` example, with predicates in place of closures.
+    let two = eq(2);
+    println!("2 in vec1: {}", vec1.iter().any(two.to_fn()));
+    println!("5 in vec1: {}", vec1.iter().any(eq(5).to_fn()));
+    println!("2 in vec2: {}", vec2.clone().into_iter().any(two.to_val_fn()));
+
+    // The `iteratorfind` example.
+    println!("Find 2 in vec1: {:?}", vec1.iter().find(two.to_ref_fn()));
+    println!("Find 5 in vec2: {:?}", vec2.clone().into_iter().find(eq(5).to_fn()));
+
+    // Combinators. A reference to a predicate is itself a predicate, so
+    // `small` can be used in several compositions.
+    let small = in_range(1..=3);
+    let even = from_fn("even", |x: &i32| x % 2 == 0);
+    let wanted = (&small).xor(even).or(one_of(vec![9, 10]));
+
+    println!("wanted: {:?}", wanted);
+    println!("wanted, as a tree: {:#?}", wanted);
+
+    let numbers: Vec<i32> = (0..12).collect();
+    let picked: Vec<&i32> = numbers.iter().filter(wanted.to_ref_fn()).collect();
+    println!("wanted in {:?}: {:?}", numbers, picked);
+    println!("first not small in {:?}: {:?}",
+             numbers,
+             numbers.iter().position((&small).not().to_fn()));
+    println!("all small in vec2: {}", vec2.iter().all(small.to_fn()));
+    println!("all small and positive in vec1: {}",
+             vec1.into_iter().all((&small).and(in_range(1..)).to_val_fn()));
+
+    // Globs work on anything that is `AsRef<str>`.
+    let words = vec!["hotdog", "cat", "cart", "dog", "Dogma"];
+    let doggy = matches("*dog*").or(matches("[A-Z]og*"));
+    println!("{:?} in {:?}: {:?}",
+             doggy,
+             words,
+             words.iter().filter(doggy.to_ref_fn()).collect::<Vec<_>>());
+    println!("three letter words: {:?}",
+             words.iter().filter(matches("???").to_ref_fn()).collect::<Vec<_>>());
+}
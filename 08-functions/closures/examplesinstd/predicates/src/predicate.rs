@@ -0,0 +1,354 @@
+// predicate.rs
+// A `Predicate<T>` is a named, composable test on a `&T`. Unlike a bare
+// closure, a predicate knows what it is, so `{:?}` prints the expression it
+// was built from and `{:#?}` prints it as an indented tree.
+use std::fmt;
+use std::ops::RangeBounds;
+
+// The combinators live in their own trait, without the `T`. Most predicates
+// here accept many `T`s (`matches` takes `str`, `String` and `&str`), so a
+// `T` on `and`/`or` could not be inferred from the arguments alone.
+pub trait Compose: fmt::Debug {
+    // Both `self` and `other` must hold.
+    fn and<P>(self, other: P) -> And<Self, P>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    // At least one of `self` and `other` must hold.
+    fn or<P>(self, other: P) -> Or<Self, P>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    // Exactly one of `self` and `other` must hold.
+    fn xor<P>(self, other: P) -> Xor<Self, P>
+    where
+        Self: Sized,
+    {
+        Xor(self, other)
+    }
+
+    // `self` must not hold.
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+pub trait Predicate<T: ?Sized>: fmt::Debug {
+    // The only required method.
+    fn test(&self, value: &T) -> bool;
+
+    // The iterator adaptors differ in how they hand over the element, so
+    // there is one closure per shape:
+    //
+    // `to_fn` takes `&T`: `filter` and `find` on `into_iter()`, and `any`,
+    // `all` and `position` on `iter()`.
+    fn to_fn(&self) -> impl Fn(&T) -> bool + '_
+    where
+        Self: Sized,
+    {
+        move |x| self.test(x)
+    }
+
+    // `to_ref_fn` takes `&&T`: `filter` and `find` on `iter()`.
+    fn to_ref_fn(&self) -> impl Fn(&&T) -> bool + '_
+    where
+        Self: Sized,
+    {
+        move |x| self.test(x)
+    }
+
+    // `to_val_fn` takes `T`: `any`, `all` and `position` on `into_iter()`.
+    fn to_val_fn(&self) -> impl Fn(T) -> bool + '_
+    where
+        Self: Sized,
+        T: Sized,
+    {
+        move |x| self.test(&x)
+    }
+}
+
+// A reference to a predicate is a predicate, so one can be reused in several
+// compositions without being moved.
+impl<T: ?Sized, P: Predicate<T> + ?Sized> Predicate<T> for &P {
+    fn test(&self, value: &T) -> bool {
+        (**self).test(value)
+    }
+}
+
+impl<P: Compose + ?Sized> Compose for &P {}
+
+// Combinators. The derived `Debug` is what prints the expression tree.
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B>(A, B);
+
+impl<T: ?Sized, A: Predicate<T>, B: Predicate<T>> Predicate<T> for And<A, B> {
+    fn test(&self, value: &T) -> bool {
+        self.0.test(value) && self.1.test(value)
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> Compose for And<A, B> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Or<A, B>(A, B);
+
+impl<T: ?Sized, A: Predicate<T>, B: Predicate<T>> Predicate<T> for Or<A, B> {
+    fn test(&self, value: &T) -> bool {
+        self.0.test(value) || self.1.test(value)
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> Compose for Or<A, B> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Xor<A, B>(A, B);
+
+impl<T: ?Sized, A: Predicate<T>, B: Predicate<T>> Predicate<T> for Xor<A, B> {
+    fn test(&self, value: &T) -> bool {
+        self.0.test(value) != self.1.test(value)
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> Compose for Xor<A, B> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Not<A>(A);
+
+impl<T: ?Sized, A: Predicate<T>> Predicate<T> for Not<A> {
+    fn test(&self, value: &T) -> bool {
+        !self.0.test(value)
+    }
+}
+
+impl<A: fmt::Debug> Compose for Not<A> {}
+
+// Built-in constructors.
+#[derive(Debug, Clone, Copy)]
+pub struct Eq<V>(V);
+
+// `value == expected`.
+pub fn eq<V>(expected: V) -> Eq<V> {
+    Eq(expected)
+}
+
+impl<T: PartialEq<V> + ?Sized, V: fmt::Debug> Predicate<T> for Eq<V> {
+    fn test(&self, value: &T) -> bool {
+        *value == self.0
+    }
+}
+
+impl<V: fmt::Debug> Compose for Eq<V> {}
+
+#[derive(Debug, Clone)]
+pub struct InRange<R>(R);
+
+// `value` lies in `range`. Any range syntax works: `1..5`, `1..=5`, `3..`.
+pub fn in_range<R>(range: R) -> InRange<R> {
+    InRange(range)
+}
+
+impl<T: PartialOrd, R: RangeBounds<T> + fmt::Debug> Predicate<T> for InRange<R> {
+    fn test(&self, value: &T) -> bool {
+        self.0.contains(value)
+    }
+}
+
+impl<R: fmt::Debug> Compose for InRange<R> {}
+
+#[derive(Debug, Clone)]
+pub struct OneOf<V>(Vec<V>);
+
+// `value` equals one of `candidates`.
+pub fn one_of<V, I: IntoIterator<Item = V>>(candidates: I) -> OneOf<V> {
+    OneOf(candidates.into_iter().collect())
+}
+
+impl<T: PartialEq<V> + ?Sized, V: fmt::Debug> Predicate<T> for OneOf<V> {
+    fn test(&self, value: &T) -> bool {
+        self.0.iter().any(|c| *value == *c)
+    }
+}
+
+impl<V: fmt::Debug> Compose for OneOf<V> {}
+
+#[derive(Debug, Clone)]
+pub struct Matches(String);
+
+// The whole of `value` matches the glob `pattern`:
+//
+// `?`      any one character
+// `*`      any run of characters, including none
+// `[abc]`  one of the listed characters; `a-z` ranges are allowed
+// `[!abc]` any character not listed
+// `\c`     the character `c` itself
+pub fn matches(pattern: &str) -> Matches {
+    Matches(pattern.to_string())
+}
+
+impl<T: AsRef<str> + ?Sized> Predicate<T> for Matches {
+    fn test(&self, value: &T) -> bool {
+        let pattern: Vec<char> = self.0.chars().collect();
+        let text: Vec<char> = value.as_ref().chars().collect();
+        glob(&pattern, &text)
+    }
+}
+
+impl Compose for Matches {}
+
+// Wraps a closure, with a name to show in place of its (unprintable) body.
+#[derive(Clone, Copy)]
+pub struct FromFn<F> {
+    name: &'static str,
+    f: F,
+}
+
+pub fn from_fn<F>(name: &'static str, f: F) -> FromFn<F> {
+    FromFn { name, f }
+}
+
+impl<F> fmt::Debug for FromFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T) -> bool> Predicate<T> for FromFn<F> {
+    fn test(&self, value: &T) -> bool {
+        (self.f)(value)
+    }
+}
+
+impl<F> Compose for FromFn<F> {}
+
+// Iterative glob matching. On a mismatch we backtrack to the most recent `*`
+// and let it swallow one more character, which keeps this linear in the
+// common case instead of exponential.
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Pattern index just past the last `*`, and the text index it resumed at.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let step = if p < pattern.len() {
+            match pattern[p] {
+                '*' => {
+                    star = Some((p + 1, t));
+                    p += 1;
+                    continue;
+                }
+                '?' => Some(1),
+                '[' => match_class(&pattern[p..], text[t]),
+                '\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == text[t] {
+                        Some(2)
+                    } else {
+                        None
+                    }
+                }
+                c if c == text[t] => Some(1),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        match (step, star) {
+            (Some(width), _) => {
+                p += width;
+                t += 1;
+            }
+            (None, Some((star_p, star_t))) => {
+                p = star_p;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    // Only trailing `*`s may remain.
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// `class` starts at a `[`. Returns the width of the class in the pattern if
+// `c` is in it. An unterminated `[` is treated as a literal.
+fn match_class(class: &[char], c: char) -> Option<usize> {
+    let close = match class.iter().skip(2).position(|&x| x == ']') {
+        Some(i) => i + 2,
+        None => return if c == '[' { Some(1) } else { None },
+    };
+    let (negated, body) = if class[1] == '!' {
+        (true, &class[2..close])
+    } else {
+        (false, &class[1..close])
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < body.len() {
+        if i + 2 < body.len() && body[i + 1] == '-' {
+            found |= body[i] <= c && c <= body[i + 2];
+            i += 3;
+        } else {
+            found |= body[i] == c;
+            i += 1;
+        }
+    }
+
+    if found != negated {
+        Some(close + 1)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob_test() {
+        assert!(matches("*").test("anything"));
+        assert!(matches("*").test(""));
+        assert!(matches("c?t").test("cat"));
+        assert!(!matches("c?t").test("cart"));
+        assert!(matches("*dog*").test("hotdogs"));
+        assert!(matches("a*b*c").test("aXXbYYbc"));
+        assert!(!matches("a*b*c").test("aXXbYYbd"));
+        assert!(matches("[a-c]x").test("bx"));
+        assert!(!matches("[!a-c]x").test("bx"));
+        assert!(matches("\\*").test("*"));
+        assert!(!matches("\\*").test("x"));
+        assert!(matches("ü*").test("über"));
+    }
+
+    #[test]
+    fn combinators_test() {
+        let small = in_range(1..=3);
+        let even = from_fn("even", |x: &i32| x % 2 == 0);
+
+        assert!((&small).and(even).test(&2));
+        assert_eq!(format!("{:?}", (&small).and(even)), "And(InRange(1..=3), even)");
+        assert!(!(&small).and(even).test(&4));
+        assert!((&small).or(even).test(&4));
+        assert!((&small).xor(even).test(&4));
+        assert!(!(&small).xor(even).test(&2));
+        assert!(small.not().test(&7));
+        assert!(one_of(vec![2, 4]).test(&4));
+    }
+
+    #[test]
+    fn debug_test() {
+        let p = eq(2).or(in_range(5..7).not());
+        assert_eq!(format!("{:?}", p), "Or(Eq(2), Not(InRange(5..7)))");
+    }
+}
@@ -3,62 +3,14 @@ Methods are functions attached to objects. These methods have access to the
 data of the object and its other methods via the self keyword. Methods are 
 defined under an impl block.
 */
-struct Point {
-    x: f64,
-    y: f64,
-}
-
-// Implementation block, all `Point` methods go in here
-impl Point {
-    // This is a static method
-    // Static methods don't need to be called by an instance
-    // These methods are generally used as constructors
-    fn origin() -> Point {
-        Point { x: 0.0, y: 0.0 }
-    }
-
-    // Another static method, taking two arguments:
-    fn new(x: f64, y: f64) -> Point {
-        Point { x: x, y: y }
-    }
-}
-
-struct Rectangle {
-    p1: Point,
-    p2: Point,
-}
 
-impl Rectangle {
-    // This is an instance method
-    // `&self` is sugar for `self: &Self`, where `Self` is the type of the
-    // caller object. In this case `Self` = `Rectangle`
-    fn area(&self) -> f64 {
-        // `self` gives access to the struct fields via the dot operator
-        let Point { x: x1, y: y1 } = self.p1;
-        let Point { x: x2, y: y2 } = self.p2;
+// `Point` and `Rectangle` come from the rbe library shared by the examples,
+// in rbe/src/geometry.rs. `Point::origin` and `Point::new` are static
+// methods; `Rectangle::area` and `perimeter` take `&self`, and `translate`
+// takes `&mut self`.
+extern crate rbe;
 
-        // `abs` is a `f64` method that returns the absolute value of the
-        // caller
-        ((x1 - x2) * (y1 - y2)).abs()
-    }
-
-    fn perimeter(&self) -> f64 {
-        let Point { x: x1, y: y1 } = self.p1;
-        let Point { x: x2, y: y2 } = self.p2;
-
-        2.0 * ((x1 - x2).abs() + (y1 - y2).abs())
-    }
-
-    // This method requires the caller object to be mutable
-    // `&mut self` desugars to `self: &mut Self`
-    fn translate(&mut self, x: f64, y: f64) {
-        self.p1.x += x;
-        self.p2.x += x;
-
-        self.p1.y += y;
-        self.p2.y += y;
-    }
-}
+use rbe::geometry::{Point, Rectangle};
 
 // `Pair` owns resources: two heap allocated integers
 struct Pair(Box<i32>, Box<i32>);
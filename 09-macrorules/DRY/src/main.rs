@@ -2,44 +2,160 @@
 Macros allow writing DRY code by factoring out the common parts of functions 
 and/or test suites. Here is an example that implements and tests the +=, *= 
 and -= operators on Vec<T>:
+
+The example has since grown to cover the whole operator family: +, -, *, /,
+%, &, |, ^, << and >>. Each operator gets four functions:
+
+add_assign(xs, ys)          xs[i] += ys[i]
+add(xs, ys)                 a new Vec of xs[i] + ys[i]
+add_assign_scalar(xs, y)    xs[i] += y
+add_scalar(xs, y)           a new Vec of xs[i] + y
+
+Instead of panicking, a dimension mismatch is returned as an Err holding both
+lengths and the operator. The scalar variants can not mismatch, but they
+return a `Result` all the same, so that every function of the family is
+called the same way.
+
+$ cargo run
+add     [15, 9, 6] [15, 9, 6] [14, 9, 7] [14, 9, 7]
+...
+mul: dimension mismatch: (3,) * (2,)
 */
-use std::ops::{Add, Mul, Sub};
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Shl, Shr, Sub};
+
+// The error returned when two vectors of different lengths are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DimensionMismatch {
+    // The name of the generated function.
+    pub func: &'static str,
+    // The operator, e.g. `+=`.
+    pub op: &'static str,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: dimension mismatch: ({},) {} ({},)",
+               self.func, self.left, self.op, self.right)
+    }
+}
 
 macro_rules! assert_equal_len {
     // The `tt` (token tree) designator is used for
     // operators and tokens.
+    //
+    // On a mismatch this returns early from the calling function with a
+    // `DimensionMismatch`, much as the `?` operator would.
     ($a:ident, $b: ident, $func:ident, $op:tt) => (
-        assert!($a.len() == $b.len(),
-                "{:?}: dimension mismatch: {:?} {:?} {:?}",
-                stringify!($func),
-                ($a.len(),),
-                stringify!($op),
-                ($b.len(),));
+        if $a.len() != $b.len() {
+            return Err(DimensionMismatch {
+                func: stringify!($func),
+                op: stringify!($op),
+                left: $a.len(),
+                right: $b.len(),
+            });
+        }
     )
 }
 
 macro_rules! op {
+    // xs[i] = xs[i] op ys[i]
     ($func:ident, $bound:ident, $op:tt, $method:ident) => (
-        fn $func<T: $bound<T, Output=T> + Copy>(xs: &mut Vec<T>, ys: &Vec<T>) {
+        fn $func<T: $bound<T, Output=T> + Copy>(xs: &mut [T], ys: &[T])
+                                                -> Result<(), DimensionMismatch> {
             assert_equal_len!(xs, ys, $func, $op);
 
             for (x, y) in xs.iter_mut().zip(ys.iter()) {
                 *x = $bound::$method(*x, *y);
                 // *x = x.$method(*y);
             }
+            Ok(())
         }
-    )
+    );
+    // A new `Vec` of xs[i] op ys[i]
+    (new $func:ident, $bound:ident, $op:tt, $method:ident) => (
+        fn $func<T: $bound<T, Output=T> + Copy>(xs: &[T], ys: &[T])
+                                                -> Result<Vec<T>, DimensionMismatch> {
+            assert_equal_len!(xs, ys, $func, $op);
+
+            Ok(xs.iter().zip(ys.iter()).map(|(x, y)| $bound::$method(*x, *y)).collect())
+        }
+    );
+    // xs[i] = xs[i] op y
+    (scalar $func:ident, $bound:ident, $op:tt, $method:ident) => (
+        fn $func<T: $bound<T, Output=T> + Copy>(xs: &mut [T], y: T)
+                                                -> Result<(), DimensionMismatch> {
+            for x in xs.iter_mut() {
+                *x = $bound::$method(*x, y);
+            }
+            Ok(())
+        }
+    );
+    // A new `Vec` of xs[i] op y
+    (new scalar $func:ident, $bound:ident, $op:tt, $method:ident) => (
+        fn $func<T: $bound<T, Output=T> + Copy>(xs: &[T], y: T)
+                                                -> Result<Vec<T>, DimensionMismatch> {
+            Ok(xs.iter().map(|x| $bound::$method(*x, y)).collect())
+        }
+    );
 }
 
 // Implement `add_assign`, `mul_assign`, and `sub_assign` functions.
 op!(add_assign, Add, +=, add);
 op!(mul_assign, Mul, *=, mul);
 op!(sub_assign, Sub, -=, sub);
+// And the rest of the assigning family.
+op!(div_assign, Div, /=, div);
+op!(rem_assign, Rem, %=, rem);
+op!(bitand_assign, BitAnd, &=, bitand);
+op!(bitor_assign, BitOr, |=, bitor);
+op!(bitxor_assign, BitXor, ^=, bitxor);
+op!(shl_assign, Shl, <<=, shl);
+op!(shr_assign, Shr, >>=, shr);
+
+// The non-assigning family.
+op!(new add, Add, +, add);
+op!(new mul, Mul, *, mul);
+op!(new sub, Sub, -, sub);
+op!(new div, Div, /, div);
+op!(new rem, Rem, %, rem);
+op!(new bitand, BitAnd, &, bitand);
+op!(new bitor, BitOr, |, bitor);
+op!(new bitxor, BitXor, ^, bitxor);
+op!(new shl, Shl, <<, shl);
+op!(new shr, Shr, >>, shr);
+
+// Scalar broadcast, assigning.
+op!(scalar add_assign_scalar, Add, +=, add);
+op!(scalar mul_assign_scalar, Mul, *=, mul);
+op!(scalar sub_assign_scalar, Sub, -=, sub);
+op!(scalar div_assign_scalar, Div, /=, div);
+op!(scalar rem_assign_scalar, Rem, %=, rem);
+op!(scalar bitand_assign_scalar, BitAnd, &=, bitand);
+op!(scalar bitor_assign_scalar, BitOr, |=, bitor);
+op!(scalar bitxor_assign_scalar, BitXor, ^=, bitxor);
+op!(scalar shl_assign_scalar, Shl, <<=, shl);
+op!(scalar shr_assign_scalar, Shr, >>=, shr);
+
+// Scalar broadcast, non-assigning.
+op!(new scalar add_scalar, Add, +, add);
+op!(new scalar mul_scalar, Mul, *, mul);
+op!(new scalar sub_scalar, Sub, -, sub);
+op!(new scalar div_scalar, Div, /, div);
+op!(new scalar rem_scalar, Rem, %, rem);
+op!(new scalar bitand_scalar, BitAnd, &, bitand);
+op!(new scalar bitor_scalar, BitOr, |, bitor);
+op!(new scalar bitxor_scalar, BitXor, ^, bitxor);
+op!(new scalar shl_scalar, Shl, <<, shl);
+op!(new scalar shr_scalar, Shr, >>, shr);
 
+#[cfg(test)]
 mod test {
     use std::iter;
     macro_rules! test {
-        ($func: ident, $x:expr, $y:expr, $z:expr) => {
+        ($func: ident, $op:tt, $x:expr, $y:expr, $z:expr) => {
             #[test]
             fn $func() {
                 for size in 0usize..10 {
@@ -47,17 +163,150 @@ mod test {
                     let y: Vec<_> = iter::repeat($y).take(size).collect();
                     let z: Vec<_> = iter::repeat($z).take(size).collect();
 
-                    super::$func(&mut x, &y);
+                    assert_eq!(super::$func(&mut x, &y), Ok(()));
 
                     assert_eq!(x, z);
+
+                    // One element too many on the right.
+                    let y: Vec<_> = iter::repeat($y).take(size + 1).collect();
+                    let err = super::$func(&mut x, &y).unwrap_err();
+                    assert_eq!((err.func, err.op, err.left, err.right),
+                               (stringify!($func), stringify!($op), size, size + 1));
                 }
             }
-        }
+        };
+        (new $func: ident, $op:tt, $x:expr, $y:expr, $z:expr) => {
+            #[test]
+            fn $func() {
+                for size in 0usize..10 {
+                    let x: Vec<_> = iter::repeat($x).take(size).collect();
+                    let y: Vec<_> = iter::repeat($y).take(size).collect();
+                    let z: Vec<_> = iter::repeat($z).take(size).collect();
+
+                    assert_eq!(super::$func(&x, &y), Ok(z));
+
+                    let y: Vec<_> = iter::repeat($y).take(size + 1).collect();
+                    let err = super::$func(&x, &y).unwrap_err();
+                    assert_eq!((err.func, err.op, err.left, err.right),
+                               (stringify!($func), stringify!($op), size, size + 1));
+                }
+            }
+        };
+        (scalar $func: ident, $x:expr, $y:expr, $z:expr) => {
+            #[test]
+            fn $func() {
+                for size in 0usize..10 {
+                    let mut x: Vec<_> = iter::repeat($x).take(size).collect();
+                    let z: Vec<_> = iter::repeat($z).take(size).collect();
+
+                    assert_eq!(super::$func(&mut x, $y), Ok(()));
+
+                    assert_eq!(x, z);
+                }
+            }
+        };
+        (new scalar $func: ident, $x:expr, $y:expr, $z:expr) => {
+            #[test]
+            fn $func() {
+                for size in 0usize..10 {
+                    let x: Vec<_> = iter::repeat($x).take(size).collect();
+                    let z: Vec<_> = iter::repeat($z).take(size).collect();
+
+                    assert_eq!(super::$func(&x, $y), Ok(z));
+                }
+            }
+        };
     }
 
     // Test `add_assign`, `mul_assign` and `sub_assign`
-    test!(add_assign, 1u32, 2u32, 3u32);
-    test!(mul_assign, 2u32, 3u32, 6u32);
-    test!(sub_assign, 3u32, 2u32, 1u32);
+    test!(add_assign, +=, 1u32, 2u32, 3u32);
+    test!(mul_assign, *=, 2u32, 3u32, 6u32);
+    test!(sub_assign, -=, 3u32, 2u32, 1u32);
+    test!(div_assign, /=, 6u32, 3u32, 2u32);
+    test!(rem_assign, %=, 7u32, 4u32, 3u32);
+    test!(bitand_assign, &=, 6u32, 3u32, 2u32);
+    test!(bitor_assign, |=, 6u32, 3u32, 7u32);
+    test!(bitxor_assign, ^=, 6u32, 3u32, 5u32);
+    test!(shl_assign, <<=, 1u32, 3u32, 8u32);
+    test!(shr_assign, >>=, 16u32, 2u32, 4u32);
+
+    test!(new add, +, 1u32, 2u32, 3u32);
+    test!(new mul, *, 2u32, 3u32, 6u32);
+    test!(new sub, -, 3u32, 2u32, 1u32);
+    test!(new div, /, 6u32, 3u32, 2u32);
+    test!(new rem, %, 7u32, 4u32, 3u32);
+    test!(new bitand, &, 6u32, 3u32, 2u32);
+    test!(new bitor, |, 6u32, 3u32, 7u32);
+    test!(new bitxor, ^, 6u32, 3u32, 5u32);
+    test!(new shl, <<, 1u32, 3u32, 8u32);
+    test!(new shr, >>, 16u32, 2u32, 4u32);
+
+    test!(scalar add_assign_scalar, 1u32, 2u32, 3u32);
+    test!(scalar mul_assign_scalar, 2u32, 3u32, 6u32);
+    test!(scalar sub_assign_scalar, 3u32, 2u32, 1u32);
+    test!(scalar div_assign_scalar, 6u32, 3u32, 2u32);
+    test!(scalar rem_assign_scalar, 7u32, 4u32, 3u32);
+    test!(scalar bitand_assign_scalar, 6u32, 3u32, 2u32);
+    test!(scalar bitor_assign_scalar, 6u32, 3u32, 7u32);
+    test!(scalar bitxor_assign_scalar, 6u32, 3u32, 5u32);
+    test!(scalar shl_assign_scalar, 1u32, 3u32, 8u32);
+    test!(scalar shr_assign_scalar, 16u32, 2u32, 4u32);
+
+    test!(new scalar add_scalar, 1u32, 2u32, 3u32);
+    test!(new scalar mul_scalar, 2u32, 3u32, 6u32);
+    test!(new scalar sub_scalar, 3u32, 2u32, 1u32);
+    test!(new scalar div_scalar, 6u32, 3u32, 2u32);
+    test!(new scalar rem_scalar, 7u32, 4u32, 3u32);
+    test!(new scalar bitand_scalar, 6u32, 3u32, 2u32);
+    test!(new scalar bitor_scalar, 6u32, 3u32, 7u32);
+    test!(new scalar bitxor_scalar, 6u32, 3u32, 5u32);
+    test!(new scalar shl_scalar, 1u32, 3u32, 8u32);
+    test!(new scalar shr_scalar, 16u32, 2u32, 4u32);
+
+    #[test]
+    fn mismatch_display() {
+        let err = super::add_assign(&mut [1u32, 2], &[1u32]).unwrap_err();
+        assert_eq!(err.to_string(), "add_assign: dimension mismatch: (2,) += (1,)");
+    }
 }
 
+// `cargo run` uses every generated function and shows the error path;
+// `cargo test` (or `rustc --test`) runs the generated tests.
+#[cfg(not(test))]
+fn main() {
+    let xs = [12u32, 7, 5];
+    let ys = [3u32, 2, 1];
+
+    // For each operator: xs op ys, both ways, then xs op 2, both ways.
+    macro_rules! show {
+        ($($new:ident $assign:ident $scalar:ident $assign_scalar:ident),*) => ($(
+            let mut assigned = xs.to_vec();
+            let mut assigned_scalar = xs.to_vec();
+            match ($new(&xs, &ys), $assign(&mut assigned, &ys),
+                   $scalar(&xs, 2), $assign_scalar(&mut assigned_scalar, 2)) {
+                (Ok(v), Ok(()), Ok(s), Ok(())) => println!("{:<7} {:?} {:?} {:?} {:?}",
+                                                           stringify!($new), v, assigned,
+                                                           s, assigned_scalar),
+                (Err(e), ..) | (_, Err(e), ..) | (.., Err(e), _) | (.., Err(e)) => {
+                    println!("{}", e)
+                }
+            }
+        )*)
+    }
+
+    show!(add add_assign add_scalar add_assign_scalar,
+          sub sub_assign sub_scalar sub_assign_scalar,
+          mul mul_assign mul_scalar mul_assign_scalar,
+          div div_assign div_scalar div_assign_scalar,
+          rem rem_assign rem_scalar rem_assign_scalar,
+          bitand bitand_assign bitand_scalar bitand_assign_scalar,
+          bitor bitor_assign bitor_scalar bitor_assign_scalar,
+          bitxor bitxor_assign bitxor_scalar bitxor_assign_scalar,
+          shl shl_assign shl_scalar shl_assign_scalar,
+          shr shr_assign shr_scalar shr_assign_scalar);
+
+    match mul(&xs, &[2, 2]) {
+        Ok(v) => println!("mul: {:?}", v),
+        Err(e) => println!("{}", e),
+    }
+}
@@ -0,0 +1,295 @@
+/*
+`create_function!` takes an `ident` and generates a function with that name.
+`builder!` goes further: it takes a whole struct definition and generates the
+struct, a builder for it with one chained setter per field, and a `build()`
+that checks the result.
+
+builder! {
+    #[derive(Debug)]
+    pub struct CommandOptions => CommandOptionsBuilder {
+        program: String,
+        #[default(Vec::new())]
+        args: Vec<String>,
+        #[default(30)]
+        #[check(positive)]
+        timeout_secs: u64,
+    }
+}
+
+let options = CommandOptions::builder()
+    .program("rustc".to_string())
+    .args(vec!["--version".to_string()])
+    .build()?;
+
+Each setter takes the field's own type, so `timeout_secs(5)` needs no suffix.
+
+A field with no `#[default(...)]` is required. `#[default]` alone uses
+`Default::default()`. `#[check(f)]` runs `f(&value)` on the final value
+(default or not) and rejects it on `Err`. `f` is any closure or function
+from `&T` to `Result<(), E>` where E: ToString. Any other attribute on a
+field, a `///` doc comment or an `#[allow(...)]`, is left on the field of the
+generated struct.
+
+`build()` returns `Err(BuilderError)` naming *every* missing required field,
+or if none are missing, every field whose check failed.
+
+macro_rules! has no way to glue `CommandOptions` and `Builder` into a new
+identifier, which is why the builder's name is given after the `=>`.
+*/
+
+use std::fmt;
+
+// What `build()` found wrong. Shared by every struct `builder!` generates.
+#[derive(Debug, PartialEq)]
+pub struct BuilderError {
+    pub missing: Vec<&'static str>,
+    // (field, reason) for each field whose check failed.
+    pub invalid: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.missing.is_empty() {
+            write!(f, "missing required fields: {}", self.missing.join(", "))
+        } else {
+            let reasons: Vec<String> = self.invalid
+                .iter()
+                .map(|&(field, ref reason)| format!("{}: {}", field, reason))
+                .collect();
+            write!(f, "invalid fields: {}", reasons.join(", "))
+        }
+    }
+}
+
+macro_rules! builder {
+    // The value of a field, or `None` after recording it as missing. The
+    // field's attributes are walked looking for a default.
+    (@value $missing:ident, $name:expr, $slot:expr, []) => (
+        match $slot {
+            Some(v) => Some(v),
+            None => {
+                $missing.push($name);
+                None
+            }
+        }
+    );
+    (@value $missing:ident, $name:expr, $slot:expr, [default ($default:expr) $($rest:tt)*]) => (
+        Some($slot.unwrap_or_else(|| $default))
+    );
+    (@value $missing:ident, $name:expr, $slot:expr, [default $($rest:tt)*]) => (
+        Some($slot.unwrap_or_default())
+    );
+    (@value $missing:ident, $name:expr, $slot:expr, [check ($check:expr) $($rest:tt)*]) => (
+        builder!(@value $missing, $name, $slot, [$($rest)*])
+    );
+
+    // Run each `#[check(...)]` of a field against its value.
+    (@check $invalid:ident, $name:expr, $value:expr, []) => ();
+    (@check $invalid:ident, $name:expr, $value:expr, [check ($check:expr) $($rest:tt)*]) => (
+        if let Err(e) = ($check)($value) {
+            $invalid.push(($name, e.to_string()));
+        }
+        builder!(@check $invalid, $name, $value, [$($rest)*]);
+    );
+    (@check $invalid:ident, $name:expr, $value:expr, [default ($default:expr) $($rest:tt)*]) => (
+        builder!(@check $invalid, $name, $value, [$($rest)*]);
+    );
+    (@check $invalid:ident, $name:expr, $value:expr, [default $($rest:tt)*]) => (
+        builder!(@check $invalid, $name, $value, [$($rest)*]);
+    );
+
+    // Sort each field's attributes: `#[default]` and `#[check]` are the
+    // builder's, and everything else, doc comments included, goes on the
+    // field. One attribute or field at a time, collecting finished fields
+    // in `[$($done)*]` and the current field's attributes in `[$($kept)*]`
+    // and `[$($battr)*]`.
+    (@parse $meta:tt $vis:tt $name:ident $builder:ident [$($done:tt)*] [$($kept:tt)*] [$($battr:tt)*]
+            [#[default $($arg:tt)*] $($rest:tt)*]) => (
+        builder!(@parse $meta $vis $name $builder [$($done)*] [$($kept)*] [$($battr)* default $($arg)*]
+                 [$($rest)*]);
+    );
+    (@parse $meta:tt $vis:tt $name:ident $builder:ident [$($done:tt)*] [$($kept:tt)*] [$($battr:tt)*]
+            [#[check $($arg:tt)*] $($rest:tt)*]) => (
+        builder!(@parse $meta $vis $name $builder [$($done)*] [$($kept)*] [$($battr)* check $($arg)*]
+                 [$($rest)*]);
+    );
+    (@parse $meta:tt $vis:tt $name:ident $builder:ident [$($done:tt)*] [$($kept:tt)*] [$($battr:tt)*]
+            [#[$($other:tt)*] $($rest:tt)*]) => (
+        builder!(@parse $meta $vis $name $builder [$($done)*] [$($kept)* #[$($other)*]] [$($battr)*]
+                 [$($rest)*]);
+    );
+    (@parse $meta:tt $vis:tt $name:ident $builder:ident [$($done:tt)*] [$($kept:tt)*] [$($battr:tt)*]
+            [$fvis:vis $field:ident : $ty:ty, $($rest:tt)*]) => (
+        builder!(@parse $meta $vis $name $builder
+                 [$($done)* { [$($kept)*] [$fvis] $field [$ty] [$($battr)*] }] [] [] [$($rest)*]);
+    );
+    (@parse $meta:tt $vis:tt $name:ident $builder:ident [$($done:tt)*] [$($kept:tt)*] [$($battr:tt)*]
+            [$fvis:vis $field:ident : $ty:ty]) => (
+        builder!(@parse $meta $vis $name $builder
+                 [$($done)* { [$($kept)*] [$fvis] $field [$ty] [$($battr)*] }] [] [] []);
+    );
+
+    // Every field sorted: generate the code.
+    (@parse [$(#[$meta:meta])*] [$vis:vis] $name:ident $builder:ident
+            [$({ [$($fattr:tt)*] [$fvis:vis] $field:ident [$ty:ty] [$($attr:tt)*] })*] [] [] []) => (
+        $(#[$meta])*
+        $vis struct $name {
+            $($($fattr)* $fvis $field: $ty,)*
+        }
+
+        // Every field starts out unset.
+        #[derive(Default)]
+        $vis struct $builder {
+            $($field: Option<$ty>,)*
+        }
+
+        impl $name {
+            #[allow(dead_code)]
+            $vis fn builder() -> $builder {
+                $builder::default()
+            }
+        }
+
+        #[allow(dead_code)]
+        impl $builder {
+            $(
+                pub fn $field(mut self, value: $ty) -> $builder {
+                    self.$field = Some(value);
+                    self
+                }
+            )*
+
+            pub fn build(self) -> Result<$name, $crate::BuilderError> {
+                // Never pushed to when every field has a default.
+                #[allow(unused_mut)]
+                let mut missing = Vec::new();
+                $(
+                    let $field = builder!(@value missing, stringify!($field), self.$field,
+                                          [$($attr)*]);
+                )*
+                if !missing.is_empty() {
+                    return Err($crate::BuilderError { missing, invalid: Vec::new() });
+                }
+                // Nothing is missing, so every `Option` is now `Some`.
+                $(let $field = $field.unwrap();)*
+
+                let mut invalid: Vec<(&'static str, String)> = Vec::new();
+                $(
+                    builder!(@check invalid, stringify!($field), &$field,
+                             [$($attr)*]);
+                )*
+                if !invalid.is_empty() {
+                    return Err($crate::BuilderError { missing: Vec::new(), invalid });
+                }
+
+                Ok($name { $($field),* })
+            }
+        }
+    );
+
+    ($(#[$meta:meta])* $vis:vis struct $name:ident => $builder:ident { $($body:tt)* }) => (
+        builder!(@parse [$(#[$meta])*] [$vis] $name $builder [] [] [] [$($body)*]);
+    );
+}
+
+fn positive(n: &u64) -> Result<(), &'static str> {
+    if *n > 0 { Ok(()) } else { Err("must be positive") }
+}
+
+builder! {
+    // Options for running a child process, as with `std::process::Command`.
+    #[derive(Debug)]
+    pub struct CommandOptions => CommandOptionsBuilder {
+        pub program: String,
+        #[default(Vec::new())]
+        pub args: Vec<String>,
+        #[default]
+        pub env: Vec<(String, String)>,
+        #[default(".".to_string())]
+        #[check(|dir: &String| if dir.is_empty() { Err("empty directory") } else { Ok(()) })]
+        pub current_dir: String,
+        #[default(30)]
+        #[check(positive)]
+        pub timeout_secs: u64,
+    }
+}
+
+fn main() {
+    let options = CommandOptions::builder()
+        .program("rustc".to_string())
+        .args(vec!["--version".to_string()])
+        .build();
+    println!("{:?}", options);
+
+    // The required `program` was never set.
+    match CommandOptions::builder().timeout_secs(5).build() {
+        Ok(o) => println!("{:?}", o),
+        Err(e) => println!("error: {}", e),
+    }
+
+    // Both checks fail.
+    match CommandOptions::builder().program("ls".to_string()).current_dir(String::new()).timeout_secs(0).build() {
+        Ok(o) => println!("{:?}", o),
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BuilderError;
+
+    builder! {
+        #[derive(Debug, PartialEq)]
+        struct Point => PointBuilder {
+            x: i32,
+            y: i32,
+            #[default(1)]
+            #[check(|z: &i32| if *z != 0 { Ok(()) } else { Err("zero") })]
+            z: i32,
+        }
+    }
+
+    builder! {
+        /// A field with doc comments and one with a lint attribute.
+        #[derive(Debug)]
+        struct Documented => DocumentedBuilder {
+            /// How many, at least one.
+            #[default(1)]
+            #[check(|n: &u32| if *n > 0 { Ok(()) } else { Err("zero") })]
+            count: u32,
+            // Never read: without the `allow` on the field, the test build
+            // would warn.
+            #[allow(dead_code)]
+            /// Room for later.
+            #[doc = "More documentation."]
+            #[default]
+            scratch: Vec<u8>
+        }
+    }
+
+    #[test]
+    fn keeps_other_attributes() {
+        assert_eq!(Documented::builder().build().map(|d| d.count), Ok(1));
+        let e = Documented::builder().count(0).scratch(vec![1]).build().unwrap_err();
+        assert_eq!(e.invalid, vec![("count", "zero".to_string())]);
+    }
+
+    #[test]
+    fn builds_with_defaults() {
+        let p = Point::builder().x(1).y(2).build();
+        assert_eq!(p, Ok(Point { x: 1, y: 2, z: 1 }));
+    }
+
+    #[test]
+    fn names_every_missing_field() {
+        let e = Point::builder().z(3).build().unwrap_err();
+        assert_eq!(e.missing, vec!["x", "y"]);
+        assert_eq!(e.to_string(), "missing required fields: x, y");
+    }
+
+    #[test]
+    fn runs_checks() {
+        let e = Point::builder().x(1).y(2).z(0).build().unwrap_err();
+        assert_eq!(e, BuilderError { missing: vec![], invalid: vec![("z", "zero".to_string())] });
+    }
+}
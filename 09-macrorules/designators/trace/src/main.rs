@@ -0,0 +1,223 @@
+/*
+`print_result!` in the designators example prints `stringify!(expr) = value`.
+`trace!` does the same, and also records where the expression is (file, line
+and module), how long it took, and how deeply it is nested inside other
+traced expressions. `trace_fn!` wraps a whole function, recording when it is
+entered and when it returns.
+
+trace_fn! {
+    fn factorial(n: u64) -> u64 {
+        if n == 0 { 1 } else { trace!(n * factorial(n - 1)) }
+    }
+}
+
+Output is indented by nesting depth. An expression is reported once it has
+been evaluated, so anything traced inside it is listed first, one level
+deeper. `factorial(1)` prints:
+
+-> factorial [trace src/main.rs:74]
+    -> factorial [trace src/main.rs:74]
+    <- factorial (1.2µs)
+  n * factorial(n - 1) = 1 (9.8µs) [trace src/main.rs:76]
+<- factorial (15.1µs)
+
+Events go to a `Sink`, one per thread: stderr by default, or a file, or an
+in-memory buffer for tests (see trace.rs).
+
+All of this only exists with the `tracing` cargo feature:
+
+cargo run --features tracing
+
+Without it, `trace!(e)` expands to `e` passed through a function that does
+nothing, and `trace_fn!` to just the function, so there is nothing left to
+cost anything at run time. Each macro takes the same input either way:
+`trace!` asks for `Debug` even when there is nothing to print.
+*/
+
+#[cfg(feature = "tracing")]
+mod trace;
+
+#[cfg(feature = "tracing")]
+macro_rules! trace {
+    ($expression:expr) => ({
+        let span = $crate::trace::Span::expr(file!(), line!(), module_path!(),
+                                             stringify!($expression));
+        span.value($expression)
+    })
+}
+
+// `Span::value` needs `Debug`, and so does this, so that a `trace!` that
+// builds without the feature builds with it.
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace {
+    ($expression:expr) => ({
+        #[inline(always)]
+        fn debug<T: ::std::fmt::Debug>(value: T) -> T {
+            value
+        }
+        debug($expression)
+    })
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! trace_fn {
+    ($(#[$meta:meta])*
+     $vis:vis fn $name:ident($($arg:ident : $ty:ty),* $(,)?) -> $ret:ty $body:block) => (
+        $(#[$meta])*
+        $vis fn $name($($arg: $ty),*) -> $ret {
+            let _span = $crate::trace::Span::function(file!(), line!(), module_path!(),
+                                                      stringify!($name));
+            $body
+        }
+    );
+    ($(#[$meta:meta])*
+     $vis:vis fn $name:ident($($arg:ident : $ty:ty),* $(,)?) $body:block) => (
+        trace_fn!($(#[$meta])* $vis fn $name($($arg: $ty),*) -> () $body);
+    );
+}
+
+// The same two forms as above, so a `trace_fn!` that builds with the
+// feature builds without it, and the other way round.
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_fn {
+    ($(#[$meta:meta])*
+     $vis:vis fn $name:ident($($arg:ident : $ty:ty),* $(,)?) -> $ret:ty $body:block) => (
+        $(#[$meta])*
+        $vis fn $name($($arg: $ty),*) -> $ret $body
+    );
+    ($(#[$meta:meta])*
+     $vis:vis fn $name:ident($($arg:ident : $ty:ty),* $(,)?) $body:block) => (
+        $(#[$meta])*
+        $vis fn $name($($arg: $ty),*) $body
+    );
+}
+
+trace_fn! {
+    fn factorial(n: u64) -> u64 {
+        if n == 0 { 1 } else { trace!(n * factorial(n - 1)) }
+    }
+}
+
+trace_fn! {
+    fn greet(name: &str) {
+        println!("Hello, {}!", trace!(name.to_uppercase()));
+    }
+}
+
+fn main() {
+    // The two `print_result!` examples.
+    println!("{}", trace!(1u32 + 1));
+    println!("{}", trace!({
+        let x = 1u32;
+
+        x * x + 2 * x - 1
+    }));
+
+    // Nested expressions.
+    let total = trace!(trace!(2 * 3) + trace!(4 * 5));
+    println!("total: {}", total);
+
+    factorial(3);
+    greet("world");
+
+    #[cfg(feature = "tracing")]
+    {
+        // Send the rest of this thread's events to a file instead.
+        let path = ::std::env::temp_dir().join("trace.log");
+        match trace::FileSink::create(&path) {
+            Ok(sink) => {
+                let stderr = trace::set_sink(Box::new(sink));
+                factorial(5);
+                // Put stderr back, which also drops and flushes the file.
+                trace::set_sink(stderr);
+                println!("factorial(5) was traced to {}", path.display());
+            }
+            Err(e) => println!("can not trace to {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::factorial;
+
+    #[test]
+    fn values_pass_through() {
+        assert_eq!(trace!(2 + 2), 4);
+        assert_eq!(trace!({ let s = "ab"; s.len() }), 2);
+        assert_eq!(factorial(5), 120);
+    }
+
+    trace_fn! {
+        // Attributes, visibility and a trailing comma, with and without a
+        // return type.
+        #[inline]
+        pub(crate) fn add(a: i32, b: i32,) -> i32 {
+            a + b
+        }
+    }
+
+    trace_fn! {
+        fn push(v: &mut Vec<i32>, x: i32) {
+            v.push(trace!(x * 2));
+        }
+    }
+
+    // Only `Debug`: not `Clone`, not `Display`.
+    #[derive(Debug, PartialEq)]
+    struct Opaque(Vec<u8>);
+
+    // Run both with and without `--features tracing`. Taking the `Debug`
+    // off `Opaque` breaks both builds.
+    #[test]
+    fn values_need_only_debug() {
+        let moved = trace!(Opaque(vec![1, 2]));
+        assert_eq!(moved, Opaque(vec![1, 2]));
+        assert_eq!(trace!(&moved).0.len(), 2);
+    }
+
+    // Run both with and without `--features tracing`.
+    #[test]
+    fn functions_build_both_ways() {
+        let mut v = Vec::new();
+        push(&mut v, add(1, 2));
+        assert_eq!(v, [6]);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn records_nesting() {
+        use trace::{set_sink, Kind, MemorySink};
+
+        let sink = MemorySink::new();
+        set_sink(Box::new(sink.clone()));
+        factorial(1);
+        let events: Vec<_> = sink.events()
+            .iter()
+            .map(|e| (e.kind, e.depth, e.text))
+            .collect();
+
+        assert_eq!(events, vec![
+            (Kind::Enter, 0, "factorial"),
+            (Kind::Enter, 2, "factorial"),
+            (Kind::Exit, 2, "factorial"),
+            (Kind::Value, 1, "n * factorial(n - 1)"),
+            (Kind::Exit, 0, "factorial"),
+        ]);
+        assert_eq!(sink.events()[3].value, Some("1".to_string()));
+        assert_eq!(sink.events()[3].module, "trace");
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn depth_is_restored_after_a_panic() {
+        use std::panic;
+        use trace::{set_sink, MemorySink};
+
+        let sink = MemorySink::new();
+        set_sink(Box::new(sink.clone()));
+        let _ = panic::catch_unwind(|| trace!(if true { panic!("boom") } else { 0 }));
+        trace!(1);
+        assert_eq!(sink.events()[0].depth, 0);
+    }
+}
@@ -0,0 +1,199 @@
+// trace.rs
+// Run time support for `trace!` and `trace_fn!`. Only compiled with the
+// `tracing` feature.
+//
+// Each thread has its own nesting depth and its own sink, so that tests
+// running in parallel each see only their own events.
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    // A function was entered.
+    Enter,
+    // A function returned (or unwound).
+    Exit,
+    // An expression was evaluated.
+    Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub kind: Kind,
+    pub file: &'static str,
+    pub line: u32,
+    pub module: &'static str,
+    // How many traced expressions and functions enclose this one.
+    pub depth: usize,
+    // The expression's source, or the function's name.
+    pub text: &'static str,
+    // The `Debug` form of the value, for `Kind::Value`.
+    pub value: Option<String>,
+    // How long it took, for `Kind::Value` and `Kind::Exit`.
+    pub elapsed: Option<Duration>,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = "  ".repeat(self.depth);
+        match self.kind {
+            Kind::Enter => write!(f, "{}-> {} [{} {}:{}]",
+                                  indent, self.text, self.module, self.file, self.line),
+            Kind::Exit => write!(f, "{}<- {} ({:?})",
+                                 indent, self.text, self.elapsed.unwrap_or_default()),
+            Kind::Value => write!(f, "{}{} = {} ({:?}) [{} {}:{}]",
+                                  indent,
+                                  self.text,
+                                  self.value.as_ref().map_or("?", |v| &v[..]),
+                                  self.elapsed.unwrap_or_default(),
+                                  self.module,
+                                  self.file,
+                                  self.line),
+        }
+    }
+}
+
+// Where events go.
+pub trait Sink {
+    fn record(&mut self, event: &Event);
+}
+
+// The default sink.
+pub struct StderrSink;
+
+impl Sink for StderrSink {
+    fn record(&mut self, event: &Event) {
+        eprintln!("{}", event);
+    }
+}
+
+// Appends one line per event to a file.
+pub struct FileSink {
+    out: BufWriter<File>,
+}
+
+impl FileSink {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<FileSink> {
+        Ok(FileSink { out: BufWriter::new(File::create(path)?) })
+    }
+}
+
+impl Sink for FileSink {
+    // A failed write is dropped: tracing must never change the outcome of
+    // the program it traces.
+    fn record(&mut self, event: &Event) {
+        let _ = writeln!(self.out, "{}", event);
+    }
+}
+
+// Keeps events in memory, for tests. Clones share the same buffer, so keep
+// one clone to read back what the installed one recorded.
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub struct MemorySink {
+    events: Rc<RefCell<Vec<Event>>>,
+}
+
+#[allow(dead_code)]
+impl MemorySink {
+    pub fn new() -> MemorySink {
+        MemorySink::default()
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+}
+
+impl Sink for MemorySink {
+    fn record(&mut self, event: &Event) {
+        self.events.borrow_mut().push(event.clone());
+    }
+}
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static SINK: RefCell<Box<dyn Sink>> = RefCell::new(Box::new(StderrSink));
+}
+
+// Sends this thread's events to `sink`, returning the previous sink.
+pub fn set_sink(sink: Box<dyn Sink>) -> Box<dyn Sink> {
+    SINK.with(|s| ::std::mem::replace(&mut *s.borrow_mut(), sink))
+}
+
+fn record(event: Event) {
+    SINK.with(|s| s.borrow_mut().record(&event));
+}
+
+// One level of nesting, from construction to drop. A drop guard rather than
+// a pair of calls, so that the depth is restored even if the traced code
+// panics.
+pub struct Span {
+    kind: Kind,
+    file: &'static str,
+    line: u32,
+    module: &'static str,
+    depth: usize,
+    text: &'static str,
+    start: Instant,
+}
+
+impl Span {
+    // Used by `trace!`: nothing is recorded until `value`.
+    pub fn expr(file: &'static str, line: u32, module: &'static str, text: &'static str) -> Span {
+        Span::new(Kind::Value, file, line, module, text)
+    }
+
+    // Used by `trace_fn!`: records the enter event now and the exit event
+    // on drop.
+    pub fn function(file: &'static str, line: u32, module: &'static str, text: &'static str)
+                    -> Span {
+        let span = Span::new(Kind::Enter, file, line, module, text);
+        record(span.event(Kind::Enter, None, None));
+        span
+    }
+
+    fn new(kind: Kind, file: &'static str, line: u32, module: &'static str, text: &'static str)
+           -> Span {
+        let depth = DEPTH.with(|d| {
+            let depth = d.get();
+            d.set(depth + 1);
+            depth
+        });
+        Span { kind, file, line, module, depth, text, start: Instant::now() }
+    }
+
+    fn event(&self, kind: Kind, value: Option<String>, elapsed: Option<Duration>) -> Event {
+        Event {
+            kind,
+            file: self.file,
+            line: self.line,
+            module: self.module,
+            depth: self.depth,
+            text: self.text,
+            value,
+            elapsed,
+        }
+    }
+
+    // Records the value of a traced expression, and passes it through.
+    pub fn value<T: fmt::Debug>(self, value: T) -> T {
+        let elapsed = self.start.elapsed();
+        record(self.event(Kind::Value, Some(format!("{:?}", value)), Some(elapsed)));
+        value
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(self.depth));
+        if self.kind == Kind::Enter {
+            record(self.event(Kind::Exit, None, Some(self.start.elapsed())));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// expr.rs
+// The formula AST, evaluation, and truth tables.
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Const(bool),
+    Var(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Implies(Box<Expr>, Box<Expr>),
+    Iff(Box<Expr>, Box<Expr>),
+}
+
+use self::Expr::*;
+
+impl Expr {
+    // `value_of` gives the value of each variable.
+    pub fn eval<F: Fn(&str) -> bool>(&self, value_of: &F) -> bool {
+        match *self {
+            Const(b) => b,
+            Var(ref name) => value_of(name),
+            Not(ref e) => !e.eval(value_of),
+            And(ref l, ref r) => l.eval(value_of) && r.eval(value_of),
+            Or(ref l, ref r) => l.eval(value_of) || r.eval(value_of),
+            Xor(ref l, ref r) => l.eval(value_of) != r.eval(value_of),
+            Implies(ref l, ref r) => !l.eval(value_of) || r.eval(value_of),
+            Iff(ref l, ref r) => l.eval(value_of) == r.eval(value_of),
+        }
+    }
+
+    // The variables, in order of first appearance.
+    pub fn vars(&self) -> Vec<String> {
+        let mut vars = Vec::new();
+        self.collect_vars(&mut vars);
+        vars
+    }
+
+    fn collect_vars(&self, vars: &mut Vec<String>) {
+        match *self {
+            Const(_) => {}
+            Var(ref name) => {
+                if !vars.contains(name) {
+                    vars.push(name.clone());
+                }
+            }
+            Not(ref e) => e.collect_vars(vars),
+            And(ref l, ref r) | Or(ref l, ref r) | Xor(ref l, ref r) |
+            Implies(ref l, ref r) | Iff(ref l, ref r) => {
+                l.collect_vars(vars);
+                r.collect_vars(vars);
+            }
+        }
+    }
+
+    // The value on row `row` of the truth table over `vars`. The first
+    // variable is the most significant bit of `row`.
+    pub fn eval_row(&self, vars: &[String], row: u32) -> bool {
+        let n = vars.len();
+        self.eval(&|name: &str| {
+            let i = vars.iter().position(|v| v == name).expect("unknown variable");
+            (row >> (n - 1 - i)) & 1 == 1
+        })
+    }
+
+    // The rows on which the formula is true.
+    pub fn minterms(&self, vars: &[String]) -> Vec<u32> {
+        rows(vars).filter(|&row| self.eval_row(vars, row)).collect()
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        let vars = self.vars();
+        rows(&vars).any(|row| self.eval_row(&vars, row))
+    }
+
+    pub fn is_tautology(&self) -> bool {
+        let vars = self.vars();
+        rows(&vars).all(|row| self.eval_row(&vars, row))
+    }
+
+    // True when both formulas agree on every assignment of their variables.
+    pub fn equivalent(&self, other: &Expr) -> bool {
+        Iff(Box::new(self.clone()), Box::new(other.clone())).is_tautology()
+    }
+
+    pub fn truth_table(&self) -> TruthTable<'_> {
+        TruthTable { expr: self, vars: self.vars() }
+    }
+
+    // Binding strength, for printing with as few parentheses as possible.
+    fn precedence(&self) -> u8 {
+        match *self {
+            Iff(..) => 0,
+            Implies(..) => 1,
+            Or(..) => 2,
+            Xor(..) => 3,
+            And(..) => 4,
+            Not(..) => 5,
+            Const(_) | Var(_) => 6,
+        }
+    }
+
+    fn fmt_at(&self, f: &mut fmt::Formatter, min: u8) -> fmt::Result {
+        let p = self.precedence();
+        if p < min {
+            write!(f, "(")?;
+        }
+        match *self {
+            Const(b) => write!(f, "{}", b)?,
+            Var(ref name) => write!(f, "{}", name)?,
+            Not(ref e) => {
+                write!(f, "not ")?;
+                e.fmt_at(f, p)?;
+            }
+            // Right associative: `a implies b implies c` is
+            // `a implies (b implies c)`.
+            Implies(ref l, ref r) => {
+                l.fmt_at(f, p + 1)?;
+                write!(f, " implies ")?;
+                r.fmt_at(f, p)?;
+            }
+            // The rest are left associative.
+            And(ref l, ref r) | Or(ref l, ref r) | Xor(ref l, ref r) | Iff(ref l, ref r) => {
+                let op = match *self {
+                    And(..) => "and",
+                    Or(..) => "or",
+                    Xor(..) => "xor",
+                    _ => "iff",
+                };
+                l.fmt_at(f, p)?;
+                write!(f, " {} ", op)?;
+                r.fmt_at(f, p + 1)?;
+            }
+        }
+        if p < min {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_at(f, 0)
+    }
+}
+
+// Every row of the truth table over `vars`. Rows are numbered with a
+// `u32`, so there can be at most 31 variables.
+fn rows(vars: &[String]) -> Range<u32> {
+    assert!(vars.len() < 32, "too many variables for a truth table");
+    0..1u32 << vars.len()
+}
+
+pub struct TruthTable<'a> {
+    expr: &'a Expr,
+    vars: Vec<String>,
+}
+
+impl<'a> fmt::Display for TruthTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for v in &self.vars {
+            write!(f, "{} | ", v)?;
+        }
+        writeln!(f, "{}", self.expr)?;
+
+        let n = self.vars.len();
+        for row in rows(&self.vars) {
+            for (i, v) in self.vars.iter().enumerate() {
+                let bit = if (row >> (n - 1 - i)) & 1 == 1 { "T" } else { "F" };
+                write!(f, "{:width$} | ", bit, width = v.len())?;
+            }
+            let value = if self.expr.eval_row(&self.vars, row) { "T" } else { "F" };
+            writeln!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
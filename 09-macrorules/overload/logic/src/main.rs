@@ -0,0 +1,206 @@
+/*
+The overload example's `test!` takes `expr; and expr` or `expr; or expr`.
+Here the same idea grows into a small boolean logic toolkit, for checking
+which combinations of feature flags make sense.
+
+A formula has named variables, `true`, `false`, parentheses, and from
+loosest to tightest binding: `iff`, `implies`, `or`, `xor`, `and`, `not`.
+It can be written in two ways which build the very same `Expr`:
+
+let a = parser::parse("a and (b or not c)").unwrap();   // at run time
+let b = logic!(a and (b or not c));                      // at compile time
+
+Given a formula we can print its full truth table, decide whether it is
+satisfiable or a tautology, and simplify it to a minimal sum-of-products
+with the Quine-McCluskey method.
+
+The macro is a "token tree muncher". For each precedence level it walks the
+tokens, collecting them into what came before the last operator of that
+level and what came after it. Parenthesized groups are single token trees,
+so operators inside them are never seen until the group is unwrapped. The
+result is split at the last operator (left associative) or, for `implies`,
+the first (right associative), and each side is handed to the next level.
+*/
+
+// Munching a long formula nests deeply.
+#![recursion_limit = "512"]
+
+mod expr;
+mod parser;
+mod qm;
+
+macro_rules! logic {
+    ($($t:tt)+) => (logic_munch!(@iff [] [] $($t)+));
+}
+
+// The internals of `logic!`, kept apart so that a malformed formula stops
+// with "no rules expected the token" instead of looping.
+macro_rules! logic_munch {
+    // `iff`, left associative: split at the last one.
+    (@iff [] [$($c:tt)+] iff $($r:tt)+) => (logic_munch!(@iff [$($c)+] [] $($r)+));
+    (@iff [$($s:tt)+] [$($c:tt)+] iff $($r:tt)+) => (
+        logic_munch!(@iff [$($s)+ iff $($c)+] [] $($r)+)
+    );
+    (@iff [$($s:tt)*] [$($c:tt)*] $t:tt $($r:tt)*) => (
+        logic_munch!(@iff [$($s)*] [$($c)* $t] $($r)*)
+    );
+    (@iff [] [$($c:tt)+]) => (logic_munch!(@implies [] $($c)+));
+    (@iff [$($s:tt)+] [$($c:tt)+]) => (
+        $crate::expr::Expr::Iff(Box::new(logic_munch!(@iff [] [] $($s)+)),
+                                Box::new(logic_munch!(@implies [] $($c)+)))
+    );
+
+    // `implies`, right associative: split at the first one.
+    (@implies [$($c:tt)+] implies $($r:tt)+) => (
+        $crate::expr::Expr::Implies(Box::new(logic_munch!(@or [] [] $($c)+)),
+                                    Box::new(logic_munch!(@implies [] $($r)+)))
+    );
+    (@implies [$($c:tt)*] $t:tt $($r:tt)*) => (logic_munch!(@implies [$($c)* $t] $($r)*));
+    (@implies [$($c:tt)+]) => (logic_munch!(@or [] [] $($c)+));
+
+    // `or`, `xor` and `and` work just like `iff`.
+    (@or [] [$($c:tt)+] or $($r:tt)+) => (logic_munch!(@or [$($c)+] [] $($r)+));
+    (@or [$($s:tt)+] [$($c:tt)+] or $($r:tt)+) => (
+        logic_munch!(@or [$($s)+ or $($c)+] [] $($r)+)
+    );
+    (@or [$($s:tt)*] [$($c:tt)*] $t:tt $($r:tt)*) => (
+        logic_munch!(@or [$($s)*] [$($c)* $t] $($r)*)
+    );
+    (@or [] [$($c:tt)+]) => (logic_munch!(@xor [] [] $($c)+));
+    (@or [$($s:tt)+] [$($c:tt)+]) => (
+        $crate::expr::Expr::Or(Box::new(logic_munch!(@or [] [] $($s)+)),
+                               Box::new(logic_munch!(@xor [] [] $($c)+)))
+    );
+
+    (@xor [] [$($c:tt)+] xor $($r:tt)+) => (logic_munch!(@xor [$($c)+] [] $($r)+));
+    (@xor [$($s:tt)+] [$($c:tt)+] xor $($r:tt)+) => (
+        logic_munch!(@xor [$($s)+ xor $($c)+] [] $($r)+)
+    );
+    (@xor [$($s:tt)*] [$($c:tt)*] $t:tt $($r:tt)*) => (
+        logic_munch!(@xor [$($s)*] [$($c)* $t] $($r)*)
+    );
+    (@xor [] [$($c:tt)+]) => (logic_munch!(@and [] [] $($c)+));
+    (@xor [$($s:tt)+] [$($c:tt)+]) => (
+        $crate::expr::Expr::Xor(Box::new(logic_munch!(@xor [] [] $($s)+)),
+                                Box::new(logic_munch!(@and [] [] $($c)+)))
+    );
+
+    (@and [] [$($c:tt)+] and $($r:tt)+) => (logic_munch!(@and [$($c)+] [] $($r)+));
+    (@and [$($s:tt)+] [$($c:tt)+] and $($r:tt)+) => (
+        logic_munch!(@and [$($s)+ and $($c)+] [] $($r)+)
+    );
+    (@and [$($s:tt)*] [$($c:tt)*] $t:tt $($r:tt)*) => (
+        logic_munch!(@and [$($s)*] [$($c)* $t] $($r)*)
+    );
+    (@and [] [$($c:tt)+]) => (logic_munch!(@not $($c)+));
+    (@and [$($s:tt)+] [$($c:tt)+]) => (
+        $crate::expr::Expr::And(Box::new(logic_munch!(@and [] [] $($s)+)),
+                                Box::new(logic_munch!(@not $($c)+)))
+    );
+
+    // `not`, constants, variables and parentheses.
+    (@not not $($r:tt)+) => ($crate::expr::Expr::Not(Box::new(logic_munch!(@not $($r)+))));
+    (@not true) => ($crate::expr::Expr::Const(true));
+    (@not false) => ($crate::expr::Expr::Const(false));
+    (@not ($($inner:tt)+)) => (logic_munch!(@iff [] [] $($inner)+));
+    // An operator left over where a variable should be, as in `a and or b`
+    // or a bare `not`, is an `ident` too; refuse it as `parse` does.
+    (@not not) => (compile_error!("expected a variable, found `not`"));
+    (@not and) => (compile_error!("expected a variable, found `and`"));
+    (@not or) => (compile_error!("expected a variable, found `or`"));
+    (@not xor) => (compile_error!("expected a variable, found `xor`"));
+    (@not implies) => (compile_error!("expected a variable, found `implies`"));
+    (@not iff) => (compile_error!("expected a variable, found `iff`"));
+    (@not $v:ident) => ($crate::expr::Expr::Var(stringify!($v).to_string()));
+}
+
+fn report(name: &str, formula: &expr::Expr) {
+    println!("{}: {}", name, formula);
+    print!("{}", formula.truth_table());
+    println!("satisfiable: {}, tautology: {}",
+             formula.is_satisfiable(),
+             formula.is_tautology());
+    let simplified = qm::simplify(formula);
+    println!("simplified: {} (equivalent: {})", simplified, simplified.equivalent(formula));
+    println!();
+}
+
+fn main() {
+    // The two `test!` cases from the overload example.
+    report("and", &logic!(true and true));
+    report("or", &logic!(true or false));
+
+    // Feature flags: `tls` needs a crypto backend, and only one backend may
+    // be enabled.
+    let rules = logic!((tls implies (openssl or rustls)) and not (openssl and rustls));
+    report("rules", &rules);
+
+    // The macro and the parser agree.
+    let parsed = parser::parse("(tls implies (openssl or rustls)) and not (openssl and rustls)")
+        .unwrap();
+    println!("macro == parser: {}", parsed == rules);
+
+    match parser::parse("tls and (openssl or") {
+        Ok(e) => println!("parsed {}", e),
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use parser::parse;
+
+    macro_rules! same {
+        ($($t:tt)+) => (
+            assert_eq!(logic!($($t)+), parse(stringify!($($t)+)).unwrap(),
+                       "{}", stringify!($($t)+));
+        )
+    }
+
+    #[test]
+    fn macro_matches_parser() {
+        same!(a);
+        same!(not not a);
+        same!(a and (b or not c));
+        same!(a or b and c xor d);
+        same!(a and b and c or d or e);
+        same!(a implies b implies c iff d iff e);
+        same!(not (a iff b) xor true and false);
+        same!((a) or ((b)));
+        same!(android or notable and origin);
+    }
+
+    // None of these compiles as `logic!(...)` either; most stop with
+    // "expected a variable, found `...`".
+    #[test]
+    fn keywords_are_not_variables() {
+        for input in &["not", "a and or b", "a or and", "xor xor b", "a implies implies", "not iff"] {
+            assert!(parse(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn satisfiability() {
+        assert!(logic!(a and not b).is_satisfiable());
+        assert!(!logic!(a and not a).is_satisfiable());
+        assert!(logic!((a implies b) iff (not b implies not a)).is_tautology());
+        assert!(!logic!(a or b).is_tautology());
+    }
+
+    #[test]
+    #[should_panic(expected = "too many variables")]
+    fn too_many_variables() {
+        let names: Vec<String> = (0..32).map(|i| format!("v{}", i)).collect();
+        parse(&names.join(" or ")).unwrap().is_satisfiable();
+    }
+
+    #[test]
+    fn truth_table() {
+        let table = logic!(a xor b).truth_table().to_string();
+        assert_eq!(table, "a | b | a xor b\n\
+                           F | F | F\n\
+                           F | T | T\n\
+                           T | F | T\n\
+                           T | T | F\n");
+    }
+}
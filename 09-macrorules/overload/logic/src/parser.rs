@@ -0,0 +1,199 @@
+// parser.rs
+// Parses formulas from text. From loosest to tightest binding:
+//
+// iff       left associative
+// implies   right associative
+// or        left associative
+// xor       left associative
+// and       left associative
+// not
+//
+// Variables are identifiers; `true` and `false` are constants. This must
+// build exactly the same tree as the `logic!` macro does.
+use std::fmt;
+
+use expr::Expr;
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    // Byte offset into the input.
+    pub position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.position)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    LParen,
+    RParen,
+}
+
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens: &tokens, pos: 0, end: input.len() };
+    let expr = parser.iff()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(_) => Err(parser.error("unexpected token")),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            tokens.push((Token::LParen, i));
+            chars.next();
+        } else if c == ')' {
+            tokens.push((Token::RParen, i));
+            chars.next();
+        } else if c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    name.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((Token::Ident(name), i));
+        } else {
+            return Err(ParseError { message: format!("unexpected character `{}`", c), position: i });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+    // Reported as the position of errors at the end of input.
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.0)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let position = self.tokens.get(self.pos).map(|t| t.1).unwrap_or(self.end);
+        ParseError { message: message.to_string(), position }
+    }
+
+    // Consumes the keyword `word` if it is next.
+    fn keyword(&mut self, word: &str) -> bool {
+        if let Some(Token::Ident(name)) = self.peek() {
+            if name == word {
+                self.pos += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn iff(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.implies()?;
+        while self.keyword("iff") {
+            left = Expr::Iff(Box::new(left), Box::new(self.implies()?));
+        }
+        Ok(left)
+    }
+
+    fn implies(&mut self) -> Result<Expr, ParseError> {
+        let left = self.or()?;
+        if self.keyword("implies") {
+            Ok(Expr::Implies(Box::new(left), Box::new(self.implies()?)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.xor()?;
+        while self.keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.xor()?));
+        }
+        Ok(left)
+    }
+
+    fn xor(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.and()?;
+        while self.keyword("xor") {
+            left = Expr::Xor(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.not()?;
+        while self.keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, ParseError> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        match self.peek().cloned() {
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let inner = self.iff()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(self.error("expected `)`"));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => {
+                match &name[..] {
+                    "and" | "or" | "xor" | "not" | "implies" | "iff" => {
+                        Err(self.error(&format!("expected a variable, found `{}`", name)))
+                    }
+                    "true" => { self.pos += 1; Ok(Expr::Const(true)) }
+                    "false" => { self.pos += 1; Ok(Expr::Const(false)) }
+                    _ => { self.pos += 1; Ok(Expr::Var(name)) }
+                }
+            }
+            Some(Token::RParen) => Err(self.error("unexpected `)`")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn precedence_and_associativity() {
+        let show = |s: &str| format!("{:?}", parse(s).unwrap());
+        assert_eq!(show("a or b and c"), show("a or (b and c)"));
+        assert_eq!(show("a xor b or c"), show("(a xor b) or c"));
+        assert_eq!(show("a and b and c"), show("(a and b) and c"));
+        assert_eq!(show("a implies b implies c"), show("a implies (b implies c)"));
+        assert_eq!(show("a iff b iff c"), show("(a iff b) iff c"));
+        assert_eq!(show("not a and b"), show("(not a) and b"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("a and").unwrap_err().position, 5);
+        assert_eq!(parse("(a or b").unwrap_err().message, "expected `)`");
+        assert_eq!(parse("a & b").unwrap_err().position, 2);
+        assert_eq!(parse("a b").unwrap_err().message, "unexpected token");
+        assert!(parse("a and or b").is_err());
+    }
+}
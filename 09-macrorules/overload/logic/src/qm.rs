@@ -0,0 +1,197 @@
+// qm.rs
+// Minimal sum-of-products by Quine-McCluskey.
+//
+// 1. Start from the minterms, the truth table rows where the formula holds.
+// 2. Repeatedly merge pairs of terms that differ in exactly one variable,
+//    which then becomes a "don't care". Terms that never merge are the prime
+//    implicants.
+// 3. Pick the fewest primes that cover every minterm: the essential ones
+//    (the only cover of some minterm), then Petrick's method for the rest.
+use std::collections::BTreeSet;
+
+use expr::Expr;
+
+// A product term. A bit set in `dashes` is a variable that does not
+// appear; the same bit in `bits` is always zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Implicant {
+    bits: u32,
+    dashes: u32,
+}
+
+impl Implicant {
+    fn covers(&self, minterm: u32) -> bool {
+        minterm & !self.dashes == self.bits
+    }
+
+    fn literals(&self, n: usize) -> usize {
+        n - self.dashes.count_ones() as usize
+    }
+}
+
+// The minimal sum-of-products equivalent to `expr`, over the same
+// variables. Ties between equally small covers are broken by the number of
+// literals.
+pub fn simplify(expr: &Expr) -> Expr {
+    let vars = expr.vars();
+    let minterms = expr.minterms(&vars);
+    let primes = prime_implicants(&minterms);
+    let cover = minimal_cover(&primes, &minterms, vars.len());
+    to_expr(&cover, &vars)
+}
+
+fn prime_implicants(minterms: &[u32]) -> Vec<Implicant> {
+    let mut current: BTreeSet<Implicant> = minterms
+        .iter()
+        .map(|&m| Implicant { bits: m, dashes: 0 })
+        .collect();
+    let mut primes = BTreeSet::new();
+
+    while !current.is_empty() {
+        let terms: Vec<Implicant> = current.into_iter().collect();
+        let mut merged = vec![false; terms.len()];
+        let mut next = BTreeSet::new();
+
+        for i in 0..terms.len() {
+            for j in i + 1..terms.len() {
+                let (a, b) = (terms[i], terms[j]);
+                let diff = a.bits ^ b.bits;
+                if a.dashes == b.dashes && diff.count_ones() == 1 {
+                    next.insert(Implicant { bits: a.bits & !diff, dashes: a.dashes | diff });
+                    merged[i] = true;
+                    merged[j] = true;
+                }
+            }
+        }
+        for (term, &was_merged) in terms.iter().zip(merged.iter()) {
+            if !was_merged {
+                primes.insert(*term);
+            }
+        }
+        current = next;
+    }
+    primes.into_iter().collect()
+}
+
+fn minimal_cover(primes: &[Implicant], minterms: &[u32], n: usize) -> Vec<Implicant> {
+    let covering = |m: u32| -> Vec<usize> {
+        (0..primes.len()).filter(|&i| primes[i].covers(m)).collect()
+    };
+
+    let mut chosen = BTreeSet::new();
+    for &m in minterms {
+        let c = covering(m);
+        if c.len() == 1 {
+            chosen.insert(c[0]);
+        }
+    }
+    let remaining: Vec<u32> = minterms
+        .iter()
+        .cloned()
+        .filter(|&m| !chosen.iter().any(|&i| primes[i].covers(m)))
+        .collect();
+
+    // Petrick's method: the remaining minterms give a product of sums
+    // ("p1 or p3") and ("p2 or p3") ..., multiplied out into a sum of
+    // products, each of which is a candidate cover. Covers that contain
+    // another cover (or repeat one) are dropped as we go to keep this small.
+    let mut covers: Vec<BTreeSet<usize>> = vec![BTreeSet::new()];
+    for m in remaining {
+        let mut next: Vec<BTreeSet<usize>> = Vec::new();
+        for cover in &covers {
+            for i in covering(m) {
+                let mut c = cover.clone();
+                c.insert(i);
+                next.push(c);
+            }
+        }
+        next.sort_by_key(|c| c.len());
+        let mut kept: Vec<BTreeSet<usize>> = Vec::new();
+        for c in next {
+            if !kept.iter().any(|k| k.is_subset(&c)) {
+                kept.push(c);
+            }
+        }
+        covers = kept;
+    }
+
+    let best = covers
+        .into_iter()
+        .min_by_key(|c| {
+            let literals: usize = c.iter().map(|&i| primes[i].literals(n)).sum();
+            (c.len(), literals)
+        })
+        .unwrap_or_default();
+
+    chosen.extend(best);
+    chosen.into_iter().map(|i| primes[i]).collect()
+}
+
+// Terms are listed with the highest rows of the truth table first, so
+// `a and b` comes before `not a and c`.
+fn to_expr(cover: &[Implicant], vars: &[String]) -> Expr {
+    let n = vars.len();
+    let mut cover = cover.to_vec();
+    cover.sort_by(|x, y| y.bits.cmp(&x.bits).then(x.dashes.cmp(&y.dashes)));
+    let products = cover.iter().map(|term| {
+        let literals = vars.iter().enumerate().filter_map(|(i, v)| {
+            let bit = 1 << (n - 1 - i);
+            if term.dashes & bit != 0 {
+                None
+            } else if term.bits & bit != 0 {
+                Some(Expr::Var(v.clone()))
+            } else {
+                Some(Expr::Not(Box::new(Expr::Var(v.clone()))))
+            }
+        });
+        literals
+            .fold(None, |acc, lit| match acc {
+                None => Some(lit),
+                Some(e) => Some(Expr::And(Box::new(e), Box::new(lit))),
+            })
+            .unwrap_or(Expr::Const(true))
+    });
+    products
+        .fold(None, |acc, p| match acc {
+            None => Some(p),
+            Some(e) => Some(Expr::Or(Box::new(e), Box::new(p))),
+        })
+        .unwrap_or(Expr::Const(false))
+}
+
+#[cfg(test)]
+mod test {
+    use super::simplify;
+    use parser::parse;
+
+    fn simplified(s: &str) -> String {
+        let e = parse(s).unwrap();
+        let m = simplify(&e);
+        assert!(m.equivalent(&e), "{} is not equivalent to {}", m, e);
+        m.to_string()
+    }
+
+    #[test]
+    fn textbook_cases() {
+        assert_eq!(simplified("a and b or a and not b"), "a");
+        assert_eq!(simplified("a and b or not a and c or b and c"), "a and b or not a and c");
+        assert_eq!(simplified("a xor b"), "a and not b or not a and b");
+        assert_eq!(simplified("a implies b"), "b or not a");
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(simplified("a or not a"), "true");
+        assert_eq!(simplified("a and not a"), "false");
+        assert_eq!(simplified("true"), "true");
+    }
+
+    #[test]
+    fn cyclic_cover_needs_petrick() {
+        // No essential primes at all; the minimum takes three of six.
+        let s = simplified("not a and not b and not c or not a and not b and c \
+                            or not a and b and c or a and b and c \
+                            or a and b and not c or a and not b and not c");
+        assert_eq!(s.matches(" or ").count(), 2);
+    }
+}
@@ -21,10 +21,32 @@ macro_rules! test {
                  stringify!($right),
                  $left || $right)
     );
+    // The rest of the connectives handled by the `logic` example.
+    ($left:expr; xor $right:expr) => (
+        println!("{:?} xor {:?} is {:?}",
+                 stringify!($left),
+                 stringify!($right),
+                 ($left) != ($right))
+    );
+    ($left:expr; implies $right:expr) => (
+        println!("{:?} implies {:?} is {:?}",
+                 stringify!($left),
+                 stringify!($right),
+                 !($left) || ($right))
+    );
+    ($left:expr; iff $right:expr) => (
+        println!("{:?} iff {:?} is {:?}",
+                 stringify!($left),
+                 stringify!($right),
+                 ($left) == ($right))
+    );
 }
 
 fn main() {
     test!(1i32 + 1 == 2i32; and 2i32 * 2 == 4i32);
     test!(true; or false);
+    test!(true; xor true);
+    test!(false; implies 1i32 == 2i32);
+    test!(1u32 < 2; iff 2u32 > 1);
 }
 
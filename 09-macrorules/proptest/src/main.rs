@@ -0,0 +1,149 @@
+/*
+The `test!` macro in the DRY example checks each operator on vectors of
+sizes 0 to 9, always filled with the same values. Property based testing
+instead states something that must hold for *every* input, and checks it
+against many random ones:
+
+proptest! {
+    fn addition_commutes(a in ints(-1000i32..1000), b in ints(-1000i32..1000)) {
+        assert_eq!(a + b, b + a);
+    }
+}
+
+Each `name in strategy` declares an input. The strategies are:
+
+ints(lo..hi)             integers in a range, shrinking toward zero
+vecs(strategy, lo..hi)   vectors with a length in a range
+strings(alphabet, lo..hi) strings over an alphabet, with a length in a range
+
+Each property becomes a #[test] run against 100 cases (`cases = N;` as the
+first line of the macro changes that). When a case fails, its input is
+shrunk to a minimal one that still fails, and the failure names the seed of
+the case:
+
+property `sum_is_small` failed after 3 passing cases
+    original input: ([38, 91, 2, 75],)
+    minimal input:  ([100],) (9 shrinks)
+    failure:        sum too large
+replay with PROPTEST_SEED=1234
+
+PROPTEST_SEED=1234 cargo test replays that case first. PROPTEST_CASES
+overrides the number of cases. There are no dependencies: the random numbers
+come from the small generator in rng.rs.
+*/
+
+mod rng;
+mod runner;
+mod strategy;
+
+use strategy::{ints, strings, vecs};
+
+// Only the tests use the macro; `main` calls the runner directly.
+#[allow(unused_macros)]
+macro_rules! proptest {
+    // Entry point with a case count.
+    (cases = $cases:expr; $($rest:tt)*) => (
+        proptest!(@fns $cases; $($rest)*);
+    );
+    // One property, then recurse on the rest.
+    (@fns $cases:expr;
+     $(#[$meta:meta])*
+     fn $name:ident($($arg:ident in $strategy:expr),+ $(,)*) $body:block
+     $($rest:tt)*) => (
+        #[test]
+        $(#[$meta])*
+        fn $name() {
+            let strategy = ($($strategy,)+);
+            let result = $crate::runner::check(stringify!($name), $cases, &strategy,
+                                               |($($arg,)+)| $body);
+            if let Err(failure) = result {
+                panic!("\n{}", failure);
+            }
+        }
+        proptest!(@fns $cases; $($rest)*);
+    );
+    (@fns $cases:expr;) => ();
+    // Entry point with the default case count.
+    ($($rest:tt)*) => (
+        proptest!(@fns 100; $($rest)*);
+    );
+}
+
+fn main() {
+    // The runner behind the macro can also be called directly. This
+    // property is false, to show what a shrunk failure looks like.
+    let strategy = (vecs(ints(0u32..200), 0..20),);
+    let result = runner::check("sum_is_small", 100, &strategy, |(xs,)| {
+        assert!(xs.iter().sum::<u32>() < 100, "sum too large");
+    });
+    match result {
+        Ok(()) => println!("sum_is_small passed"),
+        Err(failure) => println!("{}", failure),
+    }
+
+    let strategy = (strings("ab ", 0..10),);
+    let result = runner::check("no_double_space", 100, &strategy, |(s,)| {
+        assert!(!s.contains("  "), "found a double space");
+    });
+    match result {
+        Ok(()) => println!("no_double_space passed"),
+        Err(failure) => println!("{}", failure),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::runner::check;
+    use super::strategy::{ints, strings, vecs};
+
+    proptest! {
+        fn addition_commutes(a in ints(-1000i32..1000), b in ints(-1000i32..1000)) {
+            assert_eq!(a + b, b + a);
+        }
+
+        fn reverse_twice_is_identity(xs in vecs(ints(0u8..255), 0..50)) {
+            let mut ys = xs.clone();
+            ys.reverse();
+            ys.reverse();
+            assert_eq!(xs, ys);
+        }
+
+        fn concat_adds_lengths(a in strings("xyz", 0..10), b in strings("xyz", 0..10)) {
+            assert_eq!((a.clone() + &b).len(), a.len() + b.len());
+        }
+    }
+
+    proptest! {
+        cases = 10;
+
+        #[should_panic(expected = "replay with PROPTEST_SEED=")]
+        fn failures_print_the_seed(x in ints(0u32..1000)) {
+            assert!(x > 1000);
+        }
+    }
+
+    #[test]
+    fn shrinks_int_to_boundary() {
+        let failure = check("boundary", 1000, &(ints(0i64..1_000_000),), |(x,)| {
+            assert!(x < 1234);
+        }).unwrap_err();
+        assert_eq!(failure.minimal, "(1234,)");
+    }
+
+    #[test]
+    fn shrinks_vec_to_single_element() {
+        let failure = check("small", 1000, &(vecs(ints(0u32..200), 0..20),), |(xs,)| {
+            assert!(xs.iter().all(|&x| x < 100));
+        }).unwrap_err();
+        assert_eq!(failure.minimal, "([100],)");
+    }
+
+    #[test]
+    fn shrinks_string() {
+        let failure = check("no_b", 1000, &(strings("ab", 0..20),), |(s,)| {
+            assert!(!s.contains('b'), "has a b");
+        }).unwrap_err();
+        assert_eq!(failure.minimal, "(\"b\",)");
+        assert_eq!(failure.message, "has a b");
+    }
+}
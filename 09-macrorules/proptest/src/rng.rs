@@ -0,0 +1,50 @@
+// rng.rs
+// SplitMix64: tiny, fast, and good enough for test data. Every value drawn
+// from an `Rng` is determined by its seed, which is what makes a failing case
+// replayable.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A value in `0..n`. The modulo bias is at most n / 2^64, which no test
+    // will ever notice.
+    pub fn below(&mut self, n: u128) -> u128 {
+        assert!(n > 0, "Rng::below(0)");
+        u128::from(self.next_u64()) % n
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rng;
+
+    #[test]
+    fn same_seed_same_values() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn below_stays_below() {
+        let mut rng = Rng::new(7);
+        for n in 1..50 {
+            assert!(rng.below(n) < n);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// runner.rs
+// Runs a property against many generated inputs, and shrinks the first one
+// that fails.
+use std::cell::Cell;
+use std::env;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rng::Rng;
+use strategy::Strategy;
+
+// Set this to replay a failure. The seed printed with the failure is the
+// seed of the failing case, which is run first.
+pub const SEED_VAR: &str = "PROPTEST_SEED";
+// Set this to override the number of cases of every property.
+pub const CASES_VAR: &str = "PROPTEST_CASES";
+
+// Shrinking stops after this many successful steps, however large the input.
+const MAX_SHRINKS: usize = 1000;
+
+#[derive(Debug)]
+pub struct Failure {
+    pub name: &'static str,
+    // The seed of the failing case.
+    pub seed: u64,
+    // How many cases passed before this one.
+    pub passed: usize,
+    pub original: String,
+    pub minimal: String,
+    pub shrinks: usize,
+    // The panic message for the minimal input.
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "property `{}` failed after {} passing cases", self.name, self.passed)?;
+        writeln!(f, "    original input: {}", self.original)?;
+        writeln!(f, "    minimal input:  {} ({} shrinks)", self.minimal, self.shrinks)?;
+        writeln!(f, "    failure:        {}", self.message)?;
+        write!(f, "replay with {}={}", SEED_VAR, self.seed)
+    }
+}
+
+// Runs `property` against `cases` inputs drawn from `strategy`. A property
+// fails by panicking, so plain `assert!`s can be used.
+pub fn check<S, F>(name: &'static str, cases: usize, strategy: &S, property: F)
+                   -> Result<(), Failure>
+where
+    S: Strategy,
+    F: Fn(S::Value),
+{
+    install_quiet_hook();
+
+    let base_seed = match env::var(SEED_VAR) {
+        Ok(s) => s.trim().parse().expect("PROPTEST_SEED must be a u64"),
+        Err(_) => fresh_seed(),
+    };
+    let cases = match env::var(CASES_VAR) {
+        Ok(s) => s.trim().parse().expect("PROPTEST_CASES must be a usize"),
+        Err(_) => cases,
+    };
+
+    let mut seeds = Rng::new(base_seed);
+    for passed in 0..cases {
+        let seed = if passed == 0 { base_seed } else { seeds.next_u64() };
+        let input = strategy.generate(&mut Rng::new(seed));
+
+        if let Err(message) = run_one(&property, input.clone()) {
+            let (minimal, shrinks, message) = shrink(strategy, &property, input.clone(), message);
+            return Err(Failure {
+                name,
+                seed,
+                passed,
+                original: format!("{:?}", input),
+                minimal: format!("{:?}", minimal),
+                shrinks,
+                message,
+            });
+        }
+    }
+    Ok(())
+}
+
+// Greedy shrinking: take the first simpler candidate that still fails, and
+// start over from it.
+fn shrink<S, F>(strategy: &S, property: &F, mut input: S::Value, mut message: String)
+                -> (S::Value, usize, String)
+where
+    S: Strategy,
+    F: Fn(S::Value),
+{
+    let mut shrinks = 0;
+    'outer: while shrinks < MAX_SHRINKS {
+        for candidate in strategy.shrink(&input) {
+            if let Err(m) = run_one(property, candidate.clone()) {
+                input = candidate;
+                message = m;
+                shrinks += 1;
+                continue 'outer;
+            }
+        }
+        break;
+    }
+    (input, shrinks, message)
+}
+
+fn run_one<V, F: Fn(V)>(property: &F, input: V) -> Result<(), String> {
+    QUIET.with(|q| q.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| property(input)));
+    QUIET.with(|q| q.set(false));
+
+    result.map_err(|payload| {
+        if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "(non-string panic)".to_string()
+        }
+    })
+}
+
+fn fresh_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs() ^ u64::from(now.subsec_nanos()).rotate_left(32)
+}
+
+// Every failing case panics, and so does every failing shrink candidate.
+// The default hook would print each of those. Instead, one hook is
+// installed for the whole process that stays quiet while this thread is
+// running a property, and otherwise defers to whatever hook was there
+// before. A per-thread flag keeps parallel tests from silencing each other.
+thread_local!(static QUIET: Cell<bool> = const { Cell::new(false) });
+
+fn install_quiet_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(|q| q.get()) {
+                previous(info);
+            }
+        }));
+    });
+}
@@ -0,0 +1,263 @@
+// strategy.rs
+// A `Strategy` knows how to generate a random value, and how to propose
+// simpler versions of a value that failed. The runner keeps the first
+// simpler version that still fails, and asks again, until nothing simpler
+// fails.
+use std::fmt::Debug;
+use std::ops::Range;
+
+use rng::Rng;
+
+pub trait Strategy {
+    type Value: Clone + Debug;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value;
+
+    // Candidates simpler than `value`, most aggressive first. Must not
+    // include `value` itself, or shrinking would never end.
+    fn shrink(&self, value: &Self::Value) -> Vec<Self::Value>;
+}
+
+// The integer types `ints` works with. Everything is done in `i128`, which
+// holds every value of every type listed below.
+pub trait Int: Copy + Debug {
+    fn to_i128(self) -> i128;
+    fn from_i128(v: i128) -> Self;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),+) => (
+        $(
+            impl Int for $t {
+                fn to_i128(self) -> i128 { self as i128 }
+                fn from_i128(v: i128) -> $t { v as $t }
+            }
+        )+
+    )
+}
+
+impl_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+pub struct Ints<T> {
+    lo: i128,
+    hi: i128,
+    // Where shrinking heads: zero, or the end of the range nearest zero.
+    target: i128,
+    _marker: ::std::marker::PhantomData<T>,
+}
+
+// Integers in `range`, shrinking toward zero.
+pub fn ints<T: Int>(range: Range<T>) -> Ints<T> {
+    let (lo, hi) = (range.start.to_i128(), range.end.to_i128());
+    assert!(lo < hi, "ints: empty range");
+    let target = if lo > 0 {
+        lo
+    } else if hi <= 0 {
+        hi - 1
+    } else {
+        0
+    };
+    Ints { lo, hi, target, _marker: ::std::marker::PhantomData }
+}
+
+impl<T: Int> Strategy for Ints<T> {
+    type Value = T;
+
+    fn generate(&self, rng: &mut Rng) -> T {
+        let span = (self.hi - self.lo) as u128;
+        T::from_i128(self.lo + rng.below(span) as i128)
+    }
+
+    // The target, then ever smaller steps toward it: halfway, a quarter of
+    // the way, ..., one.
+    fn shrink(&self, value: &T) -> Vec<T> {
+        let v = value.to_i128();
+        let mut candidates = Vec::new();
+        if v == self.target {
+            return candidates;
+        }
+        candidates.push(T::from_i128(self.target));
+        let mut distance = (v - self.target) / 2;
+        while distance != 0 {
+            candidates.push(T::from_i128(v - distance));
+            distance /= 2;
+        }
+        candidates
+    }
+}
+
+pub struct Vecs<S> {
+    element: S,
+    len: Range<usize>,
+}
+
+// Vectors with a length in `len`, of elements drawn from `element`.
+pub fn vecs<S: Strategy>(element: S, len: Range<usize>) -> Vecs<S> {
+    assert!(len.start < len.end, "vecs: empty length range");
+    Vecs { element, len }
+}
+
+impl<S: Strategy> Strategy for Vecs<S> {
+    type Value = Vec<S::Value>;
+
+    fn generate(&self, rng: &mut Rng) -> Vec<S::Value> {
+        let span = (self.len.end - self.len.start) as u128;
+        let len = self.len.start + rng.below(span) as usize;
+        (0..len).map(|_| self.element.generate(rng)).collect()
+    }
+
+    // Shorter vectors first, since those are the easiest to read, then
+    // simpler elements one position at a time.
+    fn shrink(&self, value: &Vec<S::Value>) -> Vec<Vec<S::Value>> {
+        let mut candidates = Vec::new();
+        let min = self.len.start;
+
+        if value.len() > min {
+            let half = value.len() / 2;
+            if half >= min && half > 0 {
+                candidates.push(value[..half].to_vec());
+                candidates.push(value[value.len() - half..].to_vec());
+            }
+            for i in 0..value.len() {
+                let mut shorter = value.clone();
+                shorter.remove(i);
+                candidates.push(shorter);
+            }
+        }
+
+        for (i, element) in value.iter().enumerate() {
+            for simpler in self.element.shrink(element) {
+                let mut v = value.clone();
+                v[i] = simpler;
+                candidates.push(v);
+            }
+        }
+        candidates
+    }
+}
+
+pub struct Chars {
+    alphabet: Vec<char>,
+}
+
+// One character from `alphabet`, shrinking toward its first character.
+pub fn chars(alphabet: &str) -> Chars {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    assert!(!alphabet.is_empty(), "chars: empty alphabet");
+    Chars { alphabet }
+}
+
+impl Strategy for Chars {
+    type Value = char;
+
+    fn generate(&self, rng: &mut Rng) -> char {
+        self.alphabet[rng.below(self.alphabet.len() as u128) as usize]
+    }
+
+    fn shrink(&self, value: &char) -> Vec<char> {
+        match self.alphabet.iter().position(|c| c == value) {
+            Some(i) => self.alphabet[..i].to_vec(),
+            None => Vec::new(),
+        }
+    }
+}
+
+pub struct Strings {
+    chars: Vecs<Chars>,
+}
+
+// Strings with a length (in characters) in `len`, over `alphabet`.
+pub fn strings(alphabet: &str, len: Range<usize>) -> Strings {
+    Strings { chars: vecs(chars(alphabet), len) }
+}
+
+impl Strategy for Strings {
+    type Value = String;
+
+    fn generate(&self, rng: &mut Rng) -> String {
+        self.chars.generate(rng).into_iter().collect()
+    }
+
+    fn shrink(&self, value: &String) -> Vec<String> {
+        let chars: Vec<char> = value.chars().collect();
+        self.chars
+            .shrink(&chars)
+            .into_iter()
+            .map(|v| v.into_iter().collect())
+            .collect()
+    }
+}
+
+// A tuple of strategies is a strategy for the tuple of their values. This
+// is how the `proptest!` macro draws several inputs at once. Shrinking
+// tries one component at a time, leaving the others alone.
+macro_rules! impl_tuple {
+    ($($s:ident $i:tt),+) => (
+        impl<$($s: Strategy),+> Strategy for ($($s,)+) {
+            type Value = ($($s::Value,)+);
+
+            fn generate(&self, rng: &mut Rng) -> Self::Value {
+                ($(self.$i.generate(rng),)+)
+            }
+
+            fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+                let mut candidates = Vec::new();
+                $(
+                    for simpler in self.$i.shrink(&value.$i) {
+                        let mut v = value.clone();
+                        v.$i = simpler;
+                        candidates.push(v);
+                    }
+                )+
+                candidates
+            }
+        }
+    )
+}
+
+impl_tuple!(A 0);
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ints_shrink_toward_zero() {
+        let s = ints(-100i32..100);
+        assert_eq!(s.shrink(&0), Vec::<i32>::new());
+        assert_eq!(s.shrink(&8), vec![0, 4, 6, 7]);
+        assert_eq!(s.shrink(&-3), vec![0, -2]);
+    }
+
+    #[test]
+    fn ints_shrink_toward_range_start() {
+        let s = ints(10u8..20);
+        assert_eq!(s.shrink(&13), vec![10, 12]);
+        let mut rng = Rng::new(1);
+        for _ in 0..100 {
+            let v = s.generate(&mut rng);
+            assert!((10..20).contains(&v));
+        }
+    }
+
+    #[test]
+    fn vecs_respect_min_len() {
+        let s = vecs(ints(0u32..10), 2..5);
+        assert!(s.shrink(&vec![0, 0]).is_empty());
+        assert!(s.shrink(&vec![1, 2, 3]).iter().all(|v| v.len() >= 2));
+    }
+
+    #[test]
+    fn strings_use_alphabet() {
+        let s = strings("ab", 0..8);
+        let mut rng = Rng::new(3);
+        for _ in 0..100 {
+            assert!(s.generate(&mut rng).chars().all(|c| c == 'a' || c == 'b'));
+        }
+        assert!(s.shrink(&"b".to_string()).contains(&"a".to_string()));
+    }
+}
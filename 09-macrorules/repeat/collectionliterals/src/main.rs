@@ -0,0 +1,195 @@
+/*
+`vec![]` builds a vector from a list of elements, but the other collections
+have no such literal. Filling the `contacts` HashMap from the hashmap example
+takes a series of `insert` calls. The same `$(...),*` repetition that drives
+`find_min!` gives us the missing literals:
+
+hashmap!{k => v, ...}      btreemap!{k => v, ...}
+hashset!{x, ...}           btreeset!{x, ...}
+vecdeque![x, ...]
+
+All of them accept a trailing comma. The hash based collections and VecDeque
+are created with exactly the capacity they need, counted from the macro
+arguments at compile time. (The BTree collections have no capacity.)
+
+Writing the same key twice in a literal is almost always a mistake, so in
+debug builds the map and set macros panic with the text of the duplicate
+key. Keys are compared as written, not by value: `a.to_lowercase()` twice
+is a duplicate, but `a.to_lowercase()` and `b.to_lowercase()` aren't, even
+when they come out the same. Then, and for any duplicate in a release
+build, the later entry wins, just as with `insert`.
+*/
+
+use std::collections::HashMap;
+
+// Expands to `$sub`, discarding `$_t`. Used to map each argument to `()`.
+macro_rules! replace_expr {
+    ($_t:tt $sub:expr) => ($sub);
+}
+
+// The number of token trees given, as a constant expression. A captured
+// `$x:expr` is passed along as a single token tree, so this counts
+// expressions too. Building a slice of `()` rather than recursing keeps
+// this working for literals of any size.
+macro_rules! count {
+    ($($t:tt)*) => (<[()]>::len(&[$(replace_expr!($t ())),*]));
+}
+
+// The first of `texts` that comes up twice, if any.
+fn repeated(texts: &[&'static str]) -> Option<&'static str> {
+    texts.iter().enumerate().find(|&(i, t)| texts[..i].contains(t)).map(|(_, &t)| t)
+}
+
+// In debug builds, panics if any two of the `$k` are written the same.
+macro_rules! check_duplicates {
+    ($name:expr, $what:expr, $($k:expr)*) => (
+        if cfg!(debug_assertions) {
+            if let Some(text) = $crate::repeated(&[$(stringify!($k)),*]) {
+                panic!("{}: duplicate {} `{}`", $name, $what, text);
+            }
+        }
+    )
+}
+
+macro_rules! hashmap {
+    ($($k:expr => $v:expr),* $(,)?) => ({
+        const CAPACITY: usize = count!($($k)*);
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::HashMap::with_capacity(CAPACITY);
+        check_duplicates!("hashmap!", "key", $($k)*);
+        $(
+            map.insert($k, $v);
+        )*
+        map
+    })
+}
+
+macro_rules! btreemap {
+    ($($k:expr => $v:expr),* $(,)?) => ({
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::BTreeMap::new();
+        check_duplicates!("btreemap!", "key", $($k)*);
+        $(
+            map.insert($k, $v);
+        )*
+        map
+    })
+}
+
+macro_rules! hashset {
+    ($($x:expr),* $(,)?) => ({
+        const CAPACITY: usize = count!($($x)*);
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::HashSet::with_capacity(CAPACITY);
+        check_duplicates!("hashset!", "element", $($x)*);
+        $(
+            set.insert($x);
+        )*
+        set
+    })
+}
+
+macro_rules! btreeset {
+    ($($x:expr),* $(,)?) => ({
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::BTreeSet::new();
+        check_duplicates!("btreeset!", "element", $($x)*);
+        $(
+            set.insert($x);
+        )*
+        set
+    })
+}
+
+macro_rules! vecdeque {
+    ($($x:expr),* $(,)?) => ({
+        const CAPACITY: usize = count!($($x)*);
+        #[allow(unused_mut)]
+        let mut deque = ::std::collections::VecDeque::with_capacity(CAPACITY);
+        $(
+            deque.push_back($x);
+        )*
+        deque
+    })
+}
+
+fn main() {
+    // The contacts from the hashmap example, in one expression.
+    let contacts = hashmap!{
+        "Daniel" => "798-1364",
+        "Ashley" => "645-7689",
+        "Katie" => "435-8291",
+        "Robert" => "956-1745",
+    };
+    println!("contacts: {:?} (capacity {})", contacts, contacts.capacity());
+
+    let sorted = btreemap!{3 => "three", 1 => "one", 2 => "two"};
+    println!("sorted: {:?}", sorted);
+
+    let a = hashset!{1i32, 2, 3};
+    let b = btreeset!{"pear", "apple", "fig",};
+    println!("a: {:?}, b: {:?}", a, b);
+
+    let mut queue = vecdeque![1, 2, 3];
+    queue.push_front(0);
+    println!("queue: {:?}", queue);
+
+    let empty: HashMap<&str, i32> = hashmap!{};
+    println!("empty: {:?}", empty);
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn count_is_constant() {
+        const N: usize = count!(a b c (d e) [f]);
+        assert_eq!(N, 5);
+        assert_eq!(count!(), 0);
+    }
+
+    #[test]
+    fn maps() {
+        let m = hashmap!{"a" => 1, "b" => 2,};
+        assert_eq!(m.len(), 2);
+        assert!(m.capacity() >= 2);
+        assert_eq!(m["b"], 2);
+
+        let m = btreemap!{2 => 'b', 1 => 'a'};
+        assert_eq!(m.keys().cloned().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn sets_and_deques() {
+        let s = hashset!{1, 2, 3,};
+        assert_eq!(s.len(), 3);
+        let s = btreeset!{3, 1, 2};
+        assert_eq!(s.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+        let d = vecdeque!["x", "y",];
+        assert_eq!(d, ["x", "y"]);
+    }
+
+    // Only debug builds check.
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "hashmap!: duplicate key `\"Daniel\"`")]
+    fn duplicate_map_key() {
+        let _ = hashmap!{"Daniel" => 1, "Ashley" => 2, "Daniel" => 3};
+    }
+
+    #[test]
+    fn equal_values_written_differently() {
+        let (a, b) = ("Daniel", "DANIEL");
+        let m = hashmap!{a.to_lowercase() => 1, b.to_lowercase() => 2};
+        assert_eq!(m.len(), 1);
+        assert_eq!(m["daniel"], 2);
+        let s = btreeset!{1 + 1, 2};
+        assert_eq!(s.len(), 1);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "btreeset!: duplicate element `2`")]
+    fn duplicate_set_element() {
+        let _ = btreeset!{1, 2, 2};
+    }
+}
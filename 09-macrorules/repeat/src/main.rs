@@ -19,11 +19,81 @@ macro_rules! find_min {
     )
 }
 
+// `find_max!` is the mirror image of `find_min!`.
+macro_rules! find_max {
+    ($x:expr) => ($x);
+    ($x:expr, $($y:expr),+) => (
+        std::cmp::max($x, find_max!($($y),+))
+    )
+}
+
+// `min_by!` finds the argument with the smallest key, where `$key` is a
+// closure from a reference to an argument to its key. Ties go to the
+// leftmost argument.
+macro_rules! min_by {
+    ($key:expr; $x:expr) => ($x);
+    ($key:expr; $x:expr, $($y:expr),+) => ({
+        let first = $x;
+        let rest = min_by!($key; $($y),+);
+        if ($key)(&rest) < ($key)(&first) { rest } else { first }
+    })
+}
+
+// `sum!` adds up any number of arguments, from the left, as `a + b + c`
+// would: the first two are added, and the sum takes their place.
+macro_rules! sum {
+    ($x:expr) => ($x);
+    ($x:expr, $y:expr $(, $rest:expr)*) => (
+        sum!($x + $y $(, $rest)*)
+    )
+}
+
 fn main() {
     println!("{}", find_min!(1u32));
     println!("{}", find_min!(1u32 + 2 , 2u32));
 
     // Below gives 4.  Huh?
     println!("{}", find_min!(5u32, 2u32 * 3, 4u32));
+
+    println!("{}", find_max!(1u32));
+    println!("{}", find_max!(5u32, 2u32 * 3, 4u32));
+
+    // The shortest word. "kiwi" and "pear" tie, so the leftmost one wins.
+    println!("{}", min_by!(|s: &&str| s.len(); "banana", "kiwi", "pear"));
+    // The number nearest 10.
+    println!("{}", min_by!(|x: &i32| (x - 10).abs(); 3, 14, 8, 25));
+
+    println!("{}", sum!(1u32));
+    println!("{}", sum!(1u32, 2u32 * 3, 4u32));
 }
 
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn max() {
+        assert_eq!(find_max!(1u32), 1);
+        assert_eq!(find_max!(5u32, 2u32 * 3, 4u32), 6);
+        assert_eq!(find_max!(-1i32, -7, -3), -1);
+        assert_eq!(find_max!("pear", "apple", "plum"), "plum");
+    }
+
+    #[test]
+    fn min_by_key() {
+        assert_eq!(min_by!(|x: &i32| x.abs(); -4), -4);
+        assert_eq!(min_by!(|x: &i32| (x - 10).abs(); 3, 14, 8, 25), 8);
+        // A tie goes to the leftmost argument.
+        assert_eq!(min_by!(|p: &(usize, char)| p.0; (2, 'a'), (1, 'b'), (1, 'c')), (1, 'b'));
+        assert_eq!(min_by!(|s: &&str| s.len(); "banana", "kiwi", "pear"), "kiwi");
+    }
+
+    #[test]
+    fn sums() {
+        assert_eq!(sum!(1u32), 1);
+        assert_eq!(sum!(1u32, 2u32 * 3, 4u32), 11);
+        assert_eq!(sum!(0.5f64, 0.25, 0.125), 0.875);
+        // (0.1 + 0.2) + 0.3 isn't 0.1 + (0.2 + 0.3).
+        assert_eq!(sum!(0.1f64, 0.2, 0.3), 0.1 + 0.2 + 0.3);
+        assert_eq!(sum!(String::new(), "a", "b"), "ab");
+    }
+}
@@ -0,0 +1,514 @@
+// audit.rs
+// Resolves the references collected by the parser to items, and from that
+// finds:
+//
+// unreachable  items marked `pub` or `pub(crate)` that can not be named
+//              from the crate root, because some module on the way is
+//              private to its parent
+// dead         items nothing live refers to. The roots are `main` in a
+//              binary, or everything reachable from the root in a library,
+//              along with its `#[macro_export]` macros.
+//              Like rustc, an item only used by dead items is dead too.
+// unused       `use` bindings that are never looked up
+//
+// Resolution is by name, following `use`, `self`, `super` and `crate` the
+// way the compiler does, but without types: `x.area()` keeps every method
+// called `area` alive. Paths that lead outside the crate, such as
+// `std::fmt`, simply resolve to nothing.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+
+use parser::{Crate, Kind, RefKind, Vis};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Problem {
+    Unreachable,
+    Dead,
+    UnusedImport,
+}
+
+#[derive(Debug)]
+pub struct Finding {
+    pub problem: Problem,
+    // What was found, such as `fn my::nested::private_function`.
+    pub what: String,
+    pub file: PathBuf,
+    pub line: usize,
+    // Why, for unreachable items.
+    pub reason: Option<String>,
+    // Already silenced with `#[allow(dead_code)]` or `#[allow(unused)]`.
+    pub allowed: bool,
+}
+
+// Traits that are usually imported only for their methods, which this
+// audit can not see.
+const STD_TRAITS: &[&str] = &[
+    "Write", "Read", "BufRead", "Seek", "FromStr", "Hash", "Hasher", "FromIterator", "Extend",
+    "DoubleEndedIterator", "ExactSizeIterator", "Borrow", "BorrowMut", "Deref", "DerefMut",
+    "Any", "Error", "TryFrom", "TryInto", "ToString", "OsStrExt", "OsStringExt",
+    "PermissionsExt", "MetadataExt", "CommandExt", "ExitStatusExt", "AsRawFd", "FromRawFd",
+];
+
+// Gives up on `use` chains longer than this.
+const MAX_DEPTH: usize = 32;
+
+struct Resolver<'a> {
+    krate: &'a Crate,
+    // (module, name) -> items declared there, macros and methods aside.
+    children: HashMap<(usize, &'a str), Vec<usize>>,
+    // (type, name) -> methods of inherent impls of that type.
+    methods: HashMap<(usize, &'a str), Vec<usize>>,
+    // Uses that some lookup went through.
+    used: HashSet<usize>,
+    // Where each use points, once known, and the uses being resolved
+    // right now, so that `use` cycles (and a glob import looking itself up)
+    // end instead of going round.
+    targets: HashMap<usize, Option<usize>>,
+    resolving: HashSet<usize>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(krate: &'a Crate) -> Resolver<'a> {
+        let mut r = Resolver {
+            krate,
+            children: HashMap::new(),
+            methods: HashMap::new(),
+            used: HashSet::new(),
+            targets: HashMap::new(),
+            resolving: HashSet::new(),
+        };
+        for (id, item) in krate.items.iter().enumerate() {
+            if let Some(parent) = item.parent {
+                if item.kind != Kind::Macro && item.kind != Kind::Method {
+                    r.children.entry((parent, &item.name[..])).or_default().push(id);
+                }
+            }
+        }
+        for (id, item) in krate.items.iter().enumerate() {
+            if item.kind == Kind::Method {
+                if let Some(ty) = r.path(item.parent.unwrap(), &item.self_ty, 0) {
+                    r.methods.entry((ty, &item.name[..])).or_default().push(id);
+                }
+            }
+        }
+        r
+    }
+
+    // What `name` means in `module`: an item declared there, or brought in
+    // by a `use` there.
+    fn lookup(&mut self, module: usize, name: &str, depth: usize) -> Option<usize> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        if let Some(ids) = self.children.get(&(module, name)) {
+            return Some(ids[0]);
+        }
+        let krate = self.krate;
+        for (u, decl) in krate.uses.iter().enumerate() {
+            if decl.module == module && decl.name == name {
+                self.used.insert(u);
+                return self.use_target(u, depth + 1);
+            }
+        }
+        for (u, decl) in krate.uses.iter().enumerate() {
+            if decl.module == module && decl.name == "*" {
+                let target = match self.use_target(u, depth + 1) {
+                    Some(target) if krate.items[target].kind == Kind::Mod => target,
+                    _ => continue,
+                };
+                if let Some(id) = self.lookup(target, name, depth + 1) {
+                    self.used.insert(u);
+                    return Some(id);
+                }
+            }
+        }
+        None
+    }
+
+    // Where a `use` points. Paths are tried from the crate root first, as
+    // in the 2015 edition, then from the module, as in 2018.
+    fn use_target(&mut self, u: usize, depth: usize) -> Option<usize> {
+        if let Some(&target) = self.targets.get(&u) {
+            return target;
+        }
+        if !self.resolving.insert(u) {
+            return None;
+        }
+        let decl = &self.krate.uses[u];
+        let target = self.use_path(decl.module, &decl.path, depth);
+        self.resolving.remove(&u);
+        self.targets.insert(u, target);
+        target
+    }
+
+    fn use_path(&mut self, module: usize, path: &[String], depth: usize) -> Option<usize> {
+        match path.first().map(|s| &s[..]) {
+            Some("crate") | Some("self") | Some("super") => self.path(module, path, depth),
+            Some(_) => self.path(0, path, depth).or_else(|| self.path(module, path, depth)),
+            None => None,
+        }
+    }
+
+    // Resolves `path` as written in `module`.
+    fn path(&mut self, module: usize, path: &[String], depth: usize) -> Option<usize> {
+        let (mut current, rest) = match path.first().map(|s| &s[..]) {
+            Some("crate") => (0, &path[1..]),
+            Some("self") => (module, &path[1..]),
+            Some("super") => {
+                let mut current = module;
+                let mut rest = path;
+                while rest.first().is_some_and(|s| s == "super") {
+                    current = self.krate.items[current].parent?;
+                    rest = &rest[1..];
+                }
+                (current, rest)
+            }
+            Some(first) => (self.lookup(module, first, depth)?, &path[1..]),
+            None => return None,
+        };
+        for segment in rest {
+            match self.krate.items[current].kind {
+                Kind::Mod => current = self.lookup(current, segment, depth)?,
+                Kind::Struct | Kind::Enum | Kind::Union | Kind::Trait | Kind::Type => {
+                    // A method, or else an enum variant or associated item,
+                    // which keeps the type itself alive.
+                    return Some(match self.methods.get(&(current, &segment[..])) {
+                        Some(ids) => ids[0],
+                        None => current,
+                    });
+                }
+                _ => return Some(current),
+            }
+        }
+        Some(current)
+    }
+
+    fn parent_is_root(&self, id: usize) -> bool {
+        self.krate.items[id].parent == Some(0)
+    }
+
+    // Whether the crate root may name this item, given it can name the
+    // module the item is in.
+    fn visible_from_root(&self, id: usize) -> bool {
+        let item = &self.krate.items[id];
+        match item.vis {
+            Vis::Public | Vis::Crate => true,
+            Vis::Private => id == 0 || self.parent_is_root(id),
+            Vis::Super => {
+                self.parent_is_root(id)
+                    || item.parent.is_some_and(|p| self.parent_is_root(p))
+            }
+            Vis::Restricted(ref path) => path.len() == 1 && path[0] == "crate" || self.parent_is_root(id),
+        }
+    }
+}
+
+pub fn audit(krate: &Crate) -> Vec<Finding> {
+    let mut r = Resolver::new(krate);
+    let items = &krate.items;
+    let mut findings = Vec::new();
+
+    // Reachability: walk down from the root through visible items, and
+    // through `pub use` re-exports.
+    let mut reachable: HashSet<usize> = HashSet::new();
+    reachable.insert(0);
+    let mut queue = vec![0];
+    while let Some(module) = queue.pop() {
+        let mut found = Vec::new();
+        for (id, item) in items.iter().enumerate() {
+            if item.parent == Some(module) && item.kind != Kind::Method && r.visible_from_root(id) {
+                found.push(id);
+            }
+        }
+        for (u, decl) in krate.uses.iter().enumerate() {
+            let exported = decl.vis == Vis::Public || decl.vis == Vis::Crate;
+            if decl.module == module && exported && decl.name != "*" {
+                if let Some(id) = r.use_target(u, 0) {
+                    found.push(id);
+                }
+            }
+        }
+        for id in found {
+            if reachable.insert(id) && items[id].kind == Kind::Mod {
+                queue.push(id);
+            }
+        }
+    }
+    let method_types: Vec<(usize, usize)> = r.methods
+        .iter()
+        .flat_map(|(&(ty, _), ids)| ids.iter().map(move |&id| (id, ty)))
+        .collect();
+    let type_of: HashMap<usize, usize> = method_types.into_iter().collect();
+    for (id, item) in items.iter().enumerate() {
+        if item.kind == Kind::Method && r.visible_from_root(id)
+            && type_of.get(&id).is_some_and(|ty| reachable.contains(ty)) {
+            reachable.insert(id);
+        }
+    }
+
+    for (id, item) in items.iter().enumerate() {
+        let public = item.vis == Vis::Public || item.vis == Vis::Crate;
+        if public && !reachable.contains(&id) {
+            findings.push(Finding {
+                problem: Problem::Unreachable,
+                what: describe(krate, id),
+                file: item.file.clone(),
+                line: item.line,
+                reason: Some(unreachable_reason(&r, id, &type_of)),
+                allowed: item.allowed,
+            });
+        }
+    }
+
+    // Liveness: follow references out from the roots.
+    let binary = r.children.get(&(0, "main")).is_some_and(|ids| items[ids[0]].kind == Kind::Fn);
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); items.len()];
+    let mut live: HashSet<usize> = HashSet::new();
+    let mut roots: Vec<usize> = Vec::new();
+    if binary {
+        roots.push(r.children[&(0, "main")][0]);
+    } else {
+        roots.extend(reachable.iter().cloned());
+        // Exported macros are at the crate root for other crates, wherever
+        // they are defined.
+        roots.extend((0..items.len()).filter(|&id| items[id].exported));
+    }
+    let mut by_name: HashMap<(Kind, &str), Vec<usize>> = HashMap::new();
+    for (id, item) in items.iter().enumerate() {
+        if item.kind == Kind::Method || item.kind == Kind::Macro {
+            by_name.entry((item.kind, &item.name[..])).or_default().push(id);
+        }
+    }
+    for reference in &krate.refs {
+        let targets: Vec<usize> = match reference.kind {
+            RefKind::Path => {
+                let path = match (reference.path[0] == "Self", &reference.self_ty) {
+                    (true, Some(self_ty)) => {
+                        self_ty.iter().chain(reference.path[1..].iter()).cloned().collect()
+                    }
+                    _ => reference.path.clone(),
+                };
+                r.path(reference.module, &path, 0).into_iter().collect()
+            }
+            RefKind::Use => r.use_path(reference.module, &reference.path, 0).into_iter().collect(),
+            RefKind::Method => by_name.get(&(Kind::Method, &reference.path[0][..])).cloned().unwrap_or_default(),
+            RefKind::Macro => by_name.get(&(Kind::Macro, &reference.path[0][..])).cloned().unwrap_or_default(),
+        };
+        // Items of the same name in the same module can only be `#[cfg]`
+        // alternatives, so they live or die together.
+        let targets: Vec<usize> = targets
+            .into_iter()
+            .flat_map(|t| match items[t].parent {
+                Some(p) if items[t].kind != Kind::Macro && items[t].kind != Kind::Method => {
+                    r.children[&(p, &items[t].name[..])].clone()
+                }
+                _ => vec![t],
+            })
+            .collect();
+        for target in targets {
+            match reference.within {
+                Some(within) if within == target => {}
+                Some(within) => edges[within].push(target),
+                None => roots.push(target),
+            }
+        }
+    }
+    let mut queue: VecDeque<usize> = roots.into_iter().collect();
+    while let Some(id) = queue.pop_front() {
+        if live.insert(id) {
+            queue.extend(edges[id].iter().cloned());
+        }
+    }
+
+    for (id, item) in items.iter().enumerate() {
+        if item.kind != Kind::Mod && !live.contains(&id) {
+            findings.push(Finding {
+                problem: Problem::Dead,
+                what: describe(krate, id),
+                file: item.file.clone(),
+                line: item.line,
+                reason: None,
+                allowed: item.allowed,
+            });
+        }
+    }
+
+    for (u, decl) in krate.uses.iter().enumerate() {
+        let std_trait = decl.path.first().is_some_and(|s| s == "std" || s == "core")
+            && decl.path.last().is_some_and(|s| STD_TRAITS.contains(&&s[..]));
+        // A glob can only be judged if it is of one of our modules; an
+        // enum's variants, or `std::io::prelude::*`, are not known here.
+        let unknown_glob = decl.name == "*"
+            && !r.use_target(u, 0).is_some_and(|t| items[t].kind == Kind::Mod);
+        // A trait imported for its methods.
+        let trait_methods = r.use_target(u, 0).is_some_and(|t| {
+            items[t].kind == Kind::Trait && krate.refs.iter().any(|x| {
+                x.kind == RefKind::Method && x.module == decl.module && items[t].members.contains(&x.path[0])
+            })
+        });
+        if decl.vis == Vis::Private && !r.used.contains(&u) && !std_trait && !unknown_glob
+            && !trait_methods {
+            let mut path = decl.path.join("::");
+            if decl.name == "*" {
+                path.push_str("::*");
+            } else if decl.path.last() != Some(&decl.name) {
+                path = format!("{} as {}", path, decl.name);
+            }
+            findings.push(Finding {
+                problem: Problem::UnusedImport,
+                what: format!("use {}", path),
+                file: decl.file.clone(),
+                line: decl.line,
+                reason: None,
+                allowed: false,
+            });
+        }
+    }
+
+    findings.sort_by_key(|f| (f.file.clone(), f.line));
+    findings
+}
+
+// `my::inaccessible::public_function`, without the leading `crate`.
+pub fn item_path(krate: &Crate, id: usize) -> String {
+    let mut names = Vec::new();
+    let item = &krate.items[id];
+    if item.kind == Kind::Method {
+        names.push(item.name.clone());
+        names.push(item.self_ty.last().cloned().unwrap_or_default());
+    } else {
+        names.push(item.name.clone());
+    }
+    let mut parent = item.parent;
+    while let Some(p) = parent {
+        if p != 0 {
+            names.push(krate.items[p].name.clone());
+        }
+        parent = krate.items[p].parent;
+    }
+    names.reverse();
+    names.join("::")
+}
+
+fn describe(krate: &Crate, id: usize) -> String {
+    format!("{} {}", krate.items[id].kind.name(), item_path(krate, id))
+}
+
+// The first module on the way down from the root that the root can not
+// see, or the type a method belongs to.
+fn unreachable_reason(r: &Resolver, id: usize, type_of: &HashMap<usize, usize>) -> String {
+    let items = &r.krate.items;
+    let mut chain = Vec::new();
+    let mut parent = items[id].parent;
+    while let Some(p) = parent {
+        chain.push(p);
+        parent = items[p].parent;
+    }
+    for &module in chain.iter().rev() {
+        if !r.visible_from_root(module) {
+            let owner = items[module].parent.map_or("crate".to_string(), |p| {
+                if p == 0 { "the crate root".to_string() } else { format!("`{}`", item_path(r.krate, p)) }
+            });
+            return format!("`{}` is private to {}", item_path(r.krate, module), owner);
+        }
+    }
+    match type_of.get(&id) {
+        Some(&ty) => format!("`{}` is not reachable", item_path(r.krate, ty)),
+        None => "its type is not reachable".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use parser::load_with;
+
+    // The first file is the crate root.
+    fn run(files: &[(&str, &str)]) -> Vec<(Problem, String)> {
+        let root = PathBuf::from(files[0].0);
+        let files: HashMap<PathBuf, String> = files
+            .iter()
+            .map(|&(name, source)| (PathBuf::from(name), source.to_string()))
+            .collect();
+        let read = |path: &Path| files.get(path).cloned().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound));
+        let krate = load_with(&root, &read).unwrap();
+        audit(&krate).into_iter().map(|f| (f.problem, f.what)).collect()
+    }
+
+    #[test]
+    fn file_hierarchy() {
+        let found = run(&[
+            ("src/main.rs", "mod my;\n\
+                             fn function() {}\n\
+                             fn main() { my::function(); function(); my::indirect_access(); my::nested::function(); }"),
+            ("src/my/mod.rs", "mod inaccessible;\n\
+                               pub mod nested;\n\
+                               pub fn function() {}\n\
+                               fn private_function() {}\n\
+                               pub fn indirect_access() { private_function(); }"),
+            ("src/my/inaccessible.rs", "#[allow(dead_code)]\npub fn public_function() {}"),
+            ("src/my/nested.rs", "pub fn function() {}\nfn private_function() {}"),
+        ]);
+        assert_eq!(found, vec![
+            (Problem::Unreachable, "fn my::inaccessible::public_function".to_string()),
+            (Problem::Dead, "fn my::inaccessible::public_function".to_string()),
+            (Problem::Dead, "fn my::nested::private_function".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn methods_imports_and_dead_chains() {
+        let found = run(&[("src/main.rs", "
+            use std::collections::HashMap;
+            use std::io::Write;
+            use shapes::make;
+            use util::twice;
+
+            mod shapes {
+                pub struct Square(pub f64);
+                impl Square {
+                    pub fn area(&self) -> f64 { Self::side(self) * self.0 }
+                    fn side(&self) -> f64 { self.0 }
+                    pub fn unused(&self) {}
+                }
+                pub(crate) fn make() -> Square { Square(2.0) }
+            }
+
+            mod util {
+                pub fn twice(x: f64) -> f64 { 2.0 * x }
+                fn dead_chain() { also_dead() }
+                fn also_dead() { dead_chain() }
+                pub mod inner { pub use super::twice; }
+            }
+
+            fn main() { println!(\"{}\", make().area()); }
+        ")]);
+        let dead: Vec<&str> = found.iter().filter(|f| f.0 == Problem::Dead).map(|f| &f.1[..]).collect();
+        assert_eq!(dead, ["method shapes::Square::unused", "fn util::twice",
+                          "fn util::dead_chain", "fn util::also_dead"]);
+        let unused: Vec<&str> = found.iter().filter(|f| f.0 == Problem::UnusedImport).map(|f| &f.1[..]).collect();
+        assert_eq!(unused, ["use std::collections::HashMap", "use util::twice"]);
+        assert!(!found.iter().any(|f| f.0 == Problem::Unreachable));
+    }
+
+    #[test]
+    fn exported_macros() {
+        let found = run(&[
+            ("src/lib.rs", "#[macro_use]\nmod layout;\npub fn size() -> usize { 0 }"),
+            ("src/layout.rs", "#[macro_export]\n\
+                               macro_rules! layout_of { () => { helper() } }\n\
+                               macro_rules! unused { () => {} }\n\
+                               pub fn helper() {}"),
+        ]);
+        let dead: Vec<&str> = found.iter().filter(|f| f.0 == Problem::Dead).map(|f| &f.1[..]).collect();
+        assert_eq!(dead, ["macro layout::unused"]);
+
+        // In a binary only `main` is a root, exported or not.
+        let found = run(&[("src/main.rs", "#[macro_export]\nmacro_rules! m { () => {} }\nfn main() {}")]);
+        assert_eq!(found, vec![(Problem::Dead, "macro m".to_string())]);
+    }
+}
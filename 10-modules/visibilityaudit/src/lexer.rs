@@ -0,0 +1,353 @@
+// lexer.rs
+// Splits Rust source into token trees: identifiers, lifetimes, literals and
+// single punctuation characters, with `()`, `[]` and `{}` already matched
+// up into groups. Comments are dropped. This is only as careful as the
+// audit needs: a literal is kept as one opaque token, and `::` is two `:`.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delim {
+    Paren,
+    Bracket,
+    Brace,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    // `r#type` is kept as `type`.
+    Ident(String),
+    Lifetime(String),
+    Literal(String),
+    Punct(char),
+    Group(Delim, Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub tok: Tok,
+    pub line: usize,
+}
+
+impl Token {
+    pub fn is_ident(&self, word: &str) -> bool {
+        match self.tok {
+            Tok::Ident(ref s) => s == word,
+            _ => false,
+        }
+    }
+
+    pub fn is_punct(&self, c: char) -> bool {
+        self.tok == Tok::Punct(c)
+    }
+
+    pub fn ident(&self) -> Option<&str> {
+        match self.tok {
+            Tok::Ident(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn group(&self, delim: Delim) -> Option<&[Token]> {
+        match self.tok {
+            Tok::Group(d, ref body) if d == delim => Some(body),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LexError {
+    pub message: String,
+    pub line: usize,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, LexError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut lexer = Lexer { chars: &chars, pos: 0, line: 1 };
+    // Each open group: its delimiter, line, and the tokens so far.
+    let mut stack: Vec<(Delim, usize, Vec<Token>)> = Vec::new();
+    let mut top: Vec<Token> = Vec::new();
+
+    while let Some(token) = lexer.next_token()? {
+        let open = match token.tok {
+            Tok::Punct('(') => Some(Delim::Paren),
+            Tok::Punct('[') => Some(Delim::Bracket),
+            Tok::Punct('{') => Some(Delim::Brace),
+            _ => None,
+        };
+        let close = match token.tok {
+            Tok::Punct(')') => Some(Delim::Paren),
+            Tok::Punct(']') => Some(Delim::Bracket),
+            Tok::Punct('}') => Some(Delim::Brace),
+            _ => None,
+        };
+        if let Some(delim) = open {
+            stack.push((delim, token.line, ::std::mem::take(&mut top)));
+        } else if let Some(delim) = close {
+            match stack.pop() {
+                Some((d, line, outer)) if d == delim => {
+                    let body = ::std::mem::replace(&mut top, outer);
+                    top.push(Token { tok: Tok::Group(d, body), line });
+                }
+                _ => return Err(LexError { message: "unbalanced delimiter".to_string(),
+                                           line: token.line }),
+            }
+        } else {
+            top.push(token);
+        }
+    }
+    match stack.pop() {
+        Some((_, line, _)) => Err(LexError { message: "unclosed delimiter".to_string(), line }),
+        None => Ok(top),
+    }
+}
+
+struct Lexer<'a> {
+    chars: &'a [char],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.pos + ahead).cloned()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0);
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        self.pos += 1;
+        c
+    }
+
+    fn error(&self, message: &str) -> LexError {
+        LexError { message: message.to_string(), line: self.line }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, LexError> {
+        loop {
+            let c = match self.peek(0) {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '/' && self.peek(1) == Some('/') {
+                while self.peek(0).is_some() && self.peek(0) != Some('\n') {
+                    self.bump();
+                }
+            } else if c == '/' && self.peek(1) == Some('*') {
+                self.block_comment()?;
+            } else {
+                break;
+            }
+        }
+
+        let line = self.line;
+        let start = self.pos;
+        let c = self.peek(0).unwrap();
+        let tok = if c == '"' {
+            self.string()?;
+            Tok::Literal(self.text(start))
+        } else if (c == 'r' || c == 'b' || c == 'c') && self.raw_or_byte_start() {
+            self.prefixed_literal()?;
+            Tok::Literal(self.text(start))
+        } else if c == 'r' && self.peek(1) == Some('#') && self.peek(2).is_some_and(is_ident_start) {
+            self.pos += 2;
+            self.ident();
+            Tok::Ident(self.text(start + 2))
+        } else if is_ident_start(c) {
+            self.ident();
+            Tok::Ident(self.text(start))
+        } else if c.is_ascii_digit() {
+            self.number();
+            Tok::Literal(self.text(start))
+        } else if c == '\'' {
+            self.quote()?
+        } else {
+            self.bump();
+            Tok::Punct(c)
+        };
+        Ok(Some(Token { tok, line }))
+    }
+
+    fn text(&self, start: usize) -> String {
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn block_comment(&mut self) -> Result<(), LexError> {
+        let mut depth = 0;
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.pos += 2;
+                }
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.pos += 2;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                (Some(_), _) => {
+                    self.bump();
+                }
+                (None, _) => return Err(self.error("unterminated block comment")),
+            }
+        }
+    }
+
+    fn ident(&mut self) {
+        while self.peek(0).is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.bump();
+        }
+    }
+
+    // Numbers, including suffixes and exponents. A `.` is only taken when
+    // a digit follows, so that `0..n` is a range.
+    fn number(&mut self) {
+        loop {
+            match self.peek(0) {
+                Some(c) if c.is_alphanumeric() || c == '_' => {
+                    self.bump();
+                }
+                Some('.') if self.peek(1).is_some_and(|c| c.is_ascii_digit()) => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    // The body of a `"..."` string, from the opening quote.
+    fn string(&mut self) -> Result<(), LexError> {
+        self.bump();
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    self.bump();
+                }
+                Some('"') => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    // `r"`, `r#"`, `b"`, `b'`, `br"`, `c"` and so on.
+    fn raw_or_byte_start(&self) -> bool {
+        let mut i = 0;
+        if self.peek(i) == Some('b') || self.peek(i) == Some('c') {
+            i += 1;
+            if self.peek(i) == Some('"') || (self.peek(0) == Some('b') && self.peek(i) == Some('\'')) {
+                return true;
+            }
+        }
+        if self.peek(i) == Some('r') {
+            i += 1;
+            while self.peek(i) == Some('#') {
+                i += 1;
+            }
+            return self.peek(i) == Some('"');
+        }
+        false
+    }
+
+    fn prefixed_literal(&mut self) -> Result<(), LexError> {
+        while self.peek(0) == Some('b') || self.peek(0) == Some('c') {
+            self.bump();
+        }
+        match self.peek(0) {
+            Some('"') => self.string(),
+            Some('\'') => {
+                self.quote()?;
+                Ok(())
+            }
+            _ => {
+                // A raw string: `r`, some `#`s, then a quote.
+                self.bump();
+                let mut hashes = 0;
+                while self.peek(0) == Some('#') {
+                    hashes += 1;
+                    self.bump();
+                }
+                self.bump();
+                loop {
+                    match self.bump() {
+                        Some('"') => {
+                            let closed = (0..hashes).all(|i| self.peek(i) == Some('#'));
+                            if closed {
+                                self.pos += hashes;
+                                return Ok(());
+                            }
+                        }
+                        Some(_) => {}
+                        None => return Err(self.error("unterminated raw string")),
+                    }
+                }
+            }
+        }
+    }
+
+    // A character literal or a lifetime, from the opening quote.
+    fn quote(&mut self) -> Result<Tok, LexError> {
+        let start = self.pos;
+        self.bump();
+        if self.peek(0) == Some('\\') {
+            while self.peek(0).is_some() && self.peek(0) != Some('\'') {
+                if self.bump() == Some('\\') {
+                    self.bump();
+                }
+            }
+            self.bump();
+            return Ok(Tok::Literal(self.text(start)));
+        }
+        if self.peek(1) == Some('\'') {
+            self.pos += 2;
+            return Ok(Tok::Literal(self.text(start)));
+        }
+        if self.peek(0).is_some_and(is_ident_start) {
+            self.ident();
+            return Ok(Tok::Lifetime(self.text(start)));
+        }
+        Err(self.error("unexpected `'`"))
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens_and_groups() {
+        let tokens = tokenize("fn r#type<'a>(x: &'a str) -> char { /* } */ 'x' } // }").unwrap();
+        let kinds: Vec<String> = tokens.iter().map(|t| match t.tok {
+            Tok::Ident(ref s) => s.clone(),
+            Tok::Lifetime(ref s) => s.clone(),
+            Tok::Literal(ref s) => s.clone(),
+            Tok::Punct(c) => c.to_string(),
+            Tok::Group(d, ref body) => format!("{:?}{}", d, body.len()),
+        }).collect();
+        assert_eq!(kinds, ["fn", "type", "<", "'a", ">", "Paren5", "-", ">", "char", "Brace1"]);
+
+        let tokens = tokenize("let s = r#\"a \" b\"#; let t = b'\\''; x\ny").unwrap();
+        assert_eq!(tokens[3].tok, Tok::Literal("r#\"a \" b\"#".to_string()));
+        assert_eq!(tokens[8].tok, Tok::Literal("b'\\''".to_string()));
+        assert_eq!(tokens[11].line, 2);
+
+        assert!(tokenize("fn f() { (]").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+/*
+The visibility, filehiearchy and structvisibility examples show which items
+of the `my` module can be reached from the crate root and which can not:
+
+my::inaccessible::public_function   is `pub`, but `inaccessible` is private
+my::private_nested::function        is `pub`, but `private_nested` is private
+
+The compiler only tells us so when we try to call them. This tool reads a
+crate the same way the compiler starts to, from its root file, following
+`mod foo;` to `foo.rs` or `foo/mod.rs`, and reports:
+
+- items marked `pub` (or `pub(crate)`) that can not be reached from the
+  crate root, so that the `pub` promises something it does not deliver
+- dead items, that nothing live ever refers to
+- `use` declarations that are never used
+
+$ cargo run -- ../filehiearchy/src/main.rs
+../filehiearchy/src/main.rs (binary)
+  pub but unreachable from the crate root:
+    fn my::inaccessible::public_function  my/inaccessible.rs:3  `my::inaccessible` is private to `my`  [allowed]
+  dead:
+    fn my::inaccessible::public_function  my/inaccessible.rs:3  [allowed]
+    fn my::nested::private_function  my/nested.rs:7  [allowed]
+
+"[allowed]" marks items that already have `#[allow(dead_code)]`, which
+silences the compiler but is just as dead. With no arguments the three
+examples above are audited. `--deny` makes the exit status 1 when
+anything is found, for use in scripts.
+
+It does not run the compiler, so it works by name rather than by type:
+see audit.rs for what that means. There are three parts:
+
+lexer.rs    source text to tokens
+parser.rs   tokens to items, `use` declarations and references
+audit.rs    references to findings
+*/
+
+mod audit;
+mod lexer;
+mod parser;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use audit::{Finding, Problem};
+
+fn report(root: &Path, findings: &[Finding], binary: bool) {
+    println!("{} ({})", root.display(), if binary { "binary" } else { "library" });
+    if findings.is_empty() {
+        println!("  nothing found");
+    }
+    let dir = root.parent().unwrap_or_else(|| Path::new(""));
+    let sections = [
+        (Problem::Unreachable, "pub but unreachable from the crate root"),
+        (Problem::Dead, "dead"),
+        (Problem::UnusedImport, "unused imports"),
+    ];
+    for &(problem, title) in &sections {
+        let found: Vec<&Finding> = findings.iter().filter(|f| f.problem == problem).collect();
+        if found.is_empty() {
+            continue;
+        }
+        println!("  {}:", title);
+        for f in found {
+            let file = f.file.strip_prefix(dir).unwrap_or(&f.file);
+            print!("    {}  {}:{}", f.what, file.display(), f.line);
+            if let Some(ref reason) = f.reason {
+                print!("  {}", reason);
+            }
+            if f.allowed {
+                print!("  [allowed]");
+            }
+            println!();
+        }
+    }
+    println!();
+}
+
+fn main() {
+    let mut deny = false;
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for arg in env::args().skip(1) {
+        if arg == "--deny" {
+            deny = true;
+        } else {
+            roots.push(PathBuf::from(arg));
+        }
+    }
+    if roots.is_empty() {
+        let here = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for example in &["filehiearchy", "visibility", "structvisibility"] {
+            let root = here.join("..").join(example).join("src").join("main.rs");
+            roots.push(fs::canonicalize(&root).unwrap_or(root));
+        }
+    }
+
+    let mut total = 0;
+    for root in &roots {
+        match parser::load(root) {
+            Ok(krate) => {
+                let findings = audit::audit(&krate);
+                let binary = krate.items.iter().any(|i| i.parent == Some(0) && i.name == "main");
+                report(&krate.root, &findings, binary);
+                total += findings.len();
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(2);
+            }
+        }
+    }
+    if deny && total > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,651 @@
+// parser.rs
+// Builds the module tree of a crate from its root file, following `mod foo;`
+// to `foo.rs` or `foo/mod.rs` the way rustc does, and collects three things:
+//
+// items   every module, function, type, constant, macro and inherent
+//         method, with its visibility and where it was declared
+// uses    every binding made by a `use` declaration
+// refs    every path, method call and macro call in the code, with the
+//         module and item it appears in
+//
+// Anything under `#[cfg(test)]` or `#[test]` is left out, as it would be
+// in a normal build.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use lexer::{self, Delim, Tok, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Vis {
+    Private,
+    // `pub(crate)`, or `crate` on its own.
+    Crate,
+    // `pub(super)`.
+    Super,
+    // `pub(in path)`.
+    Restricted(Vec<String>),
+    Public,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Mod,
+    Fn,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Type,
+    Const,
+    Static,
+    Macro,
+    Method,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Kind::Mod => "mod",
+            Kind::Fn => "fn",
+            Kind::Struct => "struct",
+            Kind::Enum => "enum",
+            Kind::Union => "union",
+            Kind::Trait => "trait",
+            Kind::Type => "type",
+            Kind::Const => "const",
+            Kind::Static => "static",
+            Kind::Macro => "macro",
+            Kind::Method => "method",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Item {
+    pub name: String,
+    pub kind: Kind,
+    pub vis: Vis,
+    // The enclosing module, `None` only for the crate root. For a method
+    // this is the module of its `impl`.
+    pub parent: Option<usize>,
+    pub file: PathBuf,
+    pub line: usize,
+    // Has `#[allow(dead_code)]`, `#[allow(unused)]` or `#[allow(unused_macros)]`.
+    pub allowed: bool,
+    // A macro with `#[macro_export]`, usable by other crates whatever its
+    // module says.
+    pub exported: bool,
+    // For a method, the path of the `impl`'s type as written.
+    pub self_ty: Vec<String>,
+    // For a trait, the names of its methods.
+    pub members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Use {
+    pub module: usize,
+    pub path: Vec<String>,
+    // The name it binds, or `*` for a glob import.
+    pub name: String,
+    pub vis: Vis,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefKind {
+    // `a::b::c`, or a single name.
+    Path,
+    // `.name(`, which could be any method of that name.
+    Method,
+    // `name!`.
+    Macro,
+    // The path of a `use` inside a function body.
+    Use,
+}
+
+#[derive(Debug)]
+pub struct Ref {
+    pub kind: RefKind,
+    pub path: Vec<String>,
+    pub module: usize,
+    // The item whose body this is in. `None` for code that is always
+    // needed, such as the methods of a trait impl.
+    pub within: Option<usize>,
+    // Inside an `impl`, what `Self` stands for.
+    pub self_ty: Option<Vec<String>>,
+}
+
+// Item 0 is the crate root.
+#[derive(Debug)]
+pub struct Crate {
+    pub root: PathBuf,
+    pub items: Vec<Item>,
+    pub uses: Vec<Use>,
+    pub refs: Vec<Ref>,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    pub file: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.file.display(), self.message)
+    }
+}
+
+pub fn load(root: &Path) -> Result<Crate, Error> {
+    load_with(root, &|path| fs::read_to_string(path))
+}
+
+// `load`, reading files through `read`.
+pub fn load_with(root: &Path, read: &dyn Fn(&Path) -> io::Result<String>) -> Result<Crate, Error> {
+    let mut builder = Builder {
+        krate: Crate { root: root.to_path_buf(), items: Vec::new(), uses: Vec::new(), refs: Vec::new() },
+        read,
+    };
+    builder.krate.items.push(Item {
+        name: "crate".to_string(),
+        kind: Kind::Mod,
+        vis: Vis::Public,
+        parent: None,
+        file: root.to_path_buf(),
+        line: 1,
+        allowed: false,
+        exported: false,
+        self_ty: Vec::new(),
+        members: Vec::new(),
+    });
+    let dir = root.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+    builder.file(0, root, &dir)?;
+    Ok(builder.krate)
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+    "where", "while",
+];
+
+// Where in the crate some tokens are.
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    module: usize,
+    file: &'a Path,
+    // Where the files of `mod foo;` declared here are looked for.
+    dir: &'a Path,
+}
+
+#[derive(Default)]
+struct Attrs {
+    test: bool,
+    allowed: bool,
+    macro_export: bool,
+    path: Option<String>,
+}
+
+struct Builder<'a> {
+    krate: Crate,
+    read: &'a dyn Fn(&Path) -> io::Result<String>,
+}
+
+impl<'a> Builder<'a> {
+    fn file(&mut self, module: usize, file: &Path, dir: &Path) -> Result<(), Error> {
+        let source = (self.read)(file)
+            .map_err(|e| Error { file: file.to_path_buf(), message: e.to_string() })?;
+        let tokens = lexer::tokenize(&source)
+            .map_err(|e| Error { file: file.to_path_buf(), message: e.to_string() })?;
+        self.items(&tokens, Scope { module, file, dir })
+    }
+
+    fn add(&mut self, item: Item) -> usize {
+        self.krate.items.push(item);
+        self.krate.items.len() - 1
+    }
+
+    fn item(&mut self, name: &str, kind: Kind, vis: Vis, attrs: &Attrs, line: usize, scope: Scope)
+            -> usize {
+        self.add(Item {
+            name: name.to_string(),
+            kind,
+            vis,
+            parent: Some(scope.module),
+            file: scope.file.to_path_buf(),
+            line,
+            allowed: attrs.allowed,
+            exported: kind == Kind::Macro && attrs.macro_export,
+            self_ty: Vec::new(),
+            members: Vec::new(),
+        })
+    }
+
+    // The items of a module body.
+    fn items(&mut self, tokens: &[Token], scope: Scope) -> Result<(), Error> {
+        let mut i = 0;
+        while i < tokens.len() {
+            let attrs = attrs(tokens, &mut i);
+            if i >= tokens.len() {
+                break;
+            }
+            let start = i;
+            let vis = visibility(tokens, &mut i);
+            qualifiers(tokens, &mut i);
+            let keyword = tokens.get(i).and_then(|t| t.ident()).unwrap_or("");
+            let semicolon_only = matches!(keyword, "const" | "static" | "type" | "use");
+            let end = item_end(tokens, i, semicolon_only);
+            if attrs.test {
+                i = end;
+                continue;
+            }
+            let name = tokens.get(i + 1).and_then(|t| t.ident()).unwrap_or("_").to_string();
+            let line = tokens[i].line;
+
+            match keyword {
+                "mod" => self.module(&tokens[i..end], &name, vis, &attrs, scope)?,
+                "fn" | "struct" | "enum" | "union" | "trait" | "type" | "const" | "static"
+                    if name != "_" => {
+                    let kind = match keyword {
+                        "fn" => Kind::Fn,
+                        "struct" => Kind::Struct,
+                        "enum" => Kind::Enum,
+                        "union" => Kind::Union,
+                        "trait" => Kind::Trait,
+                        "type" => Kind::Type,
+                        "const" => Kind::Const,
+                        _ => Kind::Static,
+                    };
+                    // `static mut NAME`.
+                    let (name, body) = if name == "mut" {
+                        (tokens.get(i + 2).and_then(|t| t.ident()).unwrap_or("_").to_string(), i + 3)
+                    } else {
+                        (name, i + 2)
+                    };
+                    let id = self.item(&name, kind, vis, &attrs, line, scope);
+                    if kind == Kind::Trait {
+                        self.krate.items[id].members = trait_members(&tokens[body.min(end)..end]);
+                    }
+                    self.refs(&tokens[body.min(end)..end], scope, Some(id), &None);
+                }
+                "impl" => self.impl_block(&tokens[i..end], &attrs, scope),
+                "use" => self.use_decl(&tokens[i + 1..end], vis, line, scope),
+                "macro_rules" if tokens.get(i + 1).is_some_and(|t| t.is_punct('!')) => {
+                    let name = tokens.get(i + 2).and_then(|t| t.ident()).unwrap_or("_");
+                    let id = self.item(name, Kind::Macro, vis, &attrs, line, scope);
+                    self.refs(&tokens[(i + 3).min(end)..end], scope, Some(id), &None);
+                }
+                "extern" if tokens.get(i + 1).is_some_and(|t| t.is_ident("crate")) => {}
+                // Macro calls and anything else at module level.
+                _ => self.refs(&tokens[start..end], scope, None, &None),
+            }
+            i = end;
+        }
+        Ok(())
+    }
+
+    fn module(&mut self, tokens: &[Token], name: &str, vis: Vis, attrs: &Attrs, scope: Scope)
+              -> Result<(), Error> {
+        let id = self.item(name, Kind::Mod, vis, attrs, tokens[0].line, scope);
+        let dir = scope.dir.join(name);
+        if let Some(body) = tokens.get(2).and_then(|t| t.group(Delim::Brace)) {
+            return self.items(body, Scope { module: id, file: scope.file, dir: &dir });
+        }
+
+        let candidates = match attrs.path {
+            Some(ref path) => vec![scope.dir.join(path)],
+            None => vec![scope.dir.join(format!("{}.rs", name)), dir.join("mod.rs")],
+        };
+        for file in &candidates {
+            if (self.read)(file).is_ok() {
+                let dir = match attrs.path {
+                    Some(_) => file.parent().unwrap_or_else(|| Path::new("")).to_path_buf(),
+                    None => dir.clone(),
+                };
+                return self.file(id, file, &dir);
+            }
+        }
+        Err(Error {
+            file: scope.file.to_path_buf(),
+            message: format!("file not found for module `{}`, looked for {}",
+                             name,
+                             candidates.iter()
+                                 .map(|c| c.display().to_string())
+                                 .collect::<Vec<_>>()
+                                 .join(" and ")),
+        })
+    }
+
+    // `impl<...> [Trait for] Type [where ...] { ... }`. Methods of an
+    // inherent impl are items; those of a trait impl are only references,
+    // since they are reachable through the trait.
+    fn impl_block(&mut self, tokens: &[Token], impl_attrs: &Attrs, scope: Scope) {
+        let body = match tokens.last().and_then(|t| t.group(Delim::Brace)) {
+            Some(body) => body,
+            None => return self.refs(tokens, scope, None, &None),
+        };
+        let header = &tokens[1..tokens.len() - 1];
+        let mut h = 0;
+        if header.first().is_some_and(|t| t.is_punct('<')) {
+            h = skip_angles(header, 0);
+        }
+        let header_end = header.iter().position(|t| t.is_ident("where")).unwrap_or(header.len());
+        let ty_start = match header[h..header_end].iter().position(|t| t.is_ident("for")) {
+            Some(f) => h + f + 1,
+            None => h,
+        };
+        let inherent = ty_start == h;
+        let self_ty = type_path(&header[ty_start..header_end]);
+        self.refs(header, scope, None, &None);
+        let self_ref = Some(self_ty.clone());
+
+        let mut i = 0;
+        while i < body.len() {
+            let attrs = attrs(body, &mut i);
+            if i >= body.len() {
+                break;
+            }
+            let vis = visibility(body, &mut i);
+            qualifiers(body, &mut i);
+            let semicolon_only = !body.get(i).is_some_and(|t| t.is_ident("fn"));
+            let end = item_end(body, i, semicolon_only);
+            if !attrs.test {
+                let name = body.get(i + 1).and_then(|t| t.ident());
+                match name {
+                    Some(name) if inherent && body[i].is_ident("fn") => {
+                        let id = self.add(Item {
+                            name: name.to_string(),
+                            kind: Kind::Method,
+                            vis,
+                            parent: Some(scope.module),
+                            file: scope.file.to_path_buf(),
+                            line: body[i].line,
+                            allowed: attrs.allowed || impl_attrs.allowed,
+                            exported: false,
+                            self_ty: self_ty.clone(),
+                            members: Vec::new(),
+                        });
+                        self.refs(&body[(i + 2).min(end)..end], scope, Some(id), &self_ref);
+                    }
+                    _ => self.refs(&body[i..end], scope, None, &self_ref),
+                }
+            }
+            i = end;
+        }
+    }
+
+    fn use_decl(&mut self, tokens: &[Token], vis: Vis, line: usize, scope: Scope) {
+        let tokens = match tokens.last() {
+            Some(t) if t.is_punct(';') => &tokens[..tokens.len() - 1],
+            _ => tokens,
+        };
+        let mut bindings = Vec::new();
+        use_tree(tokens, &[], &mut bindings);
+        for (path, name) in bindings {
+            self.krate.uses.push(Use {
+                module: scope.module,
+                path,
+                name,
+                vis: vis.clone(),
+                file: scope.file.to_path_buf(),
+                line,
+            });
+        }
+    }
+
+    fn refs(&mut self, tokens: &[Token], scope: Scope, within: Option<usize>,
+            self_ty: &Option<Vec<String>>) {
+        let mut push = |kind, path| {
+            self.krate.refs.push(Ref { kind, path, module: scope.module, within, self_ty: self_ty.clone() });
+        };
+        let mut stack = vec![tokens];
+        while let Some(tokens) = stack.pop() {
+            let mut i = 0;
+            while i < tokens.len() {
+                let name = match tokens[i].tok {
+                    Tok::Group(_, ref body) => {
+                        stack.push(body);
+                        i += 1;
+                        continue;
+                    }
+                    Tok::Ident(ref name) => name.clone(),
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                };
+                let next = |k: usize| tokens.get(i + k);
+                let colons = |k: usize| {
+                    tokens.get(k).is_some_and(|t| t.is_punct(':'))
+                        && tokens.get(k + 1).is_some_and(|t| t.is_punct(':'))
+                };
+                let after_dot = i > 0 && tokens[i - 1].is_punct('.')
+                    && !(i > 1 && tokens[i - 2].is_punct('.'));
+                let after_colons = i > 1 && colons(i - 2);
+                let after_use = i > 0 && tokens[i - 1].is_ident("use");
+
+                if after_dot {
+                    if next(1).is_some_and(|t| t.group(Delim::Paren).is_some()) || colons(i + 1) {
+                        push(RefKind::Method, vec![name]);
+                    }
+                    i += 1;
+                } else if next(1).is_some_and(|t| t.is_punct('!'))
+                    && !next(2).is_some_and(|t| t.is_punct('=')) {
+                    push(RefKind::Macro, vec![name]);
+                    i += 1;
+                } else if after_colons
+                    || (KEYWORDS.contains(&&name[..]))
+                    || ((name == "self" || name == "super" || name == "crate") && !colons(i + 1)) {
+                    i += 1;
+                } else {
+                    // A path; a turbofish ends it, and its arguments are
+                    // scanned as paths of their own.
+                    let mut path = vec![name];
+                    let mut j = i + 1;
+                    while colons(j) {
+                        match tokens.get(j + 2).and_then(|t| t.ident()) {
+                            Some(segment) => path.push(segment.to_string()),
+                            None => break,
+                        }
+                        j += 3;
+                    }
+                    push(if after_use { RefKind::Use } else { RefKind::Path }, path);
+                    i = j;
+                }
+            }
+        }
+    }
+}
+
+// Attributes, keeping only what the audit needs.
+fn attrs(tokens: &[Token], i: &mut usize) -> Attrs {
+    let mut attrs = Attrs::default();
+    while tokens.get(*i).is_some_and(|t| t.is_punct('#')) {
+        let mut j = *i + 1;
+        let inner = tokens.get(j).is_some_and(|t| t.is_punct('!'));
+        if inner {
+            j += 1;
+        }
+        let body = match tokens.get(j).and_then(|t| t.group(Delim::Bracket)) {
+            Some(body) => body,
+            None => break,
+        };
+        // `#![...]` is about the enclosing module, not the next item.
+        if inner {
+            *i = j + 1;
+            continue;
+        }
+        let args = body.get(1).and_then(|t| t.group(Delim::Paren)).unwrap_or(&[]);
+        match body.first().and_then(|t| t.ident()) {
+            Some("test") => attrs.test = true,
+            Some("cfg") if args.len() == 1 && args[0].is_ident("test") => attrs.test = true,
+            Some("allow") if args.iter().any(|t| {
+                t.is_ident("dead_code") || t.is_ident("unused") || t.is_ident("unused_macros")
+            }) => attrs.allowed = true,
+            Some("macro_export") => attrs.macro_export = true,
+            Some("path") => {
+                if let Some(Tok::Literal(ref s)) = body.get(2).map(|t| &t.tok) {
+                    attrs.path = Some(s.trim_matches('"').to_string());
+                }
+            }
+            _ => {}
+        }
+        *i = j + 1;
+    }
+    attrs
+}
+
+fn visibility(tokens: &[Token], i: &mut usize) -> Vis {
+    let is_colon = |k: usize| tokens.get(k).is_some_and(|t| t.is_punct(':'));
+    if tokens.get(*i).is_some_and(|t| t.is_ident("crate")) && !is_colon(*i + 1) {
+        *i += 1;
+        return Vis::Crate;
+    }
+    if !tokens.get(*i).is_some_and(|t| t.is_ident("pub")) {
+        return Vis::Private;
+    }
+    *i += 1;
+    let args = match tokens.get(*i).and_then(|t| t.group(Delim::Paren)) {
+        Some(args) => args,
+        None => return Vis::Public,
+    };
+    *i += 1;
+    let words: Vec<&str> = args.iter().filter_map(|t| t.ident()).collect();
+    match words.first() {
+        Some(&"crate") => Vis::Crate,
+        Some(&"super") => Vis::Super,
+        Some(&"self") => Vis::Private,
+        Some(&"in") => Vis::Restricted(words[1..].iter().map(|w| w.to_string()).collect()),
+        _ => Vis::Public,
+    }
+}
+
+// `unsafe`, `async`, `const fn`, `extern "C" fn` and the like.
+fn qualifiers(tokens: &[Token], i: &mut usize) {
+    loop {
+        let next_is = |k: usize, words: &[&str]| {
+            tokens.get(*i + k).and_then(|t| t.ident()).is_some_and(|w| words.contains(&w))
+        };
+        let word = tokens.get(*i).and_then(|t| t.ident()).unwrap_or("");
+        match word {
+            "unsafe" | "async" | "default" if next_is(1, &["fn", "impl", "trait", "unsafe", "extern", "async"]) => *i += 1,
+            "const" if next_is(1, &["fn", "unsafe", "async", "extern"]) => *i += 1,
+            "extern" if next_is(1, &["fn"]) => *i += 1,
+            "extern" if next_is(2, &["fn"]) => *i += 2,
+            _ => return,
+        }
+    }
+}
+
+// The index just past the item starting at `i`: after its `;`, or after
+// its `{ ... }` body unless it only ends at a `;`.
+fn item_end(tokens: &[Token], i: usize, semicolon_only: bool) -> usize {
+    for (j, t) in tokens.iter().enumerate().skip(i) {
+        if t.is_punct(';') || (!semicolon_only && t.group(Delim::Brace).is_some()) {
+            return j + 1;
+        }
+    }
+    tokens.len()
+}
+
+// The names of the methods in a trait's `{ ... }`.
+fn trait_members(tokens: &[Token]) -> Vec<String> {
+    let body = match tokens.last().and_then(|t| t.group(Delim::Brace)) {
+        Some(body) => body,
+        None => return Vec::new(),
+    };
+    body.windows(2)
+        .filter(|w| w[0].is_ident("fn"))
+        .filter_map(|w| w[1].ident().map(|n| n.to_string()))
+        .collect()
+}
+
+// The index just past the `>` matching the `<` at `i`.
+fn skip_angles(tokens: &[Token], i: usize) -> usize {
+    let mut depth = 0;
+    for j in i..tokens.len() {
+        if tokens[j].is_punct('<') {
+            depth += 1;
+        } else if tokens[j].is_punct('>') && !(j > 0 && tokens[j - 1].is_punct('-')) {
+            depth -= 1;
+            if depth == 0 {
+                return j + 1;
+            }
+        }
+    }
+    tokens.len()
+}
+
+// `my::BlackBox<T>` -> `["my", "BlackBox"]`.
+fn type_path(tokens: &[Token]) -> Vec<String> {
+    let mut path = Vec::new();
+    for t in tokens {
+        match t.tok {
+            Tok::Ident(ref w) if w == "dyn" || w == "mut" => {}
+            Tok::Ident(ref w) => path.push(w.clone()),
+            Tok::Punct(':') | Tok::Punct('&') => {}
+            Tok::Lifetime(_) => {}
+            _ => break,
+        }
+    }
+    path
+}
+
+// The bindings of a use tree, as (path, name) pairs. `prefix` is the path
+// of the enclosing `{...}`.
+fn use_tree(tokens: &[Token], prefix: &[String], out: &mut Vec<(Vec<String>, String)>) {
+    let mut path = prefix.to_vec();
+    for t in tokens {
+        match t.tok {
+            Tok::Ident(ref w) => path.push(w.clone()),
+            Tok::Punct('*') => {
+                out.push((path, "*".to_string()));
+                return;
+            }
+            Tok::Group(Delim::Brace, ref body) => {
+                for part in body.split(|t| t.is_punct(',')) {
+                    if !part.is_empty() {
+                        use_tree(part, &path, out);
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+    }
+    let mut alias = None;
+    if let Some(k) = path.iter().position(|w| w == "as") {
+        alias = path.get(k + 1).cloned();
+        path.truncate(k);
+    }
+    if path.last().is_some_and(|w| w == "self") {
+        path.pop();
+    }
+    let name = match alias.or_else(|| path.last().cloned()) {
+        Some(name) => name,
+        None => return,
+    };
+    // `use Trait as _` binds nothing.
+    if name != "_" {
+        out.push((path, name));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lexer::tokenize;
+
+    #[test]
+    fn use_trees() {
+        let mut out = Vec::new();
+        let tokens = tokenize("std::{fmt, io::{self, Write as W}, collections::*}").unwrap();
+        use_tree(&tokens, &[], &mut out);
+        let out: Vec<String> = out.iter().map(|(p, n)| format!("{} {}", p.join("::"), n)).collect();
+        assert_eq!(out, ["std::fmt fmt", "std::io io", "std::io::Write W", "std::collections *"]);
+    }
+}
@@ -0,0 +1,112 @@
+// build.rs
+// Cargo runs this before compiling the crate. Whatever it prints as
+// `cargo:rustc-cfg=...` is passed on to rustc as `--cfg ...`, which is how
+// cargo does what `rustc --cfg some_condition main.rs` did by hand.
+//
+// It also writes $OUT_DIR/build_info.rs, the BUILD_INFO that main.rs
+// includes and prints with `--build-info`.
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Custom cfgs that rustc should accept without an `unexpected_cfgs` warning.
+const CFGS: &[&str] = &["some_condition", "has_let_else"];
+
+fn main() {
+    for cfg in CFGS {
+        println!("cargo:rustc-check-cfg=cfg({})", cfg);
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOME_CONDITION");
+
+    let mut cfgs = BTreeSet::new();
+
+    // From the `some_condition` feature, or from the environment:
+    // SOME_CONDITION=1 cargo run
+    let from_env = env::var("SOME_CONDITION").map(|v| !v.is_empty() && v != "0").unwrap_or(false);
+    if env::var_os("CARGO_FEATURE_SOME_CONDITION").is_some() || from_env {
+        cfgs.insert("some_condition");
+    }
+
+    // From probing the compiler: `let ... else` is stable since 1.65.
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = command_output(Command::new(&rustc).arg("--version"));
+    if version.as_ref().and_then(|v| minor_version(v)).is_some_and(|minor| minor >= 65) {
+        cfgs.insert("has_let_else");
+    }
+
+    for cfg in &cfgs {
+        println!("cargo:rustc-cfg={}", cfg);
+    }
+
+    // Cargo tells us the features as CARGO_FEATURE_<NAME>, upper cased and
+    // with `-` turned into `_`.
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(k, _)| k.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase()))
+        .collect();
+    features.sort();
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let git_hash = git_hash(&manifest_dir);
+
+    let info = format!(
+        "pub const BUILD_INFO: BuildInfo = BuildInfo {{\n    \
+             package: {:?},\n    \
+             version: {:?},\n    \
+             features: &{:?},\n    \
+             cfgs: &{:?},\n    \
+             target: {:?},\n    \
+             profile: {:?},\n    \
+             rustc: {:?},\n    \
+             git_hash: {:?},\n\
+         }};\n",
+        env::var("CARGO_PKG_NAME").unwrap(),
+        env::var("CARGO_PKG_VERSION").unwrap(),
+        features,
+        cfgs.iter().collect::<Vec<_>>(),
+        env::var("TARGET").unwrap(),
+        env::var("PROFILE").unwrap(),
+        version.unwrap_or_else(|| "unknown".to_string()),
+        git_hash,
+    );
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("build_info.rs");
+    fs::write(&out, info).unwrap();
+}
+
+fn command_output(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    Some(text.trim().to_string())
+}
+
+// "rustc 1.95.0 (59807616e 2026-04-14)" is 95.
+fn minor_version(version: &str) -> Option<u32> {
+    let number = version.split_whitespace().nth(1)?;
+    number.split('.').nth(1)?.parse().ok()
+}
+
+// The short hash of HEAD, or None when this is not a git checkout or git is
+// not installed. Rebuilds when HEAD moves.
+fn git_hash(dir: &Path) -> Option<String> {
+    let git_dir = dir.ancestors().map(|d| d.join(".git")).find(|d| d.exists())?;
+    let head = git_dir.join("HEAD");
+    println!("cargo:rerun-if-changed={}", head.display());
+    if let Ok(text) = fs::read_to_string(&head) {
+        if let Some(reference) = text.trim().strip_prefix("ref: ") {
+            let path = git_dir.join(reference);
+            // A branch is either a file of its own or a line in packed-refs.
+            let packed = git_dir.join("packed-refs");
+            for path in &[path, packed] {
+                if path.exists() {
+                    println!("cargo:rerun-if-changed={}", path.display());
+                }
+            }
+        }
+    }
+    command_output(Command::new("git").arg("-C").arg(dir).args(["rev-parse", "--short", "HEAD"]))
+}
@@ -1,16 +1,113 @@
 /*
-Some conditionals like target_os are implicitly provided by rustc, but custom 
+Some conditionals like target_os are implicitly provided by rustc, but custom
 conditionals must be passed to rustc using the --cfg flag.
 
 $ rustc --cfg some_condition main.rs && ./main
 condition met!
+
+cargo has no option for that. Instead build.rs, the build script, prints
+`cargo:rustc-cfg=some_condition`, and cargo passes it on. It does so when
+the `some_condition` feature is on, or SOME_CONDITION is set:
+
+$ cargo run --features some_condition
+condition met!
+$ SOME_CONDITION=1 cargo run
+condition met!
+$ cargo run
+condition not met
+
+A build script can also probe the toolchain: `has_let_else` is set when
+rustc is 1.65 or later.
+
+It also records how the binary was built, for when it has been shipped
+somewhere and nobody remembers:
+
+$ cargo run --release --features some_condition -- --build-info
+custom 0.1.0
+features: default, some_condition
+cfgs:     has_let_else, some_condition
+target:   x86_64-unknown-linux-gnu
+profile:  release
+rustc:    rustc 1.95.0 (59807616e 2026-04-14)
+git:      8aff47c
 */
+use std::env;
+use std::fmt;
+
+// What build.rs found out. The value is generated into $OUT_DIR.
+pub struct BuildInfo {
+    pub package: &'static str,
+    pub version: &'static str,
+    pub features: &'static [&'static str],
+    pub cfgs: &'static [&'static str],
+    pub target: &'static str,
+    pub profile: &'static str,
+    pub rustc: &'static str,
+    pub git_hash: Option<&'static str>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", self.package, self.version)?;
+        writeln!(f, "features: {}", self.features.join(", "))?;
+        writeln!(f, "cfgs:     {}", self.cfgs.join(", "))?;
+        writeln!(f, "target:   {}", self.target)?;
+        writeln!(f, "profile:  {}", self.profile)?;
+        writeln!(f, "rustc:    {}", self.rustc)?;
+        write!(f, "git:      {}", self.git_hash.unwrap_or("unknown"))
+    }
+}
+
 #[cfg(some_condition)]
 fn conditional_function() {
     println!("condition met!")
 }
 
+#[cfg(not(some_condition))]
+fn conditional_function() {
+    println!("condition not met")
+}
+
+#[cfg(has_let_else)]
+fn first_word(s: &str) -> &str {
+    let Some(word) = s.split_whitespace().next() else {
+        return "";
+    };
+    word
+}
+
+#[cfg(not(has_let_else))]
+fn first_word(s: &str) -> &str {
+    match s.split_whitespace().next() {
+        Some(word) => word,
+        None => "",
+    }
+}
+
 fn main() {
+    if env::args().skip(1).any(|arg| arg == "--build-info") {
+        println!("{}", BUILD_INFO);
+        return;
+    }
+
     conditional_function();
+    println!("first word of the rustc version: {}", first_word(BUILD_INFO.rustc));
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_info_matches_the_build() {
+        assert_eq!(BUILD_INFO.package, "custom");
+        assert_eq!(BUILD_INFO.features.contains(&"some_condition"),
+                   cfg!(feature = "some_condition"));
+        assert_eq!(BUILD_INFO.cfgs.contains(&"some_condition"), cfg!(some_condition));
+        assert_eq!(BUILD_INFO.profile == "release", !cfg!(debug_assertions));
+        assert_eq!(first_word("  rustc 1.95.0"), "rustc");
+        assert_eq!(first_word(""), "");
+    }
+}
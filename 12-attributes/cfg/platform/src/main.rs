@@ -0,0 +1,165 @@
+/*
+The cfg example asks one question at compile time, are we on linux? A
+benchmark result needs more than that to be compared with another: what the
+binary was built for, and what it actually ran on. This reports both.
+
+$ cargo run --release
+os:              linux (unix)
+arch:            x86_64, 64-bit, little endian
+target features: fxsr sse sse2
+kernel:          Linux 6.18.44 #1 SMP PREEMPT_DYNAMIC x86_64
+host:            build-07
+cpus:            16
+simd:            sse2 sse3 ssse3 sse4.1 sse4.2 avx avx2 fma, not avx512f
+page size:       4096
+memory limit:    8589934592 bytes (8.0 GiB)
+
+"target features" are what the compiler was allowed to use, so a binary
+built with `-C target-cpu=native` lists more. "simd" is what the CPU has,
+detected at run time; it is only filled in on x86 and x86_64.
+
+$ cargo run --release -- --json
+{"os": "linux", "family": "unix", "arch": "x86_64", ...}
+
+prints the same as one JSON object, for the build farm to store next to the
+results. platform.rs does the finding out; this file only prints.
+*/
+
+mod platform;
+
+use std::env;
+use std::process;
+
+use platform::{MemoryLimit, Platform};
+
+fn text(p: &Platform) -> String {
+    let mut out = String::new();
+    let mut line = |key: &str, value: String| {
+        out.push_str(&format!("{:<16} {}\n", format!("{}:", key), value));
+    };
+    line("os", format!("{} ({})", p.os, p.family));
+    line("arch", format!("{}, {}-bit, {} endian", p.arch, p.pointer_width, p.endian));
+    line("target features", p.target_features.join(" "));
+    match p.uname {
+        Some(ref u) => {
+            line("kernel", format!("{} {} {} {}", u.sysname, u.release, u.version, u.machine));
+            line("host", u.nodename.clone());
+        }
+        None => line("kernel", "unknown".to_string()),
+    }
+    line("cpus", p.cpus.map_or("unknown".to_string(), |n| n.to_string()));
+    if !p.simd.is_empty() {
+        let has: Vec<&str> = p.simd.iter().filter(|s| s.1).map(|s| s.0).collect();
+        let lacks: Vec<&str> = p.simd.iter().filter(|s| !s.1).map(|s| s.0).collect();
+        let mut simd = has.join(" ");
+        if !lacks.is_empty() {
+            simd.push_str(&format!(", not {}", lacks.join(" ")));
+        }
+        line("simd", simd);
+    }
+    line("page size", p.page_size.map_or("unknown".to_string(), |n| n.to_string()));
+    line("memory limit", match p.memory_limit {
+        MemoryLimit::Bytes(n) => format!("{} bytes ({:.1} GiB)", n, n as f64 / (1u64 << 30) as f64),
+        MemoryLimit::Unlimited => "unlimited".to_string(),
+        MemoryLimit::Unknown => "unknown".to_string(),
+    });
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_option<T: ToString>(value: Option<T>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
+
+// Keys are always present, with `null` for what could not be found out.
+fn json(p: &Platform) -> String {
+    let features: Vec<String> = p.target_features.iter().map(|f| json_string(f)).collect();
+    let uname = match p.uname {
+        Some(ref u) => format!(
+            "{{\"sysname\": {}, \"nodename\": {}, \"release\": {}, \"version\": {}, \"machine\": {}}}",
+            json_string(&u.sysname), json_string(&u.nodename), json_string(&u.release),
+            json_string(&u.version), json_string(&u.machine)),
+        None => "null".to_string(),
+    };
+    let simd: Vec<String> = p.simd.iter().map(|&(name, has)| format!("{}: {}", json_string(name), has)).collect();
+    let memory_limit = match p.memory_limit {
+        MemoryLimit::Bytes(n) => n.to_string(),
+        MemoryLimit::Unlimited => json_string("unlimited"),
+        MemoryLimit::Unknown => "null".to_string(),
+    };
+    format!("{{\"os\": {}, \"family\": {}, \"arch\": {}, \"pointer_width\": {}, \"endian\": {}, \
+             \"target_features\": [{}], \"uname\": {}, \"cpus\": {}, \"simd\": {{{}}}, \
+             \"page_size\": {}, \"memory_limit\": {}}}",
+            json_string(p.os), json_string(p.family), json_string(p.arch), p.pointer_width,
+            json_string(p.endian), features.join(", "), uname, json_option(p.cpus),
+            simd.join(", "), json_option(p.page_size), memory_limit)
+}
+
+fn main() {
+    let mut as_json = false;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => as_json = true,
+            _ => {
+                eprintln!("usage: platform [--json]");
+                process::exit(2);
+            }
+        }
+    }
+
+    let platform = Platform::detect();
+    if as_json {
+        println!("{}", json(&platform));
+    } else {
+        print!("{}", text(&platform));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use platform::Uname;
+
+    #[test]
+    fn text_and_json() {
+        let p = Platform {
+            os: "linux", family: "unix", arch: "x86_64", pointer_width: 64, endian: "little",
+            target_features: vec!["sse", "sse2"],
+            uname: Some(Uname {
+                sysname: "Linux".to_string(), nodename: "farm \"7\"".to_string(),
+                release: "6.1.0".to_string(), version: "#1 SMP".to_string(),
+                machine: "x86_64".to_string(),
+            }),
+            cpus: Some(4),
+            simd: vec![("sse2", true), ("avx512f", false)],
+            page_size: None,
+            memory_limit: MemoryLimit::Bytes(1 << 31),
+        };
+        let report = text(&p);
+        assert!(report.contains("arch:            x86_64, 64-bit, little endian\n"));
+        assert!(report.contains("simd:            sse2, not avx512f\n"));
+        assert!(report.contains("memory limit:    2147483648 bytes (2.0 GiB)\n"));
+        assert_eq!(json(&p),
+                   "{\"os\": \"linux\", \"family\": \"unix\", \"arch\": \"x86_64\", \"pointer_width\": 64, \
+                    \"endian\": \"little\", \"target_features\": [\"sse\", \"sse2\"], \
+                    \"uname\": {\"sysname\": \"Linux\", \"nodename\": \"farm \\\"7\\\"\", \
+                    \"release\": \"6.1.0\", \"version\": \"#1 SMP\", \"machine\": \"x86_64\"}, \
+                    \"cpus\": 4, \"simd\": {\"sse2\": true, \"avx512f\": false}, \
+                    \"page_size\": null, \"memory_limit\": 2147483648}");
+    }
+}
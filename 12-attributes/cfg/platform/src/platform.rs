@@ -0,0 +1,293 @@
+// platform.rs
+// What this binary was compiled for, and what it is running on. The first
+// half is all `cfg!`, settled when the binary was built; the second half is
+// asked of the machine at run time, partly through FFI to the C library.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+pub struct Platform {
+    // Compile time.
+    pub os: &'static str,
+    pub family: &'static str,
+    pub arch: &'static str,
+    pub pointer_width: usize,
+    pub endian: &'static str,
+    pub target_features: Vec<&'static str>,
+    // Run time.
+    pub uname: Option<Uname>,
+    pub cpus: Option<usize>,
+    pub simd: Vec<(&'static str, bool)>,
+    pub page_size: Option<usize>,
+    pub memory_limit: MemoryLimit,
+}
+
+// The fields of `struct utsname`, less `domainname`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uname {
+    pub sysname: String,
+    pub nodename: String,
+    pub release: String,
+    pub version: String,
+    pub machine: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryLimit {
+    Bytes(u64),
+    Unlimited,
+    // Not in a cgroup we could find, or not on linux at all.
+    Unknown,
+}
+
+impl Platform {
+    pub fn detect() -> Platform {
+        Platform {
+            os: ::std::env::consts::OS,
+            family: ::std::env::consts::FAMILY,
+            arch: ::std::env::consts::ARCH,
+            pointer_width: 8 * ::std::mem::size_of::<usize>(),
+            endian: if cfg!(target_endian = "little") { "little" } else { "big" },
+            target_features: target_features(),
+            uname: sys::uname(),
+            cpus: thread::available_parallelism().ok().map(|n| n.get()),
+            simd: simd(),
+            page_size: sys::page_size(),
+            memory_limit: memory_limit_with(&|path| fs::read_to_string(path).ok()),
+        }
+    }
+}
+
+// `cfg!` needs the feature name written out, so a macro writes out the list.
+macro_rules! enabled {
+    ($($feature:tt),*) => {{
+        let mut enabled = Vec::new();
+        $(
+            if cfg!(target_feature = $feature) {
+                enabled.push($feature);
+            }
+        )*
+        enabled
+    }};
+}
+
+// The features the compiler was allowed to use: the target's baseline plus
+// any `-C target-feature` or `-C target-cpu` given.
+fn target_features() -> Vec<&'static str> {
+    enabled!("fxsr", "sse", "sse2", "sse3", "ssse3", "sse4.1", "sse4.2", "popcnt",
+             "avx", "avx2", "fma", "bmi1", "bmi2", "avx512f",
+             "neon", "aes", "sha2", "crc", "lse")
+}
+
+// What the CPU actually has, which may well be more than the binary uses.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn simd() -> Vec<(&'static str, bool)> {
+    vec![
+        ("sse2", is_x86_feature_detected!("sse2")),
+        ("sse3", is_x86_feature_detected!("sse3")),
+        ("ssse3", is_x86_feature_detected!("ssse3")),
+        ("sse4.1", is_x86_feature_detected!("sse4.1")),
+        ("sse4.2", is_x86_feature_detected!("sse4.2")),
+        ("avx", is_x86_feature_detected!("avx")),
+        ("avx2", is_x86_feature_detected!("avx2")),
+        ("fma", is_x86_feature_detected!("fma")),
+        ("avx512f", is_x86_feature_detected!("avx512f")),
+    ]
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn simd() -> Vec<(&'static str, bool)> {
+    Vec::new()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android",
+          target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+mod sys {
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_int};
+
+    use super::Uname;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const UTSNAME_LENGTH: usize = 65;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const UTSNAME_LENGTH: usize = 256;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const SC_PAGESIZE: c_int = 30;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    const SC_PAGESIZE: c_int = 29;
+    #[cfg(target_os = "freebsd")]
+    const SC_PAGESIZE: c_int = 47;
+
+    // Laid out as in <sys/utsname.h>.
+    #[repr(C)]
+    struct utsname {
+        sysname: [c_char; UTSNAME_LENGTH],
+        nodename: [c_char; UTSNAME_LENGTH],
+        release: [c_char; UTSNAME_LENGTH],
+        version: [c_char; UTSNAME_LENGTH],
+        machine: [c_char; UTSNAME_LENGTH],
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        domainname: [c_char; UTSNAME_LENGTH],
+    }
+
+    // Both are in the C library, which is always linked.
+    mod c {
+        use std::os::raw::{c_int, c_long};
+
+        use super::utsname;
+
+        extern "C" {
+            pub fn uname(buf: *mut utsname) -> c_int;
+            pub fn sysconf(name: c_int) -> c_long;
+        }
+    }
+
+    fn field(chars: &[c_char]) -> String {
+        // `uname` NUL-terminates every field.
+        let text = unsafe { CStr::from_ptr(chars.as_ptr()) };
+        text.to_string_lossy().into_owned()
+    }
+
+    pub fn uname() -> Option<Uname> {
+        let mut buf = utsname {
+            sysname: [0; UTSNAME_LENGTH],
+            nodename: [0; UTSNAME_LENGTH],
+            release: [0; UTSNAME_LENGTH],
+            version: [0; UTSNAME_LENGTH],
+            machine: [0; UTSNAME_LENGTH],
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            domainname: [0; UTSNAME_LENGTH],
+        };
+        if unsafe { c::uname(&mut buf) } != 0 {
+            return None;
+        }
+        Some(Uname {
+            sysname: field(&buf.sysname),
+            nodename: field(&buf.nodename),
+            release: field(&buf.release),
+            version: field(&buf.version),
+            machine: field(&buf.machine),
+        })
+    }
+
+    pub fn page_size() -> Option<usize> {
+        let size = unsafe { c::sysconf(SC_PAGESIZE) };
+        if size > 0 { Some(size as usize) } else { None }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android",
+              target_os = "macos", target_os = "ios", target_os = "freebsd")))]
+mod sys {
+    use super::Uname;
+
+    pub fn uname() -> Option<Uname> {
+        None
+    }
+
+    pub fn page_size() -> Option<usize> {
+        None
+    }
+}
+
+// cgroup v1 has no way to say "no limit", so it says a very large number:
+// i64::MAX rounded down to a page, 9223372036854771712.
+const V1_UNLIMITED: u64 = 1 << 62;
+
+// The memory limit of the cgroup this process is in. /proc/self/cgroup says
+// which cgroup that is: "0::/path" under cgroup v2, "4:memory:/path" for the
+// memory controller under v1. In a container the path is often not visible
+// under /sys/fs/cgroup, and then the container's own root is the cgroup.
+// `read` reads a file, so that this can be tried on made up files.
+pub fn memory_limit_with(read: &dyn Fn(&Path) -> Option<String>) -> MemoryLimit {
+    let cgroups = match read(Path::new("/proc/self/cgroup")) {
+        Some(text) => text,
+        None => return MemoryLimit::Unknown,
+    };
+    let root = Path::new("/sys/fs/cgroup");
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    for line in cgroups.lines() {
+        let mut parts = line.splitn(3, ':');
+        let (controllers, path) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(_), Some(controllers), Some(path)) => (controllers, path.trim_start_matches('/')),
+            _ => continue,
+        };
+        if controllers.is_empty() {
+            candidates.push(root.join(path).join("memory.max"));
+            candidates.push(root.join("memory.max"));
+        } else if controllers.split(',').any(|c| c == "memory") {
+            let memory = root.join("memory");
+            candidates.push(memory.join(path).join("memory.limit_in_bytes"));
+            candidates.push(memory.join("memory.limit_in_bytes"));
+        }
+    }
+    for candidate in &candidates {
+        if let Some(text) = read(candidate) {
+            return parse_limit(&text);
+        }
+    }
+    MemoryLimit::Unknown
+}
+
+fn parse_limit(text: &str) -> MemoryLimit {
+    match text.trim() {
+        "max" => MemoryLimit::Unlimited,
+        number => match number.parse::<u64>() {
+            Ok(bytes) if bytes >= V1_UNLIMITED => MemoryLimit::Unlimited,
+            Ok(bytes) => MemoryLimit::Bytes(bytes),
+            Err(_) => MemoryLimit::Unknown,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn limit(files: &[(&str, &str)]) -> MemoryLimit {
+        let files: HashMap<PathBuf, String> =
+            files.iter().map(|&(p, t)| (PathBuf::from(p), t.to_string())).collect();
+        memory_limit_with(&|path| files.get(path).cloned())
+    }
+
+    #[test]
+    fn cgroup_memory_limits() {
+        // v2, in a visible cgroup.
+        assert_eq!(limit(&[("/proc/self/cgroup", "0::/build/job7\n"),
+                           ("/sys/fs/cgroup/build/job7/memory.max", "2147483648\n"),
+                           ("/sys/fs/cgroup/memory.max", "max\n")]),
+                   MemoryLimit::Bytes(2147483648));
+        // v2, in a container: only the root is there.
+        assert_eq!(limit(&[("/proc/self/cgroup", "0::/build/job7\n"),
+                           ("/sys/fs/cgroup/memory.max", "max\n")]),
+                   MemoryLimit::Unlimited);
+        // v1 next to an empty v2 hierarchy.
+        assert_eq!(limit(&[("/proc/self/cgroup", "5:cpu,memory:/job\n0::/\n"),
+                           ("/sys/fs/cgroup/memory/job/memory.limit_in_bytes", "9223372036854771712\n")]),
+                   MemoryLimit::Unlimited);
+        assert_eq!(limit(&[("/proc/self/cgroup", "4:memory:/job\n"),
+                           ("/sys/fs/cgroup/memory/memory.limit_in_bytes", "536870912")]),
+                   MemoryLimit::Bytes(536870912));
+        assert_eq!(limit(&[("/proc/self/cgroup", "1:cpu:/\n")]), MemoryLimit::Unknown);
+        assert_eq!(limit(&[]), MemoryLimit::Unknown);
+    }
+
+    #[test]
+    fn detect_agrees_with_cfg() {
+        let platform = Platform::detect();
+        assert_eq!(platform.pointer_width, if cfg!(target_pointer_width = "64") { 64 } else { 32 });
+        assert!(platform.cpus.unwrap_or(1) >= 1);
+        if cfg!(target_os = "linux") {
+            assert_eq!(platform.uname.unwrap().sysname, "Linux");
+            assert!(platform.page_size.unwrap().is_power_of_two());
+        }
+        if cfg!(target_arch = "x86_64") {
+            // SSE2 is part of x86_64 itself.
+            assert!(platform.target_features.contains(&"sse2"));
+            assert_eq!(platform.simd[0], ("sse2", true));
+        }
+    }
+}
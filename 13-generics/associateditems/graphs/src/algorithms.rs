@@ -0,0 +1,342 @@
+// algorithms.rs
+// Searches, orderings and shortest paths, each written once against the
+// `Graph` trait and so working on any storage that implements it.
+//
+// Nodes only promise `Copy + Eq + Hash`, so where an algorithm needs to
+// number or order them it does so itself, in the order `nodes()` gives.
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use graph::Graph;
+
+// Breadth first from `start`: `start`, then everything one edge away, then
+// two, and so on.
+pub fn bfs<G: Graph>(g: &G, start: G::Node) -> Vec<G::Node> {
+    let mut order = vec![start];
+    let mut seen: HashSet<G::Node> = order.iter().cloned().collect();
+    let mut queue: VecDeque<G::Node> = order.iter().cloned().collect();
+    while let Some(node) = queue.pop_front() {
+        for (next, _) in g.neighbors(node) {
+            if seen.insert(next) {
+                order.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    order
+}
+
+// Depth first from `start`, in the order nodes are first reached. With an
+// explicit stack rather than recursion, so a long chain can't overflow.
+pub fn dfs<G: Graph>(g: &G, start: G::Node) -> Vec<G::Node> {
+    let mut order = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if !seen.insert(node) {
+            continue;
+        }
+        order.push(node);
+        // Pushed backwards so that the first neighbor is visited first.
+        let next: Vec<G::Node> = g.neighbors(node).map(|(n, _)| n).collect();
+        stack.extend(next.into_iter().rev().filter(|n| !seen.contains(n)));
+    }
+    order
+}
+
+// Every node after all of the nodes with edges into it: for a graph of
+// "needs" edges reversed, an order to build things in. A cycle has no such
+// order, and the error is the nodes of one, from `strongly_connected`.
+pub fn topological_sort<G: Graph>(g: &G) -> Result<Vec<G::Node>, Vec<G::Node>> {
+    let nodes: Vec<G::Node> = g.nodes().collect();
+    let mut incoming: HashMap<G::Node, usize> = nodes.iter().map(|&n| (n, 0)).collect();
+    for &node in &nodes {
+        for (next, _) in g.neighbors(node) {
+            *incoming.entry(next).or_insert(0) += 1;
+        }
+    }
+    // Kahn's algorithm: take any node with nothing left pointing at it.
+    let mut ready: VecDeque<G::Node> = nodes.iter().cloned().filter(|n| incoming[n] == 0).collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(node) = ready.pop_front() {
+        order.push(node);
+        for (next, _) in g.neighbors(node) {
+            let count = incoming.get_mut(&next).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+    if order.len() == nodes.len() {
+        return Ok(order);
+    }
+    let cycle = strongly_connected(g)
+        .into_iter()
+        .find(|c| c.len() > 1 || g.neighbors(c[0]).any(|(n, _)| n == c[0]))
+        .unwrap();
+    Err(cycle)
+}
+
+// Tarjan's algorithm. Each component is a set of nodes that can all reach
+// each other; in a directed graph without cycles every node is one on its
+// own. Components come out in reverse topological order: nothing in a
+// component has an edge to a later one.
+pub fn strongly_connected<G: Graph>(g: &G) -> Vec<Vec<G::Node>> {
+    struct Tarjan<'a, G: Graph + 'a> {
+        g: &'a G,
+        index: HashMap<G::Node, usize>,
+        low: HashMap<G::Node, usize>,
+        stack: Vec<G::Node>,
+        on_stack: HashSet<G::Node>,
+        components: Vec<Vec<G::Node>>,
+    }
+
+    impl<'a, G: Graph> Tarjan<'a, G> {
+        // Numbers `node` and puts it on the stack. Returns it with its
+        // neighbors, last first, for `visit` to pop off one at a time.
+        fn enter(&mut self, node: G::Node) -> (G::Node, Vec<G::Node>) {
+            let index = self.index.len();
+            self.index.insert(node, index);
+            self.low.insert(node, index);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            let mut next: Vec<G::Node> = self.g.neighbors(node).map(|(n, _)| n).collect();
+            next.reverse();
+            (node, next)
+        }
+
+        // Depth first from `root`. As with `dfs`, the stack is explicit
+        // rather than recursion, so a long chain can't overflow: `calls`
+        // holds each node being visited and the neighbors it has left.
+        fn visit(&mut self, root: G::Node) {
+            let mut calls = vec![self.enter(root)];
+            while let Some(&mut (node, ref mut left)) = calls.last_mut() {
+                if let Some(next) = left.pop() {
+                    if !self.index.contains_key(&next) {
+                        calls.push(self.enter(next));
+                    } else if self.on_stack.contains(&next) {
+                        let low = cmp::min(self.low[&node], self.index[&next]);
+                        self.low.insert(node, low);
+                    }
+                    continue;
+                }
+                calls.pop();
+
+                // `node` is the first of its component to have been reached;
+                // the rest are above it on the stack.
+                if self.low[&node] == self.index[&node] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = self.stack.pop().unwrap();
+                        self.on_stack.remove(&member);
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.reverse();
+                    self.components.push(component);
+                }
+
+                // Back in the node that reached this one.
+                if let Some(&(parent, _)) = calls.last() {
+                    let low = cmp::min(self.low[&parent], self.low[&node]);
+                    self.low.insert(parent, low);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        g,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    for node in g.nodes() {
+        if !tarjan.index.contains_key(&node) {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
+}
+
+// The result of `dijkstra`: how far each reachable node is from the start,
+// and the way there.
+pub struct ShortestPaths<N> {
+    start: N,
+    // Distance, and the node before this one on the way.
+    found: HashMap<N, (u64, Option<N>)>,
+}
+
+impl<N: Copy + Eq + ::std::hash::Hash> ShortestPaths<N> {
+    pub fn distance(&self, to: N) -> Option<u64> {
+        self.found.get(&to).map(|&(d, _)| d)
+    }
+
+    // `start` to `to`, both included; `None` if `to` can't be reached.
+    pub fn path(&self, to: N) -> Option<Vec<N>> {
+        let mut path = vec![to];
+        let mut at = to;
+        while at != self.start {
+            at = self.found.get(&at)?.1?;
+            path.push(at);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// Dijkstra's algorithm, with what each edge costs given by `cost`, which
+// keeps the `Edge` type free to be anything.
+pub fn dijkstra<G, F>(g: &G, start: G::Node, cost: F) -> ShortestPaths<G::Node>
+    where G: Graph, F: Fn(&G::Edge) -> u64
+{
+    // Nodes aren't `Ord`, so the heap holds their numbers instead.
+    let mut numbered: Vec<G::Node> = vec![start];
+    let mut number: HashMap<G::Node, usize> = HashMap::new();
+    number.insert(start, 0);
+
+    let mut found: HashMap<G::Node, (u64, Option<G::Node>)> = HashMap::new();
+    found.insert(start, (0, None));
+    let mut done = HashSet::new();
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((0, 0)));
+
+    while let Some(Reverse((distance, n))) = heap.pop() {
+        let node = numbered[n];
+        if !done.insert(node) {
+            continue;
+        }
+        for (next, edge) in g.neighbors(node) {
+            let through = distance + cost(edge);
+            let better = found.get(&next).is_none_or(|&(d, _)| through < d);
+            if better {
+                found.insert(next, (through, Some(node)));
+                let n = *number.entry(next).or_insert_with(|| {
+                    numbered.push(next);
+                    numbered.len() - 1
+                });
+                heap.push(Reverse((through, n)));
+            }
+        }
+    }
+    ShortestPaths { start, found }
+}
+
+// Kruskal's algorithm on an undirected graph: the cheapest edges that join
+// every node to every other it is connected to, as (from, to, cost). A
+// graph in several pieces gets a tree for each. Ties go to the edge met
+// first.
+pub fn minimum_spanning_tree<G, F>(g: &G, cost: F) -> Vec<(G::Node, G::Node, u64)>
+    where G: Graph, F: Fn(&G::Edge) -> u64
+{
+    assert!(!g.is_directed(), "a spanning tree needs an undirected graph");
+    let nodes: Vec<G::Node> = g.nodes().collect();
+    let number: HashMap<G::Node, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+    let mut edges = Vec::new();
+    for &from in &nodes {
+        for (to, edge) in g.neighbors(from) {
+            // Every edge is seen from both ends; keep one.
+            if number[&from] <= number[&to] {
+                edges.push((cost(edge), number[&from], number[&to]));
+            }
+        }
+    }
+    edges.sort_by_key(|&(c, _, _)| c);
+
+    // Union-find: which tree each node is in so far.
+    let mut parent: Vec<usize> = (0..nodes.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut tree = Vec::new();
+    for (c, a, b) in edges {
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        if ra != rb {
+            parent[ra] = rb;
+            tree.push((nodes[a], nodes[b], c));
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use graph::{AdjacencyList, AdjacencyMatrix};
+
+    fn directed(edges: &[(&'static str, &'static str)]) -> AdjacencyList<&'static str, ()> {
+        let mut g = AdjacencyList::directed();
+        for &(a, b) in edges {
+            g.add_edge(a, b, ());
+        }
+        g
+    }
+
+    #[test]
+    fn searches() {
+        let g = directed(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("d", "e"), ("x", "a")]);
+        assert_eq!(bfs(&g, "a"), ["a", "b", "c", "d", "e"]);
+        assert_eq!(dfs(&g, "a"), ["a", "b", "d", "e", "c"]);
+        assert_eq!(bfs(&g, "e"), ["e"]);
+    }
+
+    #[test]
+    fn orderings_and_components() {
+        let g = directed(&[("std", "core"), ("app", "std"), ("app", "log"), ("log", "std")]);
+        let order = topological_sort(&g).unwrap();
+        let at = |n| order.iter().position(|&m| m == n).unwrap();
+        assert!(at("app") < at("log") && at("log") < at("std") && at("std") < at("core"));
+
+        let g = directed(&[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d"), ("d", "d"), ("e", "a")]);
+        assert_eq!(strongly_connected(&g), vec![vec!["d"], vec!["a", "b", "c"], vec!["e"]]);
+        assert_eq!(topological_sort(&g), Err(vec!["d"]));
+    }
+
+    #[test]
+    fn long_chains() {
+        // Deep enough to overflow the stack if either were recursive.
+        const N: u32 = 100_000;
+        let mut g = AdjacencyList::directed();
+        for i in 0..N - 1 {
+            g.add_edge(i, i + 1, ());
+        }
+        assert_eq!(strongly_connected(&g).len(), N as usize);
+        assert_eq!(topological_sort(&g).unwrap().len(), N as usize);
+
+        // Closing the chain makes it one cycle.
+        g.add_edge(N - 1, 0, ());
+        let cycle = topological_sort(&g).unwrap_err();
+        assert_eq!(cycle.len(), N as usize);
+        assert_eq!(cycle[..3], [0, 1, 2]);
+    }
+
+    #[test]
+    fn shortest_paths_and_spanning_trees() {
+        // 0 -4- 1 -1- 2, and 0 -2- 2 -5- 3
+        let mut g = AdjacencyMatrix::undirected(5);
+        for &(a, b, w) in &[(0, 1, 4), (1, 2, 1), (0, 2, 2), (2, 3, 5)] {
+            g.add_edge(a, b, w);
+        }
+        let paths = dijkstra(&g, 0, |&w| w);
+        assert_eq!(paths.distance(1), Some(3));
+        assert_eq!(paths.path(1), Some(vec![0, 2, 1]));
+        assert_eq!(paths.path(3), Some(vec![0, 2, 3]));
+        assert_eq!(paths.distance(4), None);
+        assert_eq!(paths.path(0), Some(vec![0]));
+
+        let tree = minimum_spanning_tree(&g, |&w| w);
+        assert_eq!(tree, [(1, 2, 1), (0, 2, 2), (2, 3, 5)]);
+    }
+}
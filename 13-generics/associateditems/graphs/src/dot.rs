@@ -0,0 +1,63 @@
+// dot.rs
+// Writes a graph in the DOT language of Graphviz, so that
+//
+// $ cargo run -- --dot | dot -Tsvg > deps.svg
+//
+// draws it. Nodes are named by their `Display`; edges are labelled by
+// `label`, or not at all where it gives `None`.
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use graph::Graph;
+
+// DOT identifiers in double quotes may hold anything but an unescaped quote.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn to_dot<G, F>(g: &G, name: &str, label: F) -> String
+    where G: Graph, G::Node: Display, F: Fn(&G::Edge) -> Option<String>
+{
+    let (keyword, arrow) = if g.is_directed() { ("digraph", "->") } else { ("graph", "--") };
+    let mut out = format!("{} {} {{\n", keyword, quote(name));
+    for node in g.nodes() {
+        out.push_str(&format!("    {};\n", quote(&node.to_string())));
+    }
+    // An undirected edge is seen from both ends; write it once.
+    let mut written = HashSet::new();
+    for from in g.nodes() {
+        for (to, edge) in g.neighbors(from) {
+            if !g.is_directed() && written.contains(&(to, from)) {
+                continue;
+            }
+            written.insert((from, to));
+            out.push_str(&format!("    {} {} {}", quote(&from.to_string()), arrow, quote(&to.to_string())));
+            if let Some(text) = label(edge) {
+                out.push_str(&format!(" [label={}]", quote(&text)));
+            }
+            out.push_str(";\n");
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use graph::{AdjacencyList, AdjacencyMatrix};
+
+    #[test]
+    fn directed_and_undirected() {
+        let mut deps = AdjacencyList::directed();
+        deps.add_edge("app", "say \"hi\"", "1.0");
+        assert_eq!(to_dot(&deps, "deps", |v| Some(v.to_string())),
+                   "digraph \"deps\" {\n    \"app\";\n    \"say \\\"hi\\\"\";\n    \
+                    \"app\" -> \"say \\\"hi\\\"\" [label=\"1.0\"];\n}\n");
+
+        let mut roads = AdjacencyMatrix::undirected(2);
+        roads.add_edge(0, 1, ());
+        assert_eq!(to_dot(&roads, "roads", |_| None),
+                   "graph \"roads\" {\n    \"0\";\n    \"1\";\n    \"0\" -- \"1\";\n}\n");
+    }
+}
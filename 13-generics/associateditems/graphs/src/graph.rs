@@ -0,0 +1,183 @@
+// graph.rs
+// The `Graph` trait, and two ways of storing a graph that implement it.
+//
+// Like `Contains` in associatedtypes, the trait names its types inside
+// itself, so an algorithm is written `fn bfs<G: Graph>(g: &G, ...)` and
+// not `fn bfs<N, E, G: Graph<N, E>>(g: &G, ...)`. What a node is depends on
+// the storage: an `AdjacencyList` is keyed by the node values themselves,
+// an `AdjacencyMatrix` by row number.
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+pub trait Graph {
+    // A handle on a node: cheap to copy, compare and hash.
+    type Node: Copy + Eq + Hash;
+    // What an edge carries: a weight, a label, or `()`.
+    type Edge;
+
+    fn is_directed(&self) -> bool;
+
+    // In a stable order, so that the algorithms give the same answer on
+    // every run.
+    fn nodes<'a>(&'a self) -> Box<dyn Iterator<Item = Self::Node> + 'a>;
+
+    // The edges leaving `node`, and where they go. In an undirected graph
+    // every edge leaves both of its ends.
+    fn neighbors<'a>(&'a self, node: Self::Node)
+        -> Box<dyn Iterator<Item = (Self::Node, &'a Self::Edge)> + 'a>;
+
+    fn node_count(&self) -> usize {
+        self.nodes().count()
+    }
+}
+
+// Each node with the list of its outgoing edges. Good for sparse graphs,
+// which most are.
+pub struct AdjacencyList<N, E> {
+    directed: bool,
+    edges: BTreeMap<N, Vec<(N, E)>>,
+}
+
+impl<N: Copy + Ord, E: Clone> AdjacencyList<N, E> {
+    pub fn directed() -> AdjacencyList<N, E> {
+        AdjacencyList { directed: true, edges: BTreeMap::new() }
+    }
+
+    pub fn undirected() -> AdjacencyList<N, E> {
+        AdjacencyList { directed: false, edges: BTreeMap::new() }
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.edges.entry(node).or_default();
+    }
+
+    // Adds both ends if they are not there yet.
+    pub fn add_edge(&mut self, from: N, to: N, edge: E) {
+        self.add_node(to);
+        if !self.directed && from != to {
+            self.edges.get_mut(&to).unwrap().push((from, edge.clone()));
+        }
+        self.edges.entry(from).or_default().push((to, edge));
+    }
+}
+
+impl<N: Copy + Ord + Hash, E> Graph for AdjacencyList<N, E> {
+    type Node = N;
+    type Edge = E;
+
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    fn nodes<'a>(&'a self) -> Box<dyn Iterator<Item = N> + 'a> {
+        Box::new(self.edges.keys().cloned())
+    }
+
+    fn neighbors<'a>(&'a self, node: N) -> Box<dyn Iterator<Item = (N, &'a E)> + 'a> {
+        match self.edges.get(&node) {
+            Some(out) => Box::new(out.iter().map(|&(to, ref edge)| (to, edge))),
+            None => Box::new(None.into_iter()),
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+// An n by n table, with the edge from i to j, if there is one, at row i,
+// column j. Good for dense graphs, and when "is there an edge from i to j"
+// is the usual question. Nodes are the numbers 0 to n - 1.
+pub struct AdjacencyMatrix<E> {
+    directed: bool,
+    size: usize,
+    cells: Vec<Option<E>>,
+}
+
+impl<E: Clone> AdjacencyMatrix<E> {
+    pub fn directed(size: usize) -> AdjacencyMatrix<E> {
+        AdjacencyMatrix { directed: true, size, cells: vec![None; size * size] }
+    }
+
+    pub fn undirected(size: usize) -> AdjacencyMatrix<E> {
+        AdjacencyMatrix { directed: false, size, cells: vec![None; size * size] }
+    }
+
+    // Replaces any edge already there. Panics if either end is out of range.
+    pub fn add_edge(&mut self, from: usize, to: usize, edge: E) {
+        assert!(from < self.size && to < self.size, "node out of range");
+        if !self.directed {
+            self.cells[to * self.size + from] = Some(edge.clone());
+        }
+        self.cells[from * self.size + to] = Some(edge);
+    }
+
+    // `None` if there is no such edge, or either end is out of range.
+    pub fn edge(&self, from: usize, to: usize) -> Option<&E> {
+        if from >= self.size || to >= self.size {
+            return None;
+        }
+        self.cells[from * self.size + to].as_ref()
+    }
+}
+
+impl<E> Graph for AdjacencyMatrix<E> {
+    type Node = usize;
+    type Edge = E;
+
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    fn nodes<'a>(&'a self) -> Box<dyn Iterator<Item = usize> + 'a> {
+        Box::new(0..self.size)
+    }
+
+    // Panics if `node` is out of range.
+    fn neighbors<'a>(&'a self, node: usize) -> Box<dyn Iterator<Item = (usize, &'a E)> + 'a> {
+        assert!(node < self.size, "node out of range");
+        let row = &self.cells[node * self.size..(node + 1) * self.size];
+        Box::new(row.iter().enumerate().filter_map(|(to, cell)| cell.as_ref().map(|e| (to, e))))
+    }
+
+    fn node_count(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn list_and_matrix_agree() {
+        let mut list = AdjacencyList::undirected();
+        let mut matrix = AdjacencyMatrix::undirected(3);
+        for &(a, b, w) in &[(0, 1, 5), (1, 2, 7)] {
+            list.add_edge(a, b, w);
+            matrix.add_edge(a, b, w);
+        }
+        for g in &[&list as &dyn Graph<Node = usize, Edge = i32>, &matrix] {
+            assert_eq!(g.node_count(), 3);
+            let around_1: Vec<(usize, i32)> = g.neighbors(1).map(|(n, &w)| (n, w)).collect();
+            assert_eq!(around_1, [(0, 5), (2, 7)]);
+        }
+        assert_eq!(matrix.edge(2, 1), Some(&7));
+        assert_eq!(matrix.edge(0, 2), None);
+        // Not cell (1, 1), which `0 * 3 + 4` would be.
+        assert_eq!(matrix.edge(0, 4), None);
+        assert_eq!(matrix.edge(3, 0), None);
+
+        let mut directed = AdjacencyList::directed();
+        directed.add_edge("a", "b", ());
+        assert_eq!(directed.neighbors("b").count(), 0);
+        assert_eq!(directed.nodes().collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "node out of range")]
+    fn matrix_neighbors_out_of_range() {
+        let matrix: AdjacencyMatrix<()> = AdjacencyMatrix::directed(3);
+        matrix.neighbors(3).count();
+    }
+}
@@ -0,0 +1,6 @@
+// lib.rs
+// The graph trait and the algorithms over it, as a library; main.rs is a
+// tour of it.
+pub mod algorithms;
+pub mod dot;
+pub mod graph;
@@ -0,0 +1,117 @@
+/*
+associatedtypes shows a trait, `Contains`, whose users no longer have to
+name `A` and `B` because the trait names them itself. A graph is the same
+kind of thing: what a node is and what an edge carries are decided by the
+graph, not by whoever walks it. So:
+
+trait Graph {
+    type Node: Copy + Eq + Hash;
+    type Edge;
+
+    fn is_directed(&self) -> bool;
+    fn nodes(&self) -> ...;
+    fn neighbors(&self, node: Self::Node) -> ...;
+}
+
+and `fn bfs<G: Graph>(g: &G, start: G::Node)` works for any graph at all.
+There are two here: an adjacency list keyed by node values, which suits
+the sparse graphs of dependencies, and an adjacency matrix numbered from 0,
+which suits the dense ones of distances. The algorithms know neither.
+
+They are a library, lib.rs, for other tools to use, and this program
+tries them out:
+
+graph.rs       the trait, AdjacencyList and AdjacencyMatrix
+algorithms.rs  BFS, DFS, topological sort, Tarjan's strongly connected
+               components, Dijkstra, and Kruskal's minimum spanning tree
+dot.rs         output for Graphviz
+
+$ cargo run
+app needs: app, cli, log, json, core, serde
+depth first: app, cli, log, core, json, serde
+build order: core, serde, log, json, cli, app
+with a cycle: cli -> log -> cli
+components: [core] [cli, log] [serde] [json] [app]
+5 towns, Leeds to Hull direct is 70 miles
+Dijkstra from Leeds: York 25 via Leeds, York; Hull 63 via Leeds, York, Hull; ...
+spanning tree: Leeds -- Bradford 10, Leeds -- York 25, Leeds -- Sheffield 35, ...
+
+$ cargo run -- --dot | dot -Tsvg > deps.svg
+*/
+
+extern crate graphs;
+
+use std::env;
+
+use graphs::algorithms::{bfs, dfs, dijkstra, minimum_spanning_tree, strongly_connected,
+                         topological_sort};
+use graphs::dot;
+use graphs::graph::{AdjacencyList, AdjacencyMatrix, Graph};
+
+// An edge from a crate to each crate it needs, labelled with the version.
+fn dependencies() -> AdjacencyList<&'static str, &'static str> {
+    let mut deps = AdjacencyList::directed();
+    deps.add_edge("app", "cli", "0.3");
+    deps.add_edge("app", "log", "0.4");
+    deps.add_edge("app", "json", "1.0");
+    deps.add_edge("cli", "log", "0.4");
+    deps.add_edge("cli", "core", "0.1");
+    deps.add_edge("json", "serde", "1.0");
+    deps.add_edge("log", "core", "0.1");
+    deps.add_edge("serde", "core", "0.1");
+    deps
+}
+
+fn names(nodes: &[&str]) -> String {
+    nodes.join(", ")
+}
+
+fn main() {
+    let deps = dependencies();
+    if env::args().skip(1).any(|arg| arg == "--dot") {
+        print!("{}", dot::to_dot(&deps, "dependencies", |version| Some(version.to_string())));
+        return;
+    }
+
+    println!("app needs: {}", names(&bfs(&deps, "app")));
+    println!("depth first: {}", names(&dfs(&deps, "app")));
+
+    // A crate comes before everything it needs, so build in reverse.
+    let mut order = topological_sort(&deps).unwrap();
+    order.reverse();
+    println!("build order: {}", names(&order));
+
+    let mut cyclic = dependencies();
+    cyclic.add_edge("log", "cli", "0.3");
+    match topological_sort(&cyclic) {
+        Ok(_) => println!("no cycle?"),
+        Err(cycle) => println!("with a cycle: {} -> {}", cycle.join(" -> "), cycle[0]),
+    }
+    let components: Vec<String> = strongly_connected(&cyclic)
+        .iter()
+        .map(|c| format!("[{}]", names(c)))
+        .collect();
+    println!("components: {}", components.join(" "));
+
+    // Road distances in miles, a dense graph and so a matrix.
+    let towns = ["Leeds", "York", "Hull", "Sheffield", "Bradford"];
+    let mut roads = AdjacencyMatrix::undirected(towns.len());
+    for &(a, b, miles) in &[(0, 1, 25), (0, 3, 35), (0, 4, 10), (1, 2, 38),
+                            (3, 2, 60), (4, 3, 40), (0, 2, 70)] {
+        roads.add_edge(a, b, miles);
+    }
+    println!("{} towns, Leeds to Hull direct is {} miles",
+             roads.node_count(), roads.edge(0, 2).unwrap());
+    let paths = dijkstra(&roads, 0, |&miles| miles);
+    let routes: Vec<String> = roads.nodes().skip(1).map(|town| {
+        let via: Vec<&str> = paths.path(town).unwrap().iter().map(|&t| towns[t]).collect();
+        format!("{} {} via {}", towns[town], paths.distance(town).unwrap(), names(&via))
+    }).collect();
+    println!("Dijkstra from Leeds: {}", routes.join("; "));
+
+    let tree: Vec<String> = minimum_spanning_tree(&roads, |&miles| miles)
+        .iter()
+        .map(|&(a, b, miles)| format!("{} -- {} {}", towns[a], towns[b], miles))
+        .collect();
+    println!("spanning tree: {}", tree.join(", "));
+}
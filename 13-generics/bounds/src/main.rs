@@ -14,21 +14,50 @@ One consequence is that generic instances are allowed to access the methods
 of the traits specified in the bounds. For example:
 */
 
-// A trait which implements the print marker: `{:?}`.
-use std::fmt::Debug;
+/*
+HasArea has grown into a module, shapes.rs. `Triangle` is a real shape now,
+and the TODO lines at the end of `main` work. `Shape` adds perimeter,
+centroid and bounding box to `HasArea`, for rectangles (rbe's, the same type
+as the bounding boxes), triangles, circles, ellipses and any polygon that
+doesn't cross itself:
 
-trait HasArea {
-    fn area(&self) -> f64;
+trait Shape: HasArea {
+    fn name(&self) -> &'static str;
+    fn perimeter(&self) -> f64;
+    fn centroid(&self) -> Point;
+    fn bounding_box(&self) -> geometry::Rectangle;
 }
 
-impl HasArea for Rectangle {
-    fn area(&self) -> f64 { self.length * self.height }
-}
+`&S` and `Box<S>` are shapes whenever `S` is, even `S = dyn Shape`, so one
+generic function sorts both a `Vec<Circle>` and a `Vec<Box<dyn Shape>>`:
+
+fn sort_by_area<S: Shape>(shapes: &mut [S])
+
+$ cargo run
+Rectangle { p1: Point { x: 0.0, y: 0.0 }, p2: Point { x: 3.0, y: 4.0 } }
+Area: 12
+Triangle { a: Point { x: 0.0, y: 0.0 }, b: Point { x: 3.0, y: 0.0 }, c: Point { x: 0.0, y: 4.0 } }
+Area: 6
+an arrow of 7 vertices
+triangle     area   6.00  perimeter 12.00  centroid (1, 1.3333333333333333)  box [(0, 0) - (3, 4)]
+circle       area   7.07  perimeter  9.42  centroid (10, 10)  box [(8.5, 8.5) - (11.5, 11.5)]
+...
+total area 44.49, largest a rectangle
+1 x circle: 7.07
+...
+bow tie: edges 0 and 2 cross
+*/
 
-#[derive(Debug)]
-struct Rectangle { length: f64, height: f64 }
-#[allow(dead_code)]
-struct Triangle  { length: f64, height: f64 }
+extern crate rbe;
+
+mod shapes;
+
+// A trait which implements the print marker: `{:?}`.
+use std::fmt::Debug;
+
+use rbe::geometry::{Point, Rectangle};
+use shapes::{area_by_kind, largest, sort_by_area, total_area, Circle, Ellipse, HasArea,
+             Polygon, Shape, Triangle};
 
 // The generic `T` must implement `Debug`. Regardless
 // of the type, this will work properly.
@@ -41,16 +70,46 @@ fn print_debug<T: Debug>(t: &T) {
 fn area<T: HasArea>(t: &T) -> f64 { t.area() }
 
 fn main() {
-    let rectangle = Rectangle { length: 3.0, height: 4.0 };
-    let _triangle = Triangle  { length: 3.0, height: 4.0 };
+    let rectangle = Rectangle::new(Point::origin(), Point::new(3.0, 4.0));
+    let triangle = Triangle::new(Point::origin(), Point::new(3.0, 0.0), Point::new(0.0, 4.0));
 
     print_debug(&rectangle);
     println!("Area: {}", area(&rectangle));
 
-    //print_debug(&_triangle);
-    //println!("Area: {}", area(&_triangle));
-    // ^ TODO: Try uncommenting these.
-    // | Error: Does not implement either `Debug` or `HasArea`. 
+    // These were the TODO: `Triangle` implements both `Debug` and
+    // `HasArea` now.
+    print_debug(&triangle);
+    println!("Area: {}", area(&triangle));
+
+    // A drawing holds shapes of every kind, so as trait objects.
+    let arrow = Polygon::new(vec![Point::new(0.0, 1.0), Point::new(3.0, 1.0), Point::new(3.0, 0.0),
+                                  Point::new(5.0, 2.0), Point::new(3.0, 4.0), Point::new(3.0, 3.0),
+                                  Point::new(0.0, 3.0)]).unwrap();
+    println!("an arrow of {} vertices", arrow.vertices().len());
+    let mut drawing: Vec<Box<dyn Shape>> = vec![
+        Box::new(rectangle),
+        Box::new(triangle),
+        Box::new(Circle::new(Point::new(10.0, 10.0), 1.5)),
+        Box::new(Ellipse::new(Point::new(-4.0, 0.0), 3.0, 1.0)),
+        Box::new(arrow),
+    ];
+    sort_by_area(&mut drawing);
+    for shape in &drawing {
+        println!("{:<12} area {:6.2}  perimeter {:5.2}  centroid {}  box {}",
+                 shape.name(), shape.area(), shape.perimeter(), shape.centroid(),
+                 shape.bounding_box());
+    }
+    println!("total area {:.2}, largest a {}", total_area(&drawing),
+             largest(&drawing).unwrap().name());
+    for (kind, (count, total)) in area_by_kind(&drawing) {
+        println!("{} x {}: {:.2}", count, kind, total);
+    }
+
+    // A crossed polygon is refused.
+    let bow_tie = vec![Point::origin(), Point::new(2.0, 2.0), Point::new(2.0, 0.0), Point::new(0.0, 2.0)];
+    if let Err(e) = Polygon::new(bow_tie) {
+        println!("bow tie: {}", e);
+    }
 }
 
 /*
@@ -0,0 +1,401 @@
+// shapes.rs
+// `HasArea` from main.rs, and `Shape`, which is everything else a drawing
+// needs to know about a shape: how far round it is, where its middle is,
+// and what box it fits in. Points, rectangles and bounding boxes are the
+// shared `Point` and `Rectangle` of the rbe library.
+//
+// Every shape works both as a bound, `fn f<S: Shape>(s: &S)`, and as a
+// trait object, `Box<dyn Shape>`, so a drawing can hold a mix of them.
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fmt;
+
+use rbe::geometry::{self, Point};
+
+pub trait HasArea {
+    fn area(&self) -> f64;
+}
+
+pub trait Shape: HasArea {
+    fn name(&self) -> &'static str;
+    fn perimeter(&self) -> f64;
+    // The center of mass, for a shape of even thickness.
+    fn centroid(&self) -> Point;
+    // The smallest upright rectangle that holds the whole shape.
+    fn bounding_box(&self) -> geometry::Rectangle;
+}
+
+// `Box<dyn Shape>` and `&S` are shapes too, so the functions at the end of
+// the file take a slice of any of them.
+impl<S: Shape + ?Sized> HasArea for &S {
+    fn area(&self) -> f64 { (**self).area() }
+}
+
+impl<S: Shape + ?Sized> Shape for &S {
+    fn name(&self) -> &'static str { (**self).name() }
+    fn perimeter(&self) -> f64 { (**self).perimeter() }
+    fn centroid(&self) -> Point { (**self).centroid() }
+    fn bounding_box(&self) -> geometry::Rectangle { (**self).bounding_box() }
+}
+
+impl<S: Shape + ?Sized> HasArea for Box<S> {
+    fn area(&self) -> f64 { (**self).area() }
+}
+
+impl<S: Shape + ?Sized> Shape for Box<S> {
+    fn name(&self) -> &'static str { (**self).name() }
+    fn perimeter(&self) -> f64 { (**self).perimeter() }
+    fn centroid(&self) -> Point { (**self).centroid() }
+    fn bounding_box(&self) -> geometry::Rectangle { (**self).bounding_box() }
+}
+
+// The box around some points, as the lower left and upper right corners.
+fn bounds(points: &[Point]) -> geometry::Rectangle {
+    let mut low = points[0];
+    let mut high = points[0];
+    for p in &points[1..] {
+        low = Point::new(low.x.min(p.x), low.y.min(p.y));
+        high = Point::new(high.x.max(p.x), high.y.max(p.y));
+    }
+    geometry::Rectangle::new(low, high)
+}
+
+// rbe's `Rectangle` is the rectangle shape as well as the bounding box.
+// Its own `area` and `perimeter` come first in method lookup, so these
+// call them by path.
+impl HasArea for geometry::Rectangle {
+    fn area(&self) -> f64 { geometry::Rectangle::area(self) }
+}
+
+impl Shape for geometry::Rectangle {
+    fn name(&self) -> &'static str { "rectangle" }
+    fn perimeter(&self) -> f64 { geometry::Rectangle::perimeter(self) }
+    fn centroid(&self) -> Point { self.center() }
+
+    // Itself, but with the corners lower left and upper right.
+    fn bounding_box(&self) -> geometry::Rectangle {
+        bounds(&[self.p1, self.p2])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub a: Point,
+    pub b: Point,
+    pub c: Point,
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point) -> Triangle {
+        Triangle { a, b, c }
+    }
+}
+
+impl HasArea for Triangle {
+    fn area(&self) -> f64 {
+        signed_area(&[self.a, self.b, self.c]).abs()
+    }
+}
+
+impl Shape for Triangle {
+    fn name(&self) -> &'static str { "triangle" }
+
+    fn perimeter(&self) -> f64 {
+        self.a.distance(&self.b) + self.b.distance(&self.c) + self.c.distance(&self.a)
+    }
+
+    fn centroid(&self) -> Point {
+        Point::new((self.a.x + self.b.x + self.c.x) / 3.0, (self.a.y + self.b.y + self.c.y) / 3.0)
+    }
+
+    fn bounding_box(&self) -> geometry::Rectangle {
+        bounds(&[self.a, self.b, self.c])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point, radius: f64) -> Circle {
+        Circle { center, radius }
+    }
+}
+
+impl HasArea for Circle {
+    fn area(&self) -> f64 { PI * self.radius * self.radius }
+}
+
+impl Shape for Circle {
+    fn name(&self) -> &'static str { "circle" }
+    fn perimeter(&self) -> f64 { 2.0 * PI * self.radius }
+    fn centroid(&self) -> Point { self.center }
+
+    fn bounding_box(&self) -> geometry::Rectangle {
+        Ellipse::new(self.center, self.radius, self.radius).bounding_box()
+    }
+}
+
+// An upright ellipse: `rx` across, `ry` up and down, from the center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipse {
+    pub center: Point,
+    pub rx: f64,
+    pub ry: f64,
+}
+
+impl Ellipse {
+    pub fn new(center: Point, rx: f64, ry: f64) -> Ellipse {
+        Ellipse { center, rx, ry }
+    }
+}
+
+impl HasArea for Ellipse {
+    fn area(&self) -> f64 { PI * self.rx * self.ry }
+}
+
+impl Shape for Ellipse {
+    fn name(&self) -> &'static str { "ellipse" }
+
+    // There is no exact formula. Ramanujan's second approximation is good
+    // to about one part in a hundred thousand even for a long thin ellipse.
+    fn perimeter(&self) -> f64 {
+        let (a, b) = (self.rx, self.ry);
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+
+    fn centroid(&self) -> Point { self.center }
+
+    fn bounding_box(&self) -> geometry::Rectangle {
+        let (c, rx, ry) = (self.center, self.rx, self.ry);
+        geometry::Rectangle::new(Point::new(c.x - rx, c.y - ry), Point::new(c.x + rx, c.y + ry))
+    }
+}
+
+// Twice the area, positive when the points go round anticlockwise: the
+// shoelace formula, named for the criss-cross of the products.
+fn signed_area(points: &[Point]) -> f64 {
+    let n = points.len();
+    let twice: f64 = (0..n).map(|i| {
+        let (p, q) = (points[i], points[(i + 1) % n]);
+        p.x * q.y - q.x * p.y
+    }).sum();
+    twice / 2.0
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolygonError {
+    TooFewVertices(usize),
+    // All the vertices are on one line.
+    NoArea,
+    // Two edges, by the number of the vertex each starts at, that cross.
+    SelfIntersecting(usize, usize),
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PolygonError::TooFewVertices(n) => write!(f, "a polygon needs 3 vertices, not {}", n),
+            PolygonError::NoArea => write!(f, "the polygon has no area"),
+            PolygonError::SelfIntersecting(i, j) => write!(f, "edges {} and {} cross", i, j),
+        }
+    }
+}
+
+// A simple polygon: one that doesn't cross itself. The vertices go round
+// in either direction, and the last joins back to the first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Result<Polygon, PolygonError> {
+        let n = vertices.len();
+        if n < 3 {
+            return Err(PolygonError::TooFewVertices(n));
+        }
+        // Edge i runs from vertex i to vertex i + 1. Edges next to each
+        // other share a vertex, so only the others may not meet at all.
+        // This looks at every pair, which is fine for the few dozen
+        // vertices of a drawing.
+        for i in 0..n {
+            for j in i + 2..n {
+                if i == 0 && j == n - 1 {
+                    continue;
+                }
+                let (p, q) = (vertices[i], vertices[(i + 1) % n]);
+                let (r, s) = (vertices[j], vertices[(j + 1) % n]);
+                if segments_meet(p, q, r, s) {
+                    return Err(PolygonError::SelfIntersecting(i, j));
+                }
+            }
+        }
+        // A bow tie's two halves cancel out, so this only comes after.
+        if signed_area(&vertices) == 0.0 {
+            return Err(PolygonError::NoArea);
+        }
+        Ok(Polygon { vertices })
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+}
+
+// Which side of the line through p and q the point r is: positive for
+// left, negative for right, zero for on it.
+fn turn(p: Point, q: Point, r: Point) -> f64 {
+    (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x)
+}
+
+// Whether segment pq and segment rs have any point in common, including
+// touching at an end or overlapping along a line.
+fn segments_meet(p: Point, q: Point, r: Point, s: Point) -> bool {
+    let within = |a: Point, b: Point, c: Point| {
+        c.x >= a.x.min(b.x) && c.x <= a.x.max(b.x) && c.y >= a.y.min(b.y) && c.y <= a.y.max(b.y)
+    };
+    let (d1, d2) = (turn(r, s, p), turn(r, s, q));
+    let (d3, d4) = (turn(p, q, r), turn(p, q, s));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0)) {
+        return true;
+    }
+    (d1 == 0.0 && within(r, s, p)) || (d2 == 0.0 && within(r, s, q))
+        || (d3 == 0.0 && within(p, q, r)) || (d4 == 0.0 && within(p, q, s))
+}
+
+impl HasArea for Polygon {
+    fn area(&self) -> f64 {
+        signed_area(&self.vertices).abs()
+    }
+}
+
+impl Shape for Polygon {
+    fn name(&self) -> &'static str { "polygon" }
+
+    fn perimeter(&self) -> f64 {
+        let n = self.vertices.len();
+        (0..n).map(|i| self.vertices[i].distance(&self.vertices[(i + 1) % n])).sum()
+    }
+
+    // The shoelace again, each term weighted by where its edge is.
+    fn centroid(&self) -> Point {
+        let v = &self.vertices;
+        let n = v.len();
+        let (mut cx, mut cy) = (0.0, 0.0);
+        for i in 0..n {
+            let (p, q) = (v[i], v[(i + 1) % n]);
+            let cross = p.x * q.y - q.x * p.y;
+            cx += (p.x + q.x) * cross;
+            cy += (p.y + q.y) * cross;
+        }
+        let six_area = 6.0 * signed_area(v);
+        Point::new(cx / six_area, cy / six_area)
+    }
+
+    fn bounding_box(&self) -> geometry::Rectangle {
+        bounds(&self.vertices)
+    }
+}
+
+// Smallest first. Works on `Vec<Circle>` and on `Vec<Box<dyn Shape>>`.
+pub fn sort_by_area<S: Shape>(shapes: &mut [S]) {
+    shapes.sort_by(|a, b| a.area().total_cmp(&b.area()));
+}
+
+pub fn total_area<S: Shape>(shapes: &[S]) -> f64 {
+    shapes.iter().map(|s| s.area()).sum()
+}
+
+pub fn largest<S: Shape>(shapes: &[S]) -> Option<&S> {
+    shapes.iter().max_by(|a, b| a.area().total_cmp(&b.area()))
+}
+
+// How many of each kind of shape there are, and their total area.
+pub fn area_by_kind<S: Shape>(shapes: &[S]) -> BTreeMap<&'static str, (usize, f64)> {
+    let mut kinds = BTreeMap::new();
+    for s in shapes {
+        let entry = kinds.entry(s.name()).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += s.area();
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn polygon(points: &[(f64, f64)]) -> Result<Polygon, PolygonError> {
+        Polygon::new(points.iter().map(|&(x, y)| Point::new(x, y)).collect())
+    }
+
+    #[test]
+    fn measurements() {
+        let t = Triangle::new(Point::origin(), Point::new(4.0, 0.0), Point::new(0.0, 3.0));
+        assert!(close(t.area(), 6.0));
+        assert!(close(t.perimeter(), 12.0));
+        assert_eq!(t.centroid(), Point::new(4.0 / 3.0, 1.0));
+
+        let e = Ellipse::new(Point::new(1.0, 1.0), 2.0, 2.0);
+        assert!(close(e.perimeter(), Circle::new(Point::origin(), 2.0).perimeter()));
+        assert_eq!(e.bounding_box(), geometry::Rectangle::new(Point::new(-1.0, -1.0), Point::new(3.0, 3.0)));
+        // a = 10, b = 1 is 40.63974 by numerical integration.
+        assert!((Ellipse::new(Point::origin(), 10.0, 1.0).perimeter() - 40.639_74).abs() < 1e-3);
+
+        // An L: a 2 x 2 square with the top right 1 x 1 taken out,
+        // clockwise.
+        let l = polygon(&[(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]).unwrap();
+        assert!(close(l.area(), 3.0));
+        assert!(close(l.perimeter(), 8.0));
+        let c = l.centroid();
+        assert!(close(c.x, 5.0 / 6.0) && close(c.y, 5.0 / 6.0));
+        assert_eq!(l.bounding_box().area(), 4.0);
+    }
+
+    #[test]
+    fn polygons_must_be_simple() {
+        assert_eq!(polygon(&[(0.0, 0.0), (1.0, 1.0)]), Err(PolygonError::TooFewVertices(2)));
+        assert_eq!(polygon(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]), Err(PolygonError::NoArea));
+        // A bow tie.
+        assert_eq!(polygon(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]),
+                   Err(PolygonError::SelfIntersecting(0, 2)));
+        // A vertex touching another edge.
+        assert_eq!(polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (2.0, 0.0), (0.0, 4.0)]),
+                   Err(PolygonError::SelfIntersecting(0, 2)));
+        assert!(polygon(&[(0.0, 0.0), (4.0, 0.0), (2.0, 1.0), (4.0, 4.0), (0.0, 4.0)]).is_ok());
+    }
+
+    #[test]
+    fn mixed_collections() {
+        let mut shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new(Circle::new(Point::origin(), 1.0)),
+            Box::new(geometry::Rectangle::new(Point::origin(), Point::new(1.0, 2.0))),
+            Box::new(geometry::Rectangle::new(Point::new(3.0, 3.0), Point::origin())),
+            Box::new(polygon(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]).unwrap()),
+        ];
+        sort_by_area(&mut shapes);
+        let names: Vec<&str> = shapes.iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["polygon", "rectangle", "circle", "rectangle"]);
+        assert!(close(total_area(&shapes), 0.5 + 2.0 + PI + 9.0));
+        assert_eq!(largest(&shapes).unwrap().area(), 9.0);
+        assert_eq!(area_by_kind(&shapes)["rectangle"], (2, 11.0));
+        let r = &shapes[3];
+        assert_eq!(r.centroid(), Point::new(1.5, 1.5));
+        assert_eq!(r.bounding_box(), geometry::Rectangle::new(Point::origin(), Point::new(3.0, 3.0)));
+        assert_eq!(r.perimeter(), 12.0);
+
+        // The same functions, statically dispatched.
+        let circles = [Circle::new(Point::origin(), 2.0), Circle::new(Point::origin(), 1.0)];
+        assert_eq!(largest(&circles).unwrap().radius, 2.0);
+    }
+}
@@ -15,12 +15,28 @@ impl<U> Add for T<U> {
     ...
 }
 
-The whole implementation:
+The whole implementation follows. That is where the example stops: inches
+add to inches, millimeters to millimeters, and nothing turns one into the
+other. units.rs carries on, with length, mass, time and temperature, the
+units of each, conversions between them, and the quantities derived by
+multiplying and dividing:
+
+one foot + one_foot = Quantity(24.0 in)
+one foot is 304.8 mm
+1.5 km is 0.9320567883560009 mi
+a mile in 4 min is 24.1402 km/h
+a 100 m by 50 m field is 5000 m²
+-40 °C is -40 °F
 */
 
+mod units;
+
 use std::ops::Add;
 use std::marker::PhantomData;
 
+use units::{Celsius, Fahrenheit, Kilometer, KilometerPerHour, Meter, Mile, Minute,
+            Quantity, SquareMeter};
+
 /// Create void enumerations to define unit types.
 #[derive(Debug, Clone, Copy)]
 enum Inch {}
@@ -64,5 +80,32 @@ fn main() {
     // Nonsensical operations fail as they should:
     // Compile-time Error: type mismatch.
     //let one_feter = one_foot + one_meter;
+
+    // The same with `units::Quantity`, which knows how to convert.
+    let one_foot: Quantity<units::Inch> = Quantity::new(12.0);
+    println!("one foot + one_foot = {:?}", one_foot + one_foot);
+    println!("one foot is {:.1}", one_foot.to::<units::Millimeter>());
+
+    // `FromStr` accepts any unit of the right dimension.
+    let distance: Quantity<Meter> = "1.5 km".parse().unwrap();
+    let miles: Quantity<Mile> = distance.into();
+    println!("1.5 km is {}", miles);
+
+    // Length / Time = Speed, worked out by the compiler.
+    let mile: Quantity<Mile> = Quantity::new(1.0);
+    let speed = mile / Quantity::<Minute>::new(4.0);
+    println!("a mile in 4 min is {:.4}", speed.to::<KilometerPerHour>());
+
+    // Length * Length = Area; it comes out in square meters.
+    let field: Quantity<SquareMeter> = Quantity::<Kilometer>::new(0.1) * Quantity::<Meter>::new(50.0);
+    println!("a 100 m by 50 m field is {}", field);
+
+    let cold: Quantity<Celsius> = Quantity::new(-40.0);
+    println!("{} is {:.0}", cold, cold.to::<Fahrenheit>());
+
+    // Compile-time Errors: there is no `Product` of length and time, and an
+    // hour is not a length.
+    //let nonsense = mile * Quantity::<units::Hour>::new(1.0);
+    //let nonsense: Quantity<Meter> = Quantity::<units::Hour>::new(1.0).into();
 }
 
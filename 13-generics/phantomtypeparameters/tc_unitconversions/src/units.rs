@@ -0,0 +1,457 @@
+// units.rs
+// `Length<Unit>` from main.rs, grown into quantities of any dimension.
+//
+// A `Quantity<U>` is an `f64` and a phantom unit `U`. Every unit belongs to
+// one dimension: `Inch` and `Meter` to `Length`, `Pound` to `Mass`, and so
+// on. Quantities of the same unit add; quantities of the same dimension
+// convert; and multiplying or dividing two quantities gives a quantity of
+// the derived dimension, in its SI unit:
+//
+// Quantity<Inch> * Quantity<Foot>  = Quantity<SquareMeter>
+// Quantity<Mile> / Quantity<Hour>  = Quantity<MeterPerSecond>
+// Quantity<Inch> * Quantity<Pound>   does not compile: no such dimension
+//
+// All of it is checked by the compiler, through the `Unit`, `Dimension`,
+// `Product` and `Quotient` traits. At run time a quantity is just its `f64`.
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+pub trait Dimension {
+    // The SI unit, which products and quotients are given in.
+    type Base: Unit<Dimension = Self>;
+    const NAME: &'static str;
+
+    // The `(factor, offset)` of the unit with this symbol or name.
+    fn lookup(symbol: &str) -> Option<(f64, f64)>;
+}
+
+// A unit is `FACTOR` base units, plus `OFFSET` for temperatures:
+// base = value * FACTOR + OFFSET.
+pub trait Unit {
+    type Dimension: Dimension;
+    const SYMBOL: &'static str;
+    const FACTOR: f64;
+    const OFFSET: f64 = 0.0;
+}
+
+// `A * B = Output` and `A / B = Output`, for dimensions.
+pub trait Product<Rhs> {
+    type Output: Dimension;
+}
+
+pub trait Quotient<Rhs> {
+    type Output: Dimension;
+}
+
+pub struct Quantity<U>(f64, PhantomData<U>);
+
+impl<U: Unit> Quantity<U> {
+    pub fn new(value: f64) -> Quantity<U> {
+        Quantity(value, PhantomData)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    fn base(self) -> f64 {
+        self.0 * U::FACTOR + U::OFFSET
+    }
+
+    fn from_base(base: f64) -> Quantity<U> {
+        Quantity::new((base - U::OFFSET) / U::FACTOR)
+    }
+
+    // The same quantity in another unit of the same dimension.
+    pub fn to<V: Unit<Dimension = U::Dimension>>(self) -> Quantity<V> {
+        Quantity::from_base(self.base())
+    }
+}
+
+// Derived by hand, because `#[derive]` would require `U: Clone` and so on,
+// and units are empty enums that can't be anything.
+impl<U> Clone for Quantity<U> {
+    fn clone(&self) -> Quantity<U> {
+        *self
+    }
+}
+
+impl<U> Copy for Quantity<U> {}
+
+impl<U> PartialEq for Quantity<U> {
+    fn eq(&self, other: &Quantity<U>) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<U> PartialOrd for Quantity<U> {
+    fn partial_cmp(&self, other: &Quantity<U>) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<U: Unit> fmt::Debug for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Quantity({:?} {})", self.0, U::SYMBOL)
+    }
+}
+
+// `12 in`, and `{:.1}` gives `12.0 in`.
+impl<U: Unit> fmt::Display for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, self.0, U::SYMBOL),
+            None => write!(f, "{} {}", self.0, U::SYMBOL),
+        }
+    }
+}
+
+// Only the same unit adds: `Inch + Mm` is still an error, as in main.rs.
+impl<U> Add for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn add(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity(self.0 + rhs.0, PhantomData)
+    }
+}
+
+impl<U> Sub for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn sub(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity(self.0 - rhs.0, PhantomData)
+    }
+}
+
+impl<U> Neg for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn neg(self) -> Quantity<U> {
+        Quantity(-self.0, PhantomData)
+    }
+}
+
+// Scaling by a plain number keeps the unit.
+impl<U> Mul<f64> for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn mul(self, rhs: f64) -> Quantity<U> {
+        Quantity(self.0 * rhs, PhantomData)
+    }
+}
+
+impl<U> Mul<Quantity<U>> for f64 {
+    type Output = Quantity<U>;
+
+    fn mul(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity(self * rhs.0, PhantomData)
+    }
+}
+
+impl<U> Div<f64> for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn div(self, rhs: f64) -> Quantity<U> {
+        Quantity(self.0 / rhs, PhantomData)
+    }
+}
+
+impl<A: Unit, B: Unit> Mul<Quantity<B>> for Quantity<A>
+    where A::Dimension: Product<B::Dimension>
+{
+    type Output = Quantity<<<A::Dimension as Product<B::Dimension>>::Output as Dimension>::Base>;
+
+    fn mul(self, rhs: Quantity<B>) -> Self::Output {
+        Quantity::new(self.base() * rhs.base())
+    }
+}
+
+impl<A: Unit, B: Unit> Div<Quantity<B>> for Quantity<A>
+    where A::Dimension: Quotient<B::Dimension>
+{
+    type Output = Quantity<<<A::Dimension as Quotient<B::Dimension>>::Output as Dimension>::Base>;
+
+    fn div(self, rhs: Quantity<B>) -> Self::Output {
+        Quantity::new(self.base() / rhs.base())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseQuantityError {
+    Number(String),
+    MissingUnit,
+    // The unit given, and the dimension it should have been a unit of.
+    Unit(String, &'static str),
+}
+
+impl fmt::Display for ParseQuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseQuantityError::Number(ref s) => write!(f, "`{}` does not start with a number", s),
+            ParseQuantityError::MissingUnit => write!(f, "no unit given"),
+            ParseQuantityError::Unit(ref unit, dimension) =>
+                write!(f, "`{}` is not a unit of {}", unit, dimension),
+        }
+    }
+}
+
+impl error::Error for ParseQuantityError {}
+
+// `12 in`, `12in`, `1.5 km`, `-40 °F`: a number, then any unit of the
+// right dimension, which is converted from.
+impl<U: Unit> FromStr for Quantity<U> {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Quantity<U>, ParseQuantityError> {
+        let s = s.trim();
+        // The longest prefix that is a number, so that `1e3 m` is 1000 m.
+        let split = s.char_indices()
+            .map(|(i, _)| i)
+            .skip(1)
+            .chain(Some(s.len()))
+            .filter(|&i| s[..i].trim_end().parse::<f64>().is_ok())
+            .last();
+        let split = match split {
+            Some(i) => i,
+            None => return Err(ParseQuantityError::Number(s.to_string())),
+        };
+        let value: f64 = s[..split].trim_end().parse().unwrap();
+        let symbol = s[split..].trim();
+        if symbol.is_empty() {
+            return Err(ParseQuantityError::MissingUnit);
+        }
+        match U::Dimension::lookup(symbol) {
+            Some((factor, offset)) => Ok(Quantity::from_base(value * factor + offset)),
+            None => Err(ParseQuantityError::Unit(symbol.to_string(),
+                                                 <U::Dimension as Dimension>::NAME)),
+        }
+    }
+}
+
+// Converts both ways between every pair of units of a dimension, so that
+// `Quantity::<Foot>::from(inches)` and `let feet: Quantity<Foot> =
+// inches.into()` work. `From<T> for T` is in std already, so pairs only.
+macro_rules! conversions {
+    () => {};
+    ($first:ident $(, $rest:ident)*) => {
+        $(
+            impl From<Quantity<$first>> for Quantity<$rest> {
+                fn from(q: Quantity<$first>) -> Quantity<$rest> {
+                    q.to()
+                }
+            }
+
+            impl From<Quantity<$rest>> for Quantity<$first> {
+                fn from(q: Quantity<$rest>) -> Quantity<$first> {
+                    q.to()
+                }
+            }
+        )*
+        conversions!($($rest),*);
+    };
+}
+
+// A dimension and its units. Each unit is a factor (and, for temperatures,
+// an offset) and then its symbols: the first is what `Display` prints, and
+// any of them is accepted by `FromStr`.
+macro_rules! dimension {
+    ($dimension:ident $name:expr, base $base:ident;
+     $($unit:ident = $factor:expr, $offset:expr; $symbol:tt $(, $alias:tt)*;)+) => {
+        pub enum $dimension {}
+
+        impl Dimension for $dimension {
+            type Base = $base;
+            const NAME: &'static str = $name;
+
+            fn lookup(symbol: &str) -> Option<(f64, f64)> {
+                match symbol {
+                    $($symbol $(| $alias)* => Some(($factor, $offset)),)+
+                    _ => None,
+                }
+            }
+        }
+
+        $(
+            pub enum $unit {}
+
+            impl Unit for $unit {
+                type Dimension = $dimension;
+                const SYMBOL: &'static str = $symbol;
+                const FACTOR: f64 = $factor;
+                const OFFSET: f64 = $offset;
+            }
+        )+
+
+        conversions!($($unit),+);
+    };
+}
+
+dimension! { Length "length", base Meter;
+    Meter = 1.0, 0.0; "m", "meter", "meters";
+    Millimeter = 0.001, 0.0; "mm", "millimeter", "millimeters";
+    Centimeter = 0.01, 0.0; "cm", "centimeter", "centimeters";
+    Kilometer = 1000.0, 0.0; "km", "kilometer", "kilometers";
+    Inch = 0.0254, 0.0; "in", "inch", "inches";
+    Foot = 0.3048, 0.0; "ft", "foot", "feet";
+    Yard = 0.9144, 0.0; "yd", "yard", "yards";
+    Mile = 1609.344, 0.0; "mi", "mile", "miles";
+}
+
+dimension! { Mass "mass", base Kilogram;
+    Kilogram = 1.0, 0.0; "kg", "kilogram", "kilograms";
+    Gram = 0.001, 0.0; "g", "gram", "grams";
+    Tonne = 1000.0, 0.0; "t", "tonne", "tonnes";
+    Ounce = 0.028349523125, 0.0; "oz", "ounce", "ounces";
+    Pound = 0.45359237, 0.0; "lb", "pound", "pounds";
+}
+
+dimension! { Time "time", base Second;
+    Second = 1.0, 0.0; "s", "second", "seconds";
+    Millisecond = 0.001, 0.0; "ms", "millisecond", "milliseconds";
+    Minute = 60.0, 0.0; "min", "minute", "minutes";
+    Hour = 3600.0, 0.0; "h", "hour", "hours";
+    Day = 86400.0, 0.0; "d", "day", "days";
+}
+
+// Kelvin = (Fahrenheit + 459.67) * 5/9.
+dimension! { Temperature "temperature", base Kelvin;
+    Kelvin = 1.0, 0.0; "K", "kelvin";
+    Celsius = 1.0, 273.15; "°C", "C", "celsius";
+    Fahrenheit = 5.0 / 9.0, 459.67 * 5.0 / 9.0; "°F", "F", "fahrenheit";
+}
+
+dimension! { Area "area", base SquareMeter;
+    SquareMeter = 1.0, 0.0; "m²", "m2";
+    SquareFoot = 0.09290304, 0.0; "ft²", "ft2";
+    Hectare = 10000.0, 0.0; "ha", "hectare", "hectares";
+    Acre = 4046.8564224, 0.0; "ac", "acre", "acres";
+}
+
+dimension! { Volume "volume", base CubicMeter;
+    CubicMeter = 1.0, 0.0; "m³", "m3";
+    Liter = 0.001, 0.0; "L", "l", "liter", "liters";
+}
+
+dimension! { Speed "speed", base MeterPerSecond;
+    MeterPerSecond = 1.0, 0.0; "m/s";
+    KilometerPerHour = 1.0 / 3.6, 0.0; "km/h";
+    MilePerHour = 0.44704, 0.0; "mph";
+    Knot = 1852.0 / 3600.0, 0.0; "kn", "knot", "knots";
+}
+
+dimension! { Acceleration "acceleration", base MeterPerSecondSquared;
+    MeterPerSecondSquared = 1.0, 0.0; "m/s²", "m/s2";
+}
+
+dimension! { Force "force", base Newton;
+    Newton = 1.0, 0.0; "N", "newton", "newtons";
+}
+
+// The dimensions of products and quotients. Temperatures are left out: a
+// product of two temperatures means nothing, and the offsets would make
+// `°C * m` wrong anyway.
+macro_rules! derived {
+    ($($a:ident $op:tt $b:ident = $c:ident;)+) => {
+        $(derived!(@one $a $op $b = $c);)+
+    };
+    (@one $a:ident * $b:ident = $c:ident) => {
+        impl Product<$b> for $a {
+            type Output = $c;
+        }
+    };
+    (@one $a:ident / $b:ident = $c:ident) => {
+        impl Quotient<$b> for $a {
+            type Output = $c;
+        }
+    };
+}
+
+derived! {
+    Length * Length = Area;
+    Area * Length = Volume;
+    Length * Area = Volume;
+    Area / Length = Length;
+    Volume / Length = Area;
+    Volume / Area = Length;
+    Length / Time = Speed;
+    Speed * Time = Length;
+    Time * Speed = Length;
+    Length / Speed = Time;
+    Speed / Time = Acceleration;
+    Acceleration * Time = Speed;
+    Time * Acceleration = Speed;
+    Speed / Acceleration = Time;
+    Mass * Acceleration = Force;
+    Acceleration * Mass = Force;
+    Force / Mass = Acceleration;
+    Force / Acceleration = Mass;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn close<U: Unit>(q: Quantity<U>, value: f64) -> bool {
+        (q.value() - value).abs() < 1e-9 * value.abs().max(1.0)
+    }
+
+    #[test]
+    fn conversions() {
+        let foot: Quantity<Inch> = Quantity::new(12.0);
+        assert!(close(foot.to::<Foot>(), 1.0));
+        assert!(close(foot.to::<Millimeter>(), 304.8));
+        let mile: Quantity<Kilometer> = Quantity::<Mile>::new(1.0).into();
+        assert!(close(mile, 1.609344));
+        assert!(close(Quantity::<Pound>::from(Quantity::<Kilogram>::new(1.0)), 2.2046226218));
+
+        let boiling: Quantity<Celsius> = Quantity::new(100.0);
+        assert!(close(boiling.to::<Fahrenheit>(), 212.0));
+        assert!(close(boiling.to::<Kelvin>(), 373.15));
+        assert!(close(Quantity::<Fahrenheit>::new(-40.0).to::<Celsius>(), -40.0));
+    }
+
+    #[test]
+    fn derived_quantities() {
+        let side: Quantity<Meter> = Quantity::new(3.0);
+        let area: Quantity<SquareMeter> = side * Quantity::<Centimeter>::new(200.0);
+        assert!(close(area, 6.0));
+        let volume: Quantity<CubicMeter> = area * side;
+        assert!(close(volume.to::<Liter>(), 18000.0));
+        assert!(close(volume / area, 3.0));
+
+        let speed = Quantity::<Mile>::new(60.0) / Quantity::<Hour>::new(1.0);
+        assert!(close(speed.to::<MilePerHour>(), 60.0));
+        let force = Quantity::<Kilogram>::new(2.0) * (speed / Quantity::<Second>::new(10.0));
+        assert!(close(force, 2.0 * 26.8224 / 10.0));
+        assert_eq!(2.0 * side, side + side);
+    }
+
+    #[test]
+    fn display_and_parse() {
+        assert_eq!(Quantity::<Inch>::new(12.0).to_string(), "12 in");
+        assert_eq!(format!("{:.1}", Quantity::<Celsius>::new(21.25)), "21.2 °C");
+
+        let q: Quantity<Meter> = "1.5 km".parse().unwrap();
+        assert!(close(q, 1500.0));
+        let q: Quantity<Millimeter> = "12in".parse().unwrap();
+        assert!(close(q, 304.8));
+        let q: Quantity<Meter> = " 1e3 mm ".parse().unwrap();
+        assert!(close(q, 1.0));
+        let q: Quantity<Celsius> = "-40 °F".parse().unwrap();
+        assert!(close(q, -40.0));
+        let q: Quantity<SquareMeter> = "2 ha".parse().unwrap();
+        assert!(close(q, 20000.0));
+
+        assert_eq!("12 kg".parse::<Quantity<Meter>>(),
+                   Err(ParseQuantityError::Unit("kg".to_string(), "length")));
+        assert_eq!("12".parse::<Quantity<Meter>>(), Err(ParseQuantityError::MissingUnit));
+        assert_eq!("in".parse::<Quantity<Meter>>(),
+                   Err(ParseQuantityError::Number("in".to_string())));
+        assert_eq!(ParseQuantityError::Unit("kg".to_string(), "length").to_string(),
+                   "`kg` is not a unit of length");
+    }
+}
@@ -0,0 +1,88 @@
+/*
+boxstackheap puts a `Rectangle` in a `Box`. A quadtree is a tree of boxes:
+each node covers a rectangle of the map, and once it holds too many items
+it splits into four boxed children, one for each quarter. The parent owns
+its children and they own theirs, so dropping the tree frees the lot.
+
+Finding what is under the mouse in a map of ten thousand buildings then
+means looking in a handful of small nodes, not at every building. The tree
+itself is rbe::quadtree, with the `quadtree` feature:
+
+rbe = { path = "../../../../rbe", features = ["quadtree"] }
+
+QuadTree::with_limits(bounds, capacity, max_depth)
+insert(bounds, item)     Err(item) if it is outside the tree
+remove(&bounds, &item)
+query(&area)             everything touching a rectangle
+nearest(&point)          the closest item and how far away it is
+iter()                   everything
+
+$ cargo run
+10003 buildings, 6 levels deep
+click at (500.5, 500.5): town hall
+click at (10, 990): nothing
+in view [(480, 480) - (520, 520)]: 23 buildings
+nearest to (10, 990): house 8224, 3.15 away
+after the station is pulled down, the nearest to (250, 750) is house 207
+a building off the map: Err("lighthouse")
+*/
+
+extern crate rbe;
+
+use rbe::geometry::{Point, Rectangle};
+use rbe::quadtree::QuadTree;
+use rbe::random::Lcg;
+
+// The same houses on every run.
+fn houses(n: usize) -> Vec<(Rectangle, String)> {
+    let mut rng = Lcg::new(2024);
+    let mut next = move || rng.next_f64();
+    (0..n).map(|i| {
+        let corner = Point::new(next() * 995.0, next() * 995.0);
+        let far = Point::new(corner.x + 1.0 + next() * 4.0, corner.y + 1.0 + next() * 4.0);
+        (Rectangle::new(corner, far), format!("house {}", i))
+    }).collect()
+}
+
+fn main() {
+    let map = Rectangle::new(Point::origin(), Point::new(1000.0, 1000.0));
+    let mut buildings = QuadTree::with_limits(map, 16, 10);
+    for (bounds, name) in houses(10000) {
+        buildings.insert(bounds, name).unwrap();
+    }
+    let hall = Rectangle::new(Point::new(495.0, 495.0), Point::new(505.0, 505.0));
+    let station = Rectangle::new(Point::new(240.0, 740.0), Point::new(260.0, 760.0));
+    buildings.insert(hall, "town hall".to_string()).unwrap();
+    buildings.insert(station, "station".to_string()).unwrap();
+    // Across the middle of the map, so it stays in the root.
+    buildings.insert(Rectangle::new(Point::new(0.0, 499.0), Point::new(1000.0, 501.0)),
+                     "high street".to_string()).unwrap();
+    println!("{} buildings, {} levels deep", buildings.len(), buildings.depth());
+
+    for click in &[Point::new(500.5, 500.5), Point::new(10.0, 990.0)] {
+        let under = Rectangle::new(*click, *click);
+        let hits: Vec<&str> = buildings.query(&under).into_iter()
+            .map(|name| name.as_str())
+            .filter(|&name| name != "high street")
+            .collect();
+        let hit = if hits.is_empty() { "nothing".to_string() } else { hits.join(", ") };
+        println!("click at {}: {}", click, hit);
+    }
+
+    let view = Rectangle::new(Point::new(480.0, 480.0), Point::new(520.0, 520.0));
+    println!("in view {}: {} buildings", view, buildings.query(&view).len());
+
+    let corner = Point::new(10.0, 990.0);
+    let (name, distance) = buildings.nearest(&corner).unwrap();
+    println!("nearest to {}: {}, {:.2} away", corner, name, distance);
+
+    buildings.remove(&station, &"station".to_string());
+    let (name, _) = buildings.nearest(&station.center()).unwrap();
+    println!("after the station is pulled down, the nearest to {} is {}", station.center(), name);
+
+    let lighthouse = Rectangle::new(Point::new(990.0, 990.0), Point::new(1010.0, 1010.0));
+    println!("a building off the map: {:?}", buildings.insert(lighthouse, "lighthouse".to_string()));
+
+    // Everything is still there, a node at a time.
+    assert_eq!(buildings.iter().count(), buildings.len());
+}
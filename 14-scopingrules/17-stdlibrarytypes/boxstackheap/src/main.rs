@@ -6,21 +6,45 @@ called, the inner object is destroyed, and the memory in the heap is freed.
 
 Boxed values can be dereferenced using the * operator; this removes one layer of 
 indirection.
+
+The sizes below are what `mem::size_of_val` says the values take in the stack.
+What the boxes take in the heap is counted by the allocator: this program
+makes rbe's `Counting` the global allocator, so every heap allocation goes
+through it, and an `AllocSnapshot` says how much was allocated since it was
+taken.
+
+$ cargo run
+Point occupies 16 bytes in the stack
+Rectangle occupies 32 bytes in the stack
+Boxed point occupies 8 bytes in the stack
+Boxed rectangle occupies 8 bytes in the stack
+Boxed box occupies 8 bytes in the stack
+Boxed rectangle occupies 32 bytes in the heap
+Boxed point occupies 16 bytes in the heap
+Boxed box occupies 8 bytes in the heap, and its point 16 more
+Unboxed point occupies 16 bytes in the stack
+struct Rectangle: 32 bytes, align 8, 0 bytes padding
+offset  size  align  field
+     0    16      8  p1: Point
+    16    16      8  p2: Point
+
+The last table, from rbe's `layout_of!`, shows where the 32 bytes of a
+`Rectangle` go: its two points, one after the other, with nothing between.
 */
 
+#[macro_use]
+extern crate rbe;
+
+use std::alloc::System;
 use std::mem;
 
-#[derive(Clone, Copy)]
-struct Point {
-    x: f64,
-    y: f64,
-}
+use rbe::allocation::{AllocSnapshot, Counting};
 
-#[allow(dead_code)]
-struct Rectangle {
-    p1: Point,
-    p2: Point,
-}
+// Two `f64`s, and a rectangle of two points. See rbe/src/geometry.rs.
+use rbe::geometry::{Point, Rectangle};
+
+#[global_allocator]
+static ALLOC: Counting = Counting::new(System);
 
 fn origin() -> Point {
     Point { x: 0.0, y: 0.0 }
@@ -41,16 +65,23 @@ fn main() {
     };
 
     // Heap allocated rectangle
+    let heap = AllocSnapshot::new();
     let boxed_rectangle: Box<Rectangle> = Box::new(Rectangle {
         p1: origin(),
         p2: origin()
     });
 
+    let rectangle_heap = heap.delta().allocated_bytes;
+
     // The output of functions can be boxed
+    let heap = AllocSnapshot::new();
     let boxed_point: Box<Point> = Box::new(origin());
+    let point_heap = heap.delta().allocated_bytes;
 
     // Double indirection
+    let heap = AllocSnapshot::new();
     let box_in_a_box: Box<Box<Point>> = Box::new(boxed_origin());
+    let box_heap = heap.delta().allocated_bytes;
 
     println!("Point occupies {} bytes in the stack",
              mem::size_of_val(&point));
@@ -65,10 +96,17 @@ fn main() {
     println!("Boxed box occupies {} bytes in the stack",
              mem::size_of_val(&box_in_a_box));
 
+    // The boxes' contents, which the stack sizes above don't show
+    println!("Boxed rectangle occupies {} bytes in the heap", rectangle_heap);
+    println!("Boxed point occupies {} bytes in the heap", point_heap);
+    println!("Boxed box occupies {} bytes in the heap, and its point {} more",
+             mem::size_of_val(&*box_in_a_box), box_heap - mem::size_of_val(&*box_in_a_box));
+
     // Copy the data contained in `boxed_point` into `unboxed_point`
     let unboxed_point: Point = *boxed_point;
     println!("Unboxed point occupies {} bytes in the stack",
              mem::size_of_val(&unboxed_point));
-}
-
 
+    // Where the bytes of a rectangle go
+    print!("{}", layout_of!(Rectangle { p1, p2 }));
+}
@@ -0,0 +1,6 @@
+// lib.rs
+// The account store that replaces the example's plaintext `Account` key,
+// and the hashing it needs, as a library.
+pub mod pbkdf2;
+pub mod sha256;
+pub mod store;
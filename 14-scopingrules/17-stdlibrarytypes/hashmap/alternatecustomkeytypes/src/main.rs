@@ -23,62 +23,80 @@ of implementing Hash.
 
 To play around with using a struct in HashMap, let's try making a very simple 
 user logon system:
-*/
-
-use std::collections::HashMap;
-
-// Eq requires that you derive PartialEq on the type.
-#[derive(PartialEq, Eq, Hash)]
-struct Account<'a>{
-    username: &'a str,
-    password: &'a str,
-}
 
-struct AccountInfo<'a>{
-    name: &'a str,
-    email: &'a str,
-}
+That system keyed the map on `Account { username, password }`, so every
+password sat in memory as typed, and a typo said no more than "Login
+failed!". It is now a library:
+
+sha256.rs  SHA-256, checked against the FIPS 180-4 examples
+pbkdf2.rs  HMAC and PBKDF2 over it, checked against RFC 4231 and RFC 7914,
+           and a comparison that takes the same time however much matches
+store.rs   AccountStore: a HashMap keyed by `Username`, a type of our own
+           that derives Eq and Hash, holding salted PBKDF2 hashes.
+           Accounts lock after too many wrong passwords, and the store
+           saves to and loads from a file.
+
+`try_logon` no longer prints; it says what happened with a `Logon`:
+
+$ cargo run
+j.everyman, psasword123: WrongPassword { attempts_left: 4 }
+j.everyman, password123: logged on as John Everyman <j.everyman@email.com>
+nobody, password123: UnknownUser
+j.everyman, guess 1: WrongPassword { attempts_left: 4 }
+...
+j.everyman, guess 5: locked for 15 minutes
+j.everyman, password123: locked for 15 minutes
+saved and loaded 1 account; j.everyman is still locked for 15 minutes
+*/
 
-type Accounts<'a> = HashMap<Account<'a>, AccountInfo<'a>>;
+extern crate alternatecustomkeytypes;
 
-fn try_logon<'a>(accounts: &Accounts<'a>,
-        username: &'a str, password: &'a str){
-    println!("Username: {}", username);
-    println!("Password: {}", password);
-    println!("Attempting logon...");
+use std::env;
+use std::fs;
+use std::process;
 
-    let logon = Account {
-        username: username,
-        password: password,
-    };
+use alternatecustomkeytypes::store::{AccountInfo, AccountStore, Clock, Logon, SystemClock};
 
-    match accounts.get(&logon) {
-        Some(account_info) => {
-            println!("Successful logon!");
-            println!("Name: {}", account_info.name);
-            println!("Email: {}", account_info.email);
-        },
-        _ => println!("Login failed!"),
-    }
+// Rounded up, so a lock of 900 seconds with part of a second gone is
+// still 15 minutes.
+fn minutes_until(until: u64) -> u64 {
+    until.saturating_sub(SystemClock.now()).div_ceil(60)
 }
 
-fn main(){
-    let mut accounts: Accounts = HashMap::new();
-
-    let account = Account {
-        username: "j.everyman",
-        password: "password123",
+fn report(store: &mut AccountStore, username: &str, password: &str, tried: &str) {
+    let result = match store.try_logon(username, password) {
+        Logon::Success(info) => format!("logged on as {} <{}>", info.name, info.email),
+        Logon::Locked { until } => format!("locked for {} minutes", minutes_until(until)),
+        other => format!("{:?}", other),
     };
+    println!("{}, {}: {}", username, tried, result);
+}
 
-    let account_info = AccountInfo {
-        name: "John Everyman",
-        email: "j.everyman@email.com",
+fn main() {
+    // A tenth of the usual iterations, so that a debug build doesn't take
+    // seconds over each logon.
+    let mut accounts = AccountStore::new().iterations(10_000);
+    let info = AccountInfo {
+        name: "John Everyman".to_string(),
+        email: "j.everyman@email.com".to_string(),
     };
+    accounts.create("j.everyman", "password123", info).unwrap();
 
-    accounts.insert(account, account_info);
-
-    try_logon(&accounts, "j.everyman", "psasword123");
-
-    try_logon(&accounts, "j.everyman", "password123");
+    report(&mut accounts, "j.everyman", "psasword123", "psasword123");
+    report(&mut accounts, "j.everyman", "password123", "password123");
+    report(&mut accounts, "nobody", "password123", "password123");
+    for guess in 1..6 {
+        report(&mut accounts, "j.everyman", &guess.to_string(), &format!("guess {}", guess));
+    }
+    report(&mut accounts, "j.everyman", "password123", "password123");
+
+    let path = env::temp_dir().join(format!("accounts-{}.tsv", process::id()));
+    accounts.save(&path).unwrap();
+    let mut loaded = AccountStore::new();
+    loaded.load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    if let Logon::Locked { until } = loaded.try_logon("j.everyman", "password123") {
+        println!("saved and loaded {} account; j.everyman is still locked for {} minutes",
+                 loaded.len(), minutes_until(until));
+    }
 }
-
@@ -0,0 +1,114 @@
+// pbkdf2.rs
+// HMAC (RFC 2104) and PBKDF2 (RFC 8018) over the SHA-256 of sha256.rs.
+//
+// A password can't be stored as a plain SHA-256: guesses at that cost a
+// few nanoseconds each. PBKDF2 runs HMAC over the password and a salt
+// thousands of times over, so each guess costs as much as a real logon
+// does, and the salt stops one guess being tried against every account at
+// once.
+use sha256::{Sha256, BLOCK_LEN, DIGEST_LEN};
+
+// HMAC with the key hashed into the inner and outer states once, so that
+// PBKDF2's many rounds with the same key start from a clone.
+#[derive(Clone)]
+pub struct Hmac {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl Hmac {
+    pub fn new(key: &[u8]) -> Hmac {
+        let mut block = [0u8; BLOCK_LEN];
+        if key.len() > BLOCK_LEN {
+            let mut hash = Sha256::new();
+            hash.update(key);
+            block[..DIGEST_LEN].copy_from_slice(&hash.finish());
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = Sha256::new();
+        inner.update(&block.map(|b| b ^ 0x36));
+        let mut outer = Sha256::new();
+        outer.update(&block.map(|b| b ^ 0x5c));
+        Hmac { inner, outer }
+    }
+
+    pub fn mac(&self, message: &[u8]) -> [u8; DIGEST_LEN] {
+        let mut inner = self.inner.clone();
+        inner.update(message);
+        let mut outer = self.outer.clone();
+        outer.update(&inner.finish());
+        outer.finish()
+    }
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; DIGEST_LEN] {
+    Hmac::new(key).mac(message)
+}
+
+// Fills `out` with key derived from `password` and `salt`. Each 32 bytes
+// of it is the XOR of `iterations` HMACs, each of the one before.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    assert!(iterations > 0, "PBKDF2 needs at least one iteration");
+    let hmac = Hmac::new(password);
+    for (i, chunk) in out.chunks_mut(DIGEST_LEN).enumerate() {
+        let mut first = salt.to_vec();
+        first.extend_from_slice(&(i as u32 + 1).to_be_bytes());
+        let mut u = hmac.mac(&first);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac.mac(&u);
+            for (t, u) in t.iter_mut().zip(u.iter()) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+// Whether `a` and `b` are the same, taking as long to say no at the first
+// byte as at the last. `==` stops at the first difference, and how soon it
+// answers tells an attacker how much of a guess was right.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha256::hex;
+
+    // RFC 4231, test cases 1, 2 and 6.
+    #[test]
+    fn hmac_vectors() {
+        assert_eq!(hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+                   "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+                   "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        assert_eq!(hex(&hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+                   "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+    }
+
+    // RFC 7914, section 11.
+    #[test]
+    fn pbkdf2_vectors() {
+        let mut out = [0u8; 64];
+        pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut out);
+        assert_eq!(hex(&out), "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+                               49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783");
+        pbkdf2_hmac_sha256(b"Password", b"NaCl", 80000, &mut out);
+        assert_eq!(hex(&out), "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
+                               a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d");
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secrets"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
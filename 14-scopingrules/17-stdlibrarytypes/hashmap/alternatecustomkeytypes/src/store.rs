@@ -0,0 +1,465 @@
+// store.rs
+// `AccountStore`: accounts keyed by `Username`, each with a salted PBKDF2
+// hash of its password rather than the password itself.
+//
+// Too many wrong passwords within a while of each other lock the account
+// for a while longer. The time comes from a `Clock`, so that a test can
+// move it on without waiting.
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pbkdf2::{constant_time_eq, pbkdf2_hmac_sha256};
+use sha256::{hex, DIGEST_LEN};
+
+const SALT_LEN: usize = 16;
+
+// Seconds since 1970.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+// A clock that only moves when told to.
+#[derive(Debug, Default)]
+pub struct ManualClock(Cell<u64>);
+
+impl ManualClock {
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock(Cell::new(now))
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.0.set(self.0.get() + seconds);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+// The key of the map. Like `Account` in the example before it, it derives
+// `PartialEq`, `Eq` and `Hash`, but it is only the name: the password is
+// never a key, and never stored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Username(String);
+
+impl Username {
+    // Letters, digits and ".-_", so it can go in the file as it is.
+    pub fn new(name: &str) -> Option<Username> {
+        let ok = !name.is_empty()
+            && name.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_');
+        if ok { Some(Username(name.to_string())) } else { None }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountInfo {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone)]
+struct Account {
+    info: AccountInfo,
+    salt: [u8; SALT_LEN],
+    hash: [u8; DIGEST_LEN],
+    iterations: u32,
+    // When the recent wrong passwords were given.
+    failures: Vec<u64>,
+    locked_until: Option<u64>,
+}
+
+// How many wrong passwords, within how many seconds, lock an account, and
+// for how long.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lockout {
+    pub attempts: usize,
+    pub window: u64,
+    pub duration: u64,
+}
+
+impl Default for Lockout {
+    // Five tries in five minutes, then fifteen minutes off.
+    fn default() -> Lockout {
+        Lockout { attempts: 5, window: 5 * 60, duration: 15 * 60 }
+    }
+}
+
+// What `try_logon` found. A program should tell the user the same thing
+// for `UnknownUser` as for `WrongPassword`, or it tells an attacker which
+// names are real.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Logon {
+    Success(AccountInfo),
+    WrongPassword { attempts_left: usize },
+    // Until this time, right password or not.
+    Locked { until: u64 },
+    UnknownUser,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    BadUsername(String),
+    Exists(Username),
+    Io(io::Error),
+    // A line of the file, counting from 1, that can't be read.
+    Corrupt(usize),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StoreError::BadUsername(ref name) => write!(f, "{:?} can't be a username", name),
+            StoreError::Exists(ref name) => write!(f, "there is already an account {}", name),
+            StoreError::Io(ref e) => write!(f, "{}", e),
+            StoreError::Corrupt(line) => write!(f, "line {} of the account file is corrupt", line),
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> StoreError {
+        StoreError::Io(e)
+    }
+}
+
+// Random bytes from the system, or, where there is no /dev/urandom, from
+// the random keys std gives each `RandomState`.
+fn salt() -> [u8; SALT_LEN] {
+    let mut salt = [0; SALT_LEN];
+    let read = fs::File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut salt));
+    if read.is_err() {
+        for half in salt.chunks_mut(8) {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0));
+            half.copy_from_slice(&hasher.finish().to_le_bytes());
+        }
+    }
+    salt
+}
+
+fn hash(password: &str, salt: &[u8], iterations: u32) -> [u8; DIGEST_LEN] {
+    let mut hash = [0; DIGEST_LEN];
+    pbkdf2_hmac_sha256(password.as_bytes(), salt, iterations, &mut hash);
+    hash
+}
+
+fn unhex(s: &str, out: &mut [u8]) -> Option<()> {
+    if s.len() != out.len() * 2 || !s.is_ascii() {
+        return None;
+    }
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(())
+}
+
+pub struct AccountStore<C: Clock = SystemClock> {
+    accounts: HashMap<Username, Account>,
+    clock: C,
+    lockout: Lockout,
+    // For new passwords; each account keeps the count it was made with.
+    iterations: u32,
+    // How many accounts have each iteration count, and the count most of
+    // them have, kept up to date by `add` so that `try_logon` needn't
+    // look at every account.
+    iteration_counts: HashMap<u32, usize>,
+    typical: Option<u32>,
+}
+
+impl AccountStore<SystemClock> {
+    pub fn new() -> AccountStore<SystemClock> {
+        AccountStore::with_clock(SystemClock)
+    }
+}
+
+impl Default for AccountStore<SystemClock> {
+    fn default() -> AccountStore<SystemClock> {
+        AccountStore::new()
+    }
+}
+
+impl<C: Clock> AccountStore<C> {
+    // 100,000 iterations, which OWASP thought about right for
+    // PBKDF2-HMAC-SHA256 a few years ago.
+    pub fn with_clock(clock: C) -> AccountStore<C> {
+        AccountStore {
+            accounts: HashMap::new(),
+            clock,
+            lockout: Lockout::default(),
+            iterations: 100_000,
+            iteration_counts: HashMap::new(),
+            typical: None,
+        }
+    }
+
+    pub fn lockout(mut self, lockout: Lockout) -> AccountStore<C> {
+        self.lockout = lockout;
+        self
+    }
+
+    // Fewer make logging on quicker and guessing cheaper; tests use few.
+    pub fn iterations(mut self, iterations: u32) -> AccountStore<C> {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn create(&mut self, username: &str, password: &str, info: AccountInfo) -> Result<(), StoreError> {
+        let username = Username::new(username).ok_or_else(|| StoreError::BadUsername(username.to_string()))?;
+        if self.accounts.contains_key(&username) {
+            return Err(StoreError::Exists(username));
+        }
+        let salt = salt();
+        let account = Account {
+            info,
+            salt,
+            hash: hash(password, &salt, self.iterations),
+            iterations: self.iterations,
+            failures: Vec::new(),
+            locked_until: None,
+        };
+        self.add(username, account);
+        Ok(())
+    }
+
+    // For a username that isn't taken.
+    fn add(&mut self, username: Username, account: Account) {
+        *self.iteration_counts.entry(account.iterations).or_insert(0) += 1;
+        // Ties go to the larger count.
+        self.typical = self.iteration_counts
+            .iter()
+            .max_by_key(|&(&iterations, &n)| (n, iterations))
+            .map(|(&iterations, _)| iterations);
+        self.accounts.insert(username, account);
+    }
+
+    // The iteration count most accounts were made with, which need not be
+    // the one new accounts get: older accounts keep the count they had.
+    // With no accounts it is the new one.
+    fn typical_iterations(&self) -> u32 {
+        self.typical.unwrap_or(self.iterations)
+    }
+
+    pub fn try_logon(&mut self, username: &str, password: &str) -> Logon {
+        let now = self.clock.now();
+        let lockout = self.lockout;
+        let typical = self.typical_iterations();
+        let account = match Username::new(username).and_then(|u| self.accounts.get_mut(&u)) {
+            Some(account) => account,
+            None => {
+                // As slow as a real account, so the time taken doesn't
+                // give away that there isn't one.
+                hash(password, &[0; SALT_LEN], typical);
+                return Logon::UnknownUser;
+            }
+        };
+        if let Some(until) = account.locked_until {
+            if now < until {
+                return Logon::Locked { until };
+            }
+            account.locked_until = None;
+        }
+        if constant_time_eq(&hash(password, &account.salt, account.iterations), &account.hash) {
+            account.failures.clear();
+            return Logon::Success(account.info.clone());
+        }
+        account.failures.retain(|&at| now.saturating_sub(at) < lockout.window);
+        account.failures.push(now);
+        if account.failures.len() >= lockout.attempts {
+            account.failures.clear();
+            account.locked_until = Some(now + lockout.duration);
+            return Logon::Locked { until: now + lockout.duration };
+        }
+        Logon::WrongPassword { attempts_left: lockout.attempts - account.failures.len() }
+    }
+
+    // One account a line, tab separated: username, name, email,
+    // iterations, salt, hash, locked until, failure times. Written to a
+    // new file and renamed over the old, so a crash part way through
+    // leaves the old file whole.
+    pub fn save(&self, path: &Path) -> Result<(), StoreError> {
+        let mut names: Vec<&Username> = self.accounts.keys().collect();
+        names.sort();
+        let mut out = String::new();
+        for username in names {
+            let a = &self.accounts[username];
+            let failures: Vec<String> = a.failures.iter().map(|t| t.to_string()).collect();
+            out.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                                  username, a.info.name.replace(['\t', '\n'], " "),
+                                  a.info.email.replace(['\t', '\n'], " "), a.iterations,
+                                  hex(&a.salt), hex(&a.hash),
+                                  a.locked_until.map_or(String::new(), |t| t.to_string()),
+                                  failures.join(",")));
+        }
+        let new = path.with_extension("new");
+        fs::write(&new, out)?;
+        fs::rename(&new, path)?;
+        Ok(())
+    }
+
+    // Adds the accounts saved in `path` to those already here. Every line
+    // is read before any account is added, so a corrupt file adds none,
+    // and nor does one with an account that is already here or that is in
+    // the file twice.
+    pub fn load(&mut self, path: &Path) -> Result<(), StoreError> {
+        let text = fs::read_to_string(path)?;
+        let mut accounts: HashMap<Username, Account> = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let (username, account) = parse(line).ok_or(StoreError::Corrupt(i + 1))?;
+            if self.accounts.contains_key(&username) || accounts.contains_key(&username) {
+                return Err(StoreError::Exists(username));
+            }
+            accounts.insert(username, account);
+        }
+        for (username, account) in accounts {
+            self.add(username, account);
+        }
+        Ok(())
+    }
+}
+
+fn parse(line: &str) -> Option<(Username, Account)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() != 8 {
+        return None;
+    }
+    let mut account = Account {
+        info: AccountInfo { name: fields[1].to_string(), email: fields[2].to_string() },
+        salt: [0; SALT_LEN],
+        hash: [0; DIGEST_LEN],
+        iterations: fields[3].parse().ok().filter(|&n| n > 0)?,
+        failures: Vec::new(),
+        locked_until: None,
+    };
+    unhex(fields[4], &mut account.salt)?;
+    unhex(fields[5], &mut account.hash)?;
+    if !fields[6].is_empty() {
+        account.locked_until = Some(fields[6].parse().ok()?);
+    }
+    if !fields[7].is_empty() {
+        account.failures = fields[7].split(',').map(|t| t.parse().ok()).collect::<Option<_>>()?;
+    }
+    Some((Username::new(fields[0])?, account))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn john() -> AccountInfo {
+        AccountInfo { name: "John Everyman".to_string(), email: "j.everyman@email.com".to_string() }
+    }
+
+    fn store() -> AccountStore<ManualClock> {
+        let lockout = Lockout { attempts: 3, window: 60, duration: 600 };
+        let mut store = AccountStore::with_clock(ManualClock::new(1000)).lockout(lockout).iterations(10);
+        store.create("j.everyman", "password123", john()).unwrap();
+        store
+    }
+
+    #[test]
+    fn logons_and_lockout() {
+        let mut store = store();
+        assert!(matches!(store.create("j.everyman", "x", john()), Err(StoreError::Exists(_))));
+        assert!(matches!(store.create("j everyman", "x", john()), Err(StoreError::BadUsername(_))));
+        assert_ne!(store.accounts[&Username::new("j.everyman").unwrap()].hash,
+                   hash("password123", &[0; SALT_LEN], 10));
+
+        assert_eq!(store.try_logon("j.everyman", "password123"), Logon::Success(john()));
+        assert_eq!(store.try_logon("nobody", "password123"), Logon::UnknownUser);
+        assert_eq!(store.typical_iterations(), 10);
+        assert_eq!(store.try_logon("j.everyman", "psasword123"), Logon::WrongPassword { attempts_left: 2 });
+
+        // The first failure is out of the window by the time of the third.
+        store.clock.advance(61);
+        assert_eq!(store.try_logon("j.everyman", "1"), Logon::WrongPassword { attempts_left: 2 });
+        assert_eq!(store.try_logon("j.everyman", "2"), Logon::WrongPassword { attempts_left: 1 });
+        assert_eq!(store.try_logon("j.everyman", "3"), Logon::Locked { until: 1661 });
+        assert_eq!(store.try_logon("j.everyman", "password123"), Logon::Locked { until: 1661 });
+
+        store.clock.advance(600);
+        assert_eq!(store.try_logon("j.everyman", "password123"), Logon::Success(john()));
+        assert_eq!(store.try_logon("j.everyman", "4"), Logon::WrongPassword { attempts_left: 2 });
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut store = store();
+        store.create("jane_doe", "hunter2", AccountInfo { name: "Jane\tDoe".to_string(), email: String::new() }).unwrap();
+        store.try_logon("jane_doe", "wrong");
+        store.try_logon("jane_doe", "wrong");
+
+        let path = env::temp_dir().join(format!("accounts-{}.tsv", process::id()));
+        store.save(&path).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        let mut loaded = AccountStore::with_clock(ManualClock::new(1000)).lockout(store.lockout);
+        loaded.load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.try_logon("jane_doe", "wrong"), Logon::Locked { until: 1600 });
+        assert_eq!(loaded.try_logon("j.everyman", "password123"), Logon::Success(john()));
+        assert_eq!(loaded.accounts[&Username::new("jane_doe").unwrap()].info.name, "Jane Doe");
+
+        fs::write(&path, "j.everyman\tJohn\t\t10\tnot hex\t\t\t\n").unwrap();
+        assert!(matches!(loaded.load(&path), Err(StoreError::Corrupt(1))));
+        fs::remove_file(&path).unwrap();
+
+        // Nothing is added from a file with a bad line anywhere in it.
+        let mut loaded = AccountStore::with_clock(ManualClock::new(1000));
+        assert_eq!(loaded.typical_iterations(), 100_000);
+        fs::write(&path, format!("{}j.everyman\tJohn\t\t10\tnot hex\t\t\t\n", saved)).unwrap();
+        assert!(matches!(loaded.load(&path), Err(StoreError::Corrupt(3))));
+        fs::remove_file(&path).unwrap();
+        assert!(loaded.is_empty());
+
+        // Nor from one with an account twice, or one that is already here.
+        let first = saved.lines().next().unwrap();
+        fs::write(&path, format!("{}{}\n", saved, first)).unwrap();
+        assert!(matches!(loaded.load(&path), Err(StoreError::Exists(ref u)) if u.as_str() == "j.everyman"));
+        assert!(loaded.is_empty());
+        fs::write(&path, &saved).unwrap();
+        loaded.load(&path).unwrap();
+        assert_eq!(loaded.typical_iterations(), 10);
+        assert!(matches!(loaded.load(&path), Err(StoreError::Exists(_))));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+    }
+}
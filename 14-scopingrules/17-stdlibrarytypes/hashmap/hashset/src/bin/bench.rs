@@ -0,0 +1,97 @@
+// bench.rs
+// Times the HashSet example's operations on a `HashSet<usize>` and on a
+// `BitSet`, with a million elements in each set. Build it optimized, or
+// the numbers mean little:
+//
+// $ cargo run --release --bin bench
+//
+// Each set holds about half of the numbers below two million, picked by
+// rbe's `Lcg` so that every run uses the same ones. Each operation is
+// counted, and the counts must agree.
+extern crate hashset;
+extern crate rbe;
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use hashset::bitset::BitSet;
+use rbe::random::Lcg;
+
+const UNIVERSE: usize = 2_000_000;
+const ELEMENTS: usize = 1_000_000;
+
+// `ELEMENTS` different numbers below `UNIVERSE`.
+fn numbers(seed: u64) -> Vec<usize> {
+    let mut rng = Lcg::new(seed);
+    let mut seen = vec![false; UNIVERSE];
+    let mut out = Vec::with_capacity(ELEMENTS);
+    while out.len() < ELEMENTS {
+        let x = rng.below(UNIVERSE);
+        if !seen[x] {
+            seen[x] = true;
+            out.push(x);
+        }
+    }
+    out
+}
+
+// The quickest of a few runs, and what the last one returned.
+fn time<T, F: FnMut() -> T>(mut f: F) -> (Duration, T) {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..5 {
+        let start = Instant::now();
+        result = Some(f());
+        best = best.min(start.elapsed());
+    }
+    (best, result.unwrap())
+}
+
+fn row(name: &str, hash: (Duration, usize), bits: (Duration, usize)) {
+    assert_eq!(hash.1, bits.1, "{} gave different answers", name);
+    println!("{:<22} {:>9} {:>10.2?} {:>10.2?} {:>8.0}x", name, hash.1, hash.0, bits.0,
+             hash.0.as_secs_f64() / bits.0.as_secs_f64());
+}
+
+fn main() {
+    let (xs, ys) = (numbers(1), numbers(2));
+    println!("{:<22} {:>9} {:>10} {:>10} {:>9}", "", "count", "HashSet", "BitSet", "speedup");
+
+    let hash_build = time(|| {
+        let a: HashSet<usize> = xs.iter().cloned().collect();
+        let b: HashSet<usize> = ys.iter().cloned().collect();
+        (a, b)
+    });
+    let bits_build = time(|| {
+        let mut a = BitSet::new(UNIVERSE);
+        let mut b = BitSet::new(UNIVERSE);
+        a.extend(xs.iter().cloned());
+        b.extend(ys.iter().cloned());
+        (a, b)
+    });
+    let (a, b) = hash_build.1;
+    let (p, q) = bits_build.1;
+    row("build", (hash_build.0, a.len()), (bits_build.0, p.len()));
+
+    row("union", time(|| a.union(&b).count()), time(|| p.union(&q).count()));
+    row("difference", time(|| a.difference(&b).count()), time(|| p.difference(&q).count()));
+    row("intersection", time(|| a.intersection(&b).count()), time(|| p.intersection(&q).count()));
+    row("symmetric difference", time(|| a.symmetric_difference(&b).count()),
+        time(|| p.symmetric_difference(&q).count()));
+    row("contains, all of b", time(|| ys.iter().filter(|y| a.contains(y)).count()),
+        time(|| ys.iter().filter(|&&y| p.contains(y)).count()));
+
+    // In place, on a copy, which has to be made either way.
+    row("intersect in place", time(|| {
+        let mut c = a.clone();
+        c.retain(|x| b.contains(x));
+        c.len()
+    }), time(|| {
+        let mut c = p.clone();
+        c.intersect_with(&q);
+        c.len()
+    }));
+
+    println!("memory: HashSet about {} MB each, BitSet {} KB each",
+             a.capacity() * (8 + 1) / 1_000_000, UNIVERSE / 8 / 1000);
+}
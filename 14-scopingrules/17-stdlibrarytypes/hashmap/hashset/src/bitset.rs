@@ -0,0 +1,355 @@
+// bitset.rs
+// A set of small numbers, one bit each: bit `x % 64` of word `x / 64` is
+// set when `x` is in the set. Where the numbers are dense, from 0 up to some
+// `universe`, that is far smaller than a `HashSet`, whose every element is
+// a hashed 8 bytes and then some. The set algebra is also far quicker: a
+// union is an OR of words, 64 elements at a time.
+//
+// The universe is fixed when the set is made, and inserting outside it
+// panics, as indexing outside a `Vec` does. Sets with different universes
+// can still be combined.
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+
+const BITS: usize = 64;
+
+#[derive(Clone)]
+pub struct BitSet {
+    words: Vec<u64>,
+    universe: usize,
+}
+
+fn words_for(universe: usize) -> usize {
+    universe.div_ceil(BITS)
+}
+
+impl BitSet {
+    // An empty set that can hold 0 to `universe - 1`.
+    pub fn new(universe: usize) -> BitSet {
+        BitSet { words: vec![0; words_for(universe)], universe }
+    }
+
+    pub fn universe(&self) -> usize {
+        self.universe
+    }
+
+    // Counted a word at a time, with the processor's popcount where it has
+    // one.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn contains(&self, x: usize) -> bool {
+        self.words.get(x / BITS).is_some_and(|w| w & (1 << (x % BITS)) != 0)
+    }
+
+    // Returns whether `x` is new, as `HashSet::insert` does.
+    pub fn insert(&mut self, x: usize) -> bool {
+        assert!(x < self.universe, "{} is outside a universe of {}", x, self.universe);
+        let (word, bit) = (&mut self.words[x / BITS], 1 << (x % BITS));
+        let new = *word & bit == 0;
+        *word |= bit;
+        new
+    }
+
+    pub fn remove(&mut self, x: usize) -> bool {
+        match self.words.get_mut(x / BITS) {
+            Some(word) => {
+                let bit = 1 << (x % BITS);
+                let present = *word & bit != 0;
+                *word &= !bit;
+                present
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { words: &self.words, index: 0, word: self.words.first().cloned().unwrap_or(0) }
+    }
+
+    // How many elements are less than `x`.
+    pub fn rank(&self, x: usize) -> usize {
+        let (whole, bit) = (x / BITS, x % BITS);
+        let below: usize = self.words.iter().take(whole).map(|w| w.count_ones() as usize).sum();
+        let partial = match self.words.get(whole) {
+            Some(&w) if bit > 0 => (w & ((1 << bit) - 1)).count_ones() as usize,
+            _ => 0,
+        };
+        below + partial
+    }
+
+    // The element with `n` smaller ones before it; `select(rank(x)) == x`
+    // for any `x` in the set.
+    pub fn select(&self, mut n: usize) -> Option<usize> {
+        for (i, &w) in self.words.iter().enumerate() {
+            let ones = w.count_ones() as usize;
+            if n < ones {
+                // Drop the lowest set bit n times.
+                let mut w = w;
+                for _ in 0..n {
+                    w &= w - 1;
+                }
+                return Some(i * BITS + w.trailing_zeros() as usize);
+            }
+            n -= ones;
+        }
+        None
+    }
+
+    fn combine<'a>(&'a self, other: &'a BitSet, op: fn(u64, u64) -> u64) -> Combined<'a> {
+        Combined { a: &self.words, b: &other.words, op, next: 0, base: 0, word: 0 }
+    }
+
+    // The four operations of the HashSet example, as iterators that work
+    // a word at a time and build nothing.
+    pub fn union<'a>(&'a self, other: &'a BitSet) -> Combined<'a> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a BitSet) -> Combined<'a> {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a BitSet) -> Combined<'a> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    pub fn symmetric_difference<'a>(&'a self, other: &'a BitSet) -> Combined<'a> {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    // And the same, changing `self`. Union and symmetric difference can
+    // add elements past `self`'s universe, so widen it to `other`'s.
+    fn combine_with(&mut self, other: &BitSet, op: fn(u64, u64) -> u64) {
+        if other.universe > self.universe {
+            self.universe = other.universe;
+            self.words.resize(words_for(other.universe), 0);
+        }
+        for (i, a) in self.words.iter_mut().enumerate() {
+            *a = op(*a, other.words.get(i).cloned().unwrap_or(0));
+        }
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a | b);
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= !b;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (i, a) in self.words.iter_mut().enumerate() {
+            *a &= other.words.get(i).cloned().unwrap_or(0);
+        }
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a ^ b);
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words.iter().enumerate().all(|(i, &a)| a & !other.words.get(i).cloned().unwrap_or(0) == 0)
+    }
+
+    pub fn is_superset(&self, other: &BitSet) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+}
+
+// The elements of a set, smallest first.
+pub struct Iter<'a> {
+    words: &'a [u64],
+    index: usize,
+    // What's left of `words[index]`.
+    word: u64,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.index += 1;
+            self.word = *self.words.get(self.index)?;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.index * BITS + bit)
+    }
+}
+
+// Two sets' words run through `op` a pair at a time, and the set bits of
+// the result, smallest first. A set shorter than the other is taken to
+// have zeros after its end.
+pub struct Combined<'a> {
+    a: &'a [u64],
+    b: &'a [u64],
+    op: fn(u64, u64) -> u64,
+    // The next pair of words to combine.
+    next: usize,
+    // The first element `word` stands for, and what's left of it.
+    base: usize,
+    word: u64,
+}
+
+impl<'a> Iterator for Combined<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let len = self.a.len().max(self.b.len());
+        while self.word == 0 {
+            if self.next >= len {
+                return None;
+            }
+            let (a, b) = (self.a.get(self.next), self.b.get(self.next));
+            self.word = (self.op)(a.cloned().unwrap_or(0), b.cloned().unwrap_or(0));
+            self.base = self.next * BITS;
+            self.next += 1;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.base + bit)
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+// Just big enough for the largest element.
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> BitSet {
+        let elements: Vec<usize> = iter.into_iter().collect();
+        let mut set = BitSet::new(elements.iter().max().map_or(0, |&max| max + 1));
+        set.extend(elements);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+// Equal when they hold the same elements, whatever their universes.
+impl PartialEq for BitSet {
+    fn eq(&self, other: &BitSet) -> bool {
+        let len = self.words.len().max(other.words.len());
+        (0..len).all(|i| self.words.get(i).cloned().unwrap_or(0) == other.words.get(i).cloned().unwrap_or(0))
+    }
+}
+
+impl Eq for BitSet {}
+
+// Sets are partly ordered by inclusion, as in mathematics.
+impl PartialOrd for BitSet {
+    fn partial_cmp(&self, other: &BitSet) -> Option<Ordering> {
+        match (self.is_subset(other), other.is_subset(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+// `{1, 2, 3}`, like a `HashSet`.
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn the_hashset_example() {
+        let mut a: BitSet = vec![1, 2, 3].into_iter().collect();
+        let mut b = BitSet::new(6);
+        b.extend(vec![2, 3, 4]);
+        assert_eq!(a.universe(), 4);
+        assert!(!b.insert(4));
+        assert!(b.insert(5));
+        assert_eq!(format!("{:?}", b), "{2, 3, 4, 5}");
+
+        assert_eq!(a.union(&b).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(a.difference(&b).collect::<Vec<_>>(), [1]);
+        assert_eq!(b.difference(&a).collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(a.intersection(&b).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(a.symmetric_difference(&b).collect::<Vec<_>>(), [1, 4, 5]);
+
+        let mut c = a.clone();
+        c.intersect_with(&b);
+        assert_eq!(c, [2, 3].iter().cloned().collect());
+        assert!(c.is_subset(&a) && a.is_superset(&c) && c < a);
+        assert_eq!(a.partial_cmp(&b), None);
+        a.symmetric_difference_with(&b);
+        assert_eq!(a.universe(), 6);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [1, 4, 5]);
+        a.difference_with(&b);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [1]);
+        a.union_with(&b);
+        assert_eq!(a.len(), 5);
+        assert!(a.remove(1) && !a.remove(1) && !a.remove(1000));
+        assert_eq!(a, b);
+        assert!(BitSet::new(0).is_empty() && BitSet::new(0).iter().next().is_none());
+    }
+
+    // Against a `BTreeSet`, over several words and with empty words
+    // between.
+    #[test]
+    fn across_words() {
+        let xs: Vec<usize> = (0..1000).filter(|x| x % 7 == 0 || (300..450).contains(x)).collect();
+        let ys: Vec<usize> = (0..700).filter(|x| x % 5 == 0).collect();
+        let (a, b): (BitSet, BitSet) = (xs.iter().cloned().collect(), ys.iter().cloned().collect());
+        let (sa, sb): (BTreeSet<usize>, BTreeSet<usize>) = (xs.into_iter().collect(), ys.into_iter().collect());
+
+        assert_eq!(a.len(), sa.len());
+        assert_eq!(a.iter().collect::<Vec<_>>(), sa.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(a.union(&b).collect::<Vec<_>>(), sa.union(&sb).cloned().collect::<Vec<_>>());
+        assert_eq!(b.difference(&a).collect::<Vec<_>>(), sb.difference(&sa).cloned().collect::<Vec<_>>());
+        assert_eq!(a.symmetric_difference(&b).collect::<Vec<_>>(),
+                   sa.symmetric_difference(&sb).cloned().collect::<Vec<_>>());
+
+        for (n, &x) in sa.iter().enumerate() {
+            assert_eq!(a.rank(x), n);
+            assert_eq!(a.select(n), Some(x));
+        }
+        assert_eq!(a.rank(5000), a.len());
+        assert_eq!(a.select(a.len()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside a universe of 10")]
+    fn fixed_universe() {
+        BitSet::new(10).insert(10);
+    }
+}
@@ -0,0 +1,4 @@
+// lib.rs
+// `BitSet`, a set of small numbers with the same algebra as `HashSet`, for
+// main.rs and the benchmark in bin/bench.rs.
+pub mod bitset;
@@ -26,10 +26,51 @@ Sets have 4 primary operations (all of the following calls return an iterator):
 but not both.
 
 Try all of these in the following example.
+
+When the elements are small numbers, from 0 up to some limit, there is a
+much smaller and quicker set: one bit for each number that could be in it,
+set if it is. bitset.rs has one, `BitSet`, with the same four operations,
+as iterators or in place:
+
+a.union(&b)                 a.union_with(&b)
+a.difference(&b)            a.difference_with(&b)
+a.intersection(&b)          a.intersect_with(&b)
+a.symmetric_difference(&b)  a.symmetric_difference_with(&b)
+
+and `len`, which counts bits a word at a time, `iter`, `rank` (how many
+elements are smaller than x), `select` (the nth element), and subset and
+superset tests. The end of main() runs the example again with it.
+
+How much quicker? bin/bench.rs times both on a million elements:
+
+$ cargo run --release --bin bench
+                           count    HashSet     BitSet   speedup
+build                    1000000   196.38ms    11.21ms       18x
+union                    1499905   199.22ms    15.87ms       13x
+difference                499905   208.37ms     2.23ms       93x
+intersection              500095   221.91ms     2.92ms       76x
+symmetric difference      999810   376.84ms     6.77ms       56x
+contains, all of b        500095   151.99ms   192.36µs      790x
+intersect in place        500095   194.59ms    66.71µs     2917x
+memory: HashSet about 16 MB each, BitSet 250 KB each
+
+(the times on one machine; yours will differ, the counts won't)
+
+And when duplicates should be counted rather than dropped, rbe's Multiset
+is a bag: inserting 4 into a bag that has one makes two of them. Its union
+takes the larger count of each element, its sum adds the counts, its
+intersection takes the smaller, and its difference subtracts, stopping at
+none.
 */
 
+extern crate hashset;
+extern crate rbe;
+
 use std::collections::HashSet;
 
+use hashset::bitset::BitSet;
+use rbe::multiset::Multiset;
+
 fn main() {
     let mut a: HashSet<i32> = vec!(1i32, 2, 3).into_iter().collect();
     let mut b: HashSet<i32> = vec!(2i32, 3, 4).into_iter().collect();
@@ -62,5 +103,40 @@ fn main() {
     // Print [1, 5]
     println!("Symmetric Difference: {:?}",
              a.symmetric_difference(&b).collect::<Vec<&i32>>());
-}
 
+    // The same with bits, and the answers come out in order. A set
+    // collected from [1, 2, 3] only has room up to 3, so these are made
+    // with room for the 4 and 5 to come.
+    let mut a = BitSet::new(6);
+    let mut b = BitSet::new(6);
+    a.extend(vec![1, 2, 3]);
+    b.extend(vec![2, 3, 4]);
+    assert!(a.insert(4));
+    b.insert(5);
+    println!("Bits A: {:?}, B: {:?}", a, b);
+    println!("Union: {:?}", a.union(&b).collect::<Vec<usize>>());
+    println!("Difference: {:?}", a.difference(&b).collect::<Vec<usize>>());
+    println!("Intersection: {:?}", a.intersection(&b).collect::<Vec<usize>>());
+    println!("Symmetric Difference: {:?}", a.symmetric_difference(&b).collect::<Vec<usize>>());
+    println!("B has {} elements; {} of them are less than 4, and the first is {}",
+             b.len(), b.rank(4), b.select(0).unwrap());
+    a.intersect_with(&b);
+    println!("A and B is {:?}, a subset of both: {}", a, a.is_subset(&b) && b.is_superset(&a));
+
+    // And as bags, where the second 4 counts.
+    let mut a: Multiset<i32> = vec![1, 2, 3].into_iter().collect();
+    let mut b: Multiset<i32> = vec![2, 3, 4].into_iter().collect();
+    a.insert(4);
+    assert_eq!(b.insert(4), 1);
+    b.insert_n(5, 2);
+    let sorted = |bag: &Multiset<i32>| {
+        let mut all: Vec<i32> = bag.iter().cloned().collect();
+        all.sort();
+        all
+    };
+    println!("Bags A: {:?}, B: {:?}", sorted(&a), sorted(&b));
+    println!("Union: {:?}", sorted(&a.union(&b)));
+    println!("Sum: {:?}", sorted(&a.sum(&b)));
+    println!("Intersection: {:?}", sorted(&a.intersection(&b)));
+    println!("Difference: {:?}", sorted(&b.difference(&a)));
+}
@@ -0,0 +1,7 @@
+// lib.rs
+// The phone book that the `contacts` example grows into, as a library so
+// that other tools can use it too.
+pub mod number;
+pub mod phonebook;
+pub mod responder;
+pub mod trie;
@@ -7,21 +7,127 @@ Like vectors, HashMaps are growable, but HashMaps can also shrink themselves
 when they have excess space. You can create a HashMap with a certain starting 
 capacity using HashMap::with_capacity(uint), or use HashMap::new() to get a 
 HashMap with a default initial capacity (recommended).
+
+The `contacts` map below is where a phone book starts. It grows into one in
+lib.rs:
+
+number.rs     PhoneNumber, parsed and checked: "(555) 798-1364"
+trie.rs       a prefix tree, for finding contacts from part of a name or
+              part of a number
+phonebook.rs  PhoneBook: an owned HashMap of contacts with the two tries
+              beside it, CSV import and export, and merging of duplicates
+responder.rs  the Responder trait, for whatever answers a call. `call`'s
+              canned replies are `Canned`; `Voicemail` records calls.
+
+With no arguments this is still the example. With a file it is a tool:
+
+$ cargo run -- book.csv add "daniel smith" 798-1364
+$ cargo run -- book.csv add "DAN SMITH" "(555) 164-6743"
+$ cargo run -- book.csv add "dan  smith" 798-1364
+$ cargo run -- book.csv find dan
+Dan Smith: (555) 164-6743, 798-1364
+Daniel Smith: 798-1364
+$ cargo run -- book.csv number 1364
+Dan Smith: (555) 164-6743, 798-1364
+Daniel Smith: 798-1364
+$ cargo run -- book.csv merge
+Dan Smith merged into Daniel Smith
+$ cargo run -- book.csv call dan
+We're sorry, the call cannot be completed as dialed. Please hang up and try again.
+
+and `list` and `remove NAME`. `merge` joins contacts that share a number,
+keeping the longest name. The file is written back after `add`, `remove` and `merge`.
 */
 
 use std::collections::HashMap;
 
-fn call(number: &str) -> &str {
-    match number {
-        "798-1364" => "We're sorry, the call cannot be completed as dialed. 
-            Please hang up and try again.",
-        "645-7689" => "Hello, this is Mr. Awesome's Pizza. My name is Fred.
-            What can I get for you today?",
-        _ => "Hi! Who is this again?"
+extern crate hashmap;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+
+use hashmap::phonebook::{Contact, PhoneBook};
+use hashmap::responder::{Canned, Responder};
+
+// The replies that used to be a `match` here are `Canned::default()` now.
+fn call(responder: &dyn Responder, number: &str) -> String {
+    match number.parse() {
+        Ok(number) => responder.answer(&number),
+        Err(e) => format!("Can't call {}: {}", number, e),
+    }
+}
+
+fn show(contact: &Contact) -> String {
+    let numbers: Vec<String> = contact.numbers.iter().map(|n| n.to_string()).collect();
+    format!("{}: {}", contact.name, numbers.join(", "))
+}
+
+// `book.csv command args...`; see the top of the file.
+fn tool(args: &[String]) -> Result<(), String> {
+    let path = &args[0];
+    let mut book = match fs::read_to_string(path) {
+        Ok(csv) => PhoneBook::from_csv(&csv).map_err(|e| format!("{}: {}", path, e))?,
+        // No file yet is an empty book; anything else is worth stopping for.
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => PhoneBook::new(),
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    let words: Vec<&str> = args[1..].iter().map(|s| s.as_str()).collect();
+    let changed = match words[..] {
+        [] | ["list"] => {
+            book.contacts().into_iter().for_each(|c| println!("{}", show(c)));
+            false
+        }
+        ["find", prefix] => {
+            book.find_by_name(prefix).into_iter().for_each(|c| println!("{}", show(c)));
+            false
+        }
+        ["number", digits] => {
+            book.find_by_number(digits).into_iter().for_each(|c| println!("{}", show(c)));
+            false
+        }
+        ["add", name, number] => {
+            let number = number.parse().map_err(|e| format!("{}", e))?;
+            book.add(name, number).map_err(|e| format!("{}", e))?
+        }
+        ["remove", name] => book.remove(name).is_some(),
+        ["merge"] => {
+            let merged = book.merge_duplicates();
+            for (dropped, kept) in &merged {
+                println!("{} merged into {}", dropped, kept);
+            }
+            !merged.is_empty()
+        }
+        // The whole name, or enough of it to pick out one contact.
+        ["call", name] => {
+            let contact = book.get(name).or_else(|| match book.find_by_name(name)[..] {
+                [only] => Some(only),
+                _ => None,
+            });
+            let reply = contact.and_then(|c| book.call(&c.name, &Canned::default()));
+            println!("{}", reply.ok_or(format!("Don't have {}'s number.", name))?);
+            false
+        }
+        _ => return Err(format!("unknown command: {}", words.join(" "))),
+    };
+    if changed {
+        fs::write(path, book.to_csv()).map_err(|e| format!("{}: {}", path, e))?;
     }
+    Ok(())
 }
 
-fn main() { 
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = tool(&args) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let responder = Canned::default();
     let mut contacts = HashMap::new();
 
     contacts.insert("Daniel", "798-1364");
@@ -31,7 +137,7 @@ fn main() {
 
     // Takes a reference and returns Option<&V>
     match contacts.get(&"Daniel") {
-        Some(&number) => println!("Calling Daniel: {}", call(number)),
+        Some(&number) => println!("Calling Daniel: {}", call(&responder, number)),
         _ => println!("Don't have Daniel's number."),
     }
 
@@ -40,7 +146,7 @@ fn main() {
     contacts.insert("Daniel", "164-6743");
 
     match contacts.get(&"Ashley") {
-        Some(&number) => println!("Calling Ashley: {}", call(number)),
+        Some(&number) => println!("Calling Ashley: {}", call(&responder, number)),
         _ => println!("Don't have Ashley's number."),
     }
 
@@ -49,7 +155,14 @@ fn main() {
     // `HashMap::iter()` returns an iterator that yields 
     // (&'a key, &'a value) pairs in arbitrary order.
     for (contact, &number) in contacts.iter() {
-        println!("Calling {}: {}", contact, call(number)); 
+        println!("Calling {}: {}", contact, call(&responder, number)); 
     }
-}
 
+    // The same contacts, owned, in a `PhoneBook`, and saved the way the
+    // tool saves them.
+    let mut book = PhoneBook::new();
+    for (name, number) in contacts {
+        book.add(name, number.parse().unwrap()).unwrap();
+    }
+    print!("{}", book.to_csv());
+}
@@ -0,0 +1,113 @@
+// number.rs
+// A North American phone number, checked once when it is parsed rather
+// than carried around as whatever string someone typed. "798-1364",
+// "(555) 798-1364", "555.798.1364" and "+1 555 798 1364" are all fine;
+// the area code is optional, as it is in the original `contacts`.
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PhoneNumber {
+    pub area: Option<u16>,
+    pub exchange: u16,
+    pub line: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberError {
+    Empty,
+    BadCharacter(char),
+    // How many digits there were: 7 or 10 are wanted, or 11 with a
+    // leading 1.
+    WrongLength(usize),
+    // Area codes never start with 0 or 1.
+    BadAreaCode(u16),
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NumberError::Empty => write!(f, "no number given"),
+            NumberError::BadCharacter(c) => write!(f, "{:?} can't be in a phone number", c),
+            NumberError::WrongLength(n) => write!(f, "a phone number has 7 or 10 digits, not {}", n),
+            NumberError::BadAreaCode(area) => write!(f, "there is no area code {:03}", area),
+        }
+    }
+}
+
+impl PhoneNumber {
+    // Just the digits, for searching: "5557981364".
+    pub fn digits(&self) -> String {
+        match self.area {
+            Some(area) => format!("{:03}{:03}{:04}", area, self.exchange, self.line),
+            None => format!("{:03}{:04}", self.exchange, self.line),
+        }
+    }
+}
+
+fn number(digits: &[u32]) -> u16 {
+    digits.iter().fold(0, |n, &d| n * 10 + d as u16)
+}
+
+impl FromStr for PhoneNumber {
+    type Err = NumberError;
+
+    fn from_str(s: &str) -> Result<PhoneNumber, NumberError> {
+        let s = s.trim();
+        let s = s.strip_prefix('+').unwrap_or(s);
+        let mut digits = Vec::new();
+        for c in s.chars() {
+            match c {
+                '0'..='9' => digits.push(c.to_digit(10).unwrap()),
+                ' ' | '-' | '.' | '(' | ')' => {}
+                _ => return Err(NumberError::BadCharacter(c)),
+            }
+        }
+        if digits.len() == 11 && digits[0] == 1 {
+            digits.remove(0);
+        }
+        let (area, rest) = match digits.len() {
+            0 => return Err(NumberError::Empty),
+            7 => (None, &digits[..]),
+            10 => (Some(number(&digits[..3])), &digits[3..]),
+            n => return Err(NumberError::WrongLength(n)),
+        };
+        match area {
+            Some(area) if area < 200 => Err(NumberError::BadAreaCode(area)),
+            _ => Ok(PhoneNumber { area, exchange: number(&rest[..3]), line: number(&rest[3..]) }),
+        }
+    }
+}
+
+// "798-1364", or "(555) 798-1364".
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(area) = self.area {
+            write!(f, "({:03}) ", area)?;
+        }
+        write!(f, "{:03}-{:04}", self.exchange, self.line)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_and_print() {
+        let local: PhoneNumber = "798-1364".parse().unwrap();
+        assert_eq!(local, PhoneNumber { area: None, exchange: 798, line: 1364 });
+        assert_eq!(local.to_string(), "798-1364");
+
+        for s in &["(555) 098-0042", "555.098.0042", "+1 555 098 0042", "5550980042"] {
+            let n: PhoneNumber = s.parse().unwrap();
+            assert_eq!(n.to_string(), "(555) 098-0042");
+            assert_eq!(n.digits(), "5550980042");
+        }
+
+        assert_eq!("".parse::<PhoneNumber>(), Err(NumberError::Empty));
+        assert_eq!("798-136".parse::<PhoneNumber>(), Err(NumberError::WrongLength(6)));
+        assert_eq!("798-1364 x2".parse::<PhoneNumber>(), Err(NumberError::BadCharacter('x')));
+        assert_eq!("(155) 798-1364".parse::<PhoneNumber>(), Err(NumberError::BadAreaCode(155)));
+    }
+}
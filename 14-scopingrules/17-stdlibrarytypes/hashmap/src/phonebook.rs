@@ -0,0 +1,432 @@
+// phonebook.rs
+// The `contacts` map grown up. It owns its strings rather than borrowing
+// `&str`s, so it can be read from a file. It still is a `HashMap`, keyed by
+// the name in lower case so that "daniel" finds "Daniel". Beside it, two
+// tries find contacts from part of a name or part of a number.
+use std::collections::HashMap;
+use std::fmt;
+
+use number::{NumberError, PhoneNumber};
+use responder::Responder;
+use trie::Trie;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub name: String,
+    pub numbers: Vec<PhoneNumber>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookError {
+    EmptyName,
+    Number(NumberError),
+    // A CSV row without exactly a name and a number.
+    FieldCount(usize),
+    UnclosedQuote,
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BookError::EmptyName => write!(f, "a contact needs a name"),
+            BookError::Number(ref e) => write!(f, "{}", e),
+            BookError::FieldCount(n) => write!(f, "expected a name and a number, found {} fields", n),
+            BookError::UnclosedQuote => write!(f, "a quote is never closed"),
+        }
+    }
+}
+
+impl From<NumberError> for BookError {
+    fn from(e: NumberError) -> BookError {
+        BookError::Number(e)
+    }
+}
+
+// Which line of a CSV file was wrong, counting from 1, and how.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub line: usize,
+    pub error: BookError,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+// Single spaces between words, and a capital at the start of each word
+// and after anything in one that isn't a letter, as in "O'Brien-Smith". A
+// word typed in mixed case, like "McDonald", is taken to be right already.
+pub fn normalize_name(name: &str) -> String {
+    let words: Vec<String> = name.split_whitespace().map(|word| {
+        if word.chars().any(char::is_lowercase) && word.chars().any(char::is_uppercase) {
+            return word.to_string();
+        }
+        let mut out = String::new();
+        let mut start = true;
+        for c in word.chars() {
+            if start {
+                out.extend(c.to_uppercase());
+            } else {
+                out.extend(c.to_lowercase());
+            }
+            start = !c.is_alphabetic();
+        }
+        out
+    }).collect();
+    words.join(" ")
+}
+
+fn key(name: &str) -> String {
+    normalize_name(name).to_lowercase()
+}
+
+// What a contact is filed under in the name trie: the whole key, and the
+// rest of it from each later word on.
+fn name_keys(key: &str) -> Vec<&str> {
+    let mut keys = vec![key];
+    keys.extend(key.match_indices(' ').map(|(i, _)| &key[i + 1..]));
+    keys
+}
+
+// The fields of one line of CSV. A field starting with a double quote
+// runs to the next one, and may hold commas, and `""` for a quote; fields
+// running over several lines aren't allowed.
+fn csv_fields(line: &str) -> Result<Vec<String>, BookError> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(BookError::UnclosedQuote);
+    }
+    Ok(fields)
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PhoneBook {
+    contacts: HashMap<String, Contact>,
+    // From the start of the name and of each later word in it to the key.
+    names: Trie<String>,
+    // From every run of digits to the end of a number to the key, so
+    // that any part of a number finds it.
+    numbers: Trie<String>,
+}
+
+impl PhoneBook {
+    pub fn new() -> PhoneBook {
+        PhoneBook::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.contacts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contacts.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Contact> {
+        self.contacts.get(&key(name))
+    }
+
+    // In order of name.
+    pub fn contacts(&self) -> Vec<&Contact> {
+        let mut all: Vec<&Contact> = self.contacts.values().collect();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        all
+    }
+
+    fn index(&mut self, key: &str, number: &PhoneNumber) {
+        let digits = number.digits();
+        for i in 0..digits.len() {
+            self.numbers.insert(&digits[i..], key.to_string());
+        }
+    }
+
+    fn unindex(&mut self, key: &str, contact: &Contact) {
+        for name in name_keys(key) {
+            self.names.remove(name, &key.to_string());
+        }
+        for number in &contact.numbers {
+            let digits = number.digits();
+            for i in 0..digits.len() {
+                self.numbers.remove(&digits[i..], &key.to_string());
+            }
+        }
+    }
+
+    // Adds a number to a contact, making the contact if it is new. Names
+    // that differ only in case and spacing are the same contact. Returns
+    // whether the number is new for them.
+    pub fn add(&mut self, name: &str, number: PhoneNumber) -> Result<bool, BookError> {
+        let name = normalize_name(name);
+        if name.is_empty() {
+            return Err(BookError::EmptyName);
+        }
+        let key = name.to_lowercase();
+        if !self.contacts.contains_key(&key) {
+            for name in name_keys(&key) {
+                self.names.insert(name, key.clone());
+            }
+            self.contacts.insert(key.clone(), Contact { name, numbers: Vec::new() });
+        }
+        let contact = self.contacts.get_mut(&key).unwrap();
+        if contact.numbers.contains(&number) {
+            return Ok(false);
+        }
+        contact.numbers.push(number);
+        self.index(&key, &number);
+        Ok(true)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Contact> {
+        let key = key(name);
+        let contact = self.contacts.remove(&key)?;
+        self.unindex(&key, &contact);
+        Some(contact)
+    }
+
+    fn found(&self, keys: Vec<&String>) -> Vec<&Contact> {
+        let mut found: Vec<&Contact> = keys.into_iter().map(|k| &self.contacts[k]).collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    }
+
+    // Everyone with a word in their name starting with `prefix`: "dan"
+    // and "smi" both find Daniel Smith, and so does "daniel sm".
+    pub fn find_by_name(&self, prefix: &str) -> Vec<&Contact> {
+        let prefix = prefix.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        self.found(self.names.with_prefix(&prefix).into_iter().collect())
+    }
+
+    // Everyone with a number containing these digits, in order; anything
+    // else in `partial`, such as '-', is ignored.
+    pub fn find_by_number(&self, partial: &str) -> Vec<&Contact> {
+        let digits: String = partial.chars().filter(|c| c.is_ascii_digit()).collect();
+        if digits.is_empty() {
+            return Vec::new();
+        }
+        self.found(self.numbers.with_prefix(&digits).into_iter().collect())
+    }
+
+    // Contacts sharing a number are taken to be the same person entered
+    // twice, as "Dan Smith" and "Daniel Smith", and made into one with
+    // all their numbers. Sharing is followed through: if A shares a number
+    // with B and B another with C, all three are one. The longest name is
+    // kept. Returns each merge as (name dropped, name kept).
+    pub fn merge_duplicates(&mut self) -> Vec<(String, String)> {
+        let mut keys: Vec<String> = self.contacts.keys().cloned().collect();
+        keys.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        // Union-find over positions in `keys`. The lower position is
+        // always made the root, so each group's root is its longest name.
+        fn root(parent: &mut [usize], i: usize) -> usize {
+            let mut r = i;
+            while parent[r] != r {
+                r = parent[r];
+            }
+            parent[i] = r;
+            r
+        }
+        let mut parent: Vec<usize> = (0..keys.len()).collect();
+        let mut owner: HashMap<PhoneNumber, usize> = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            for number in &self.contacts[key].numbers {
+                let first = *owner.entry(*number).or_insert(i);
+                let (a, b) = (root(&mut parent, first), root(&mut parent, i));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut merged = Vec::new();
+        for i in 0..keys.len() {
+            let keeper = root(&mut parent, i);
+            if keeper == i {
+                continue;
+            }
+            let dropped = self.remove(&keys[i]).unwrap();
+            let kept = self.contacts[&keys[keeper]].name.clone();
+            for number in dropped.numbers {
+                self.add(&kept, number).unwrap();
+            }
+            merged.push((dropped.name, kept));
+        }
+        merged
+    }
+
+    // Rings the contact's first number, and says what `responder` said.
+    pub fn call(&self, name: &str, responder: &dyn Responder) -> Option<String> {
+        let number = self.get(name)?.numbers.first()?;
+        Some(responder.answer(number))
+    }
+
+    // One row for each of a contact's numbers, under a `name,number`
+    // header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,number\n");
+        for contact in self.contacts() {
+            for number in &contact.numbers {
+                csv.push_str(&format!("{},{}\n", csv_field(&contact.name), number));
+            }
+        }
+        csv
+    }
+
+    // Adds every row of `csv`, as written by `to_csv`, and returns how many
+    // numbers were new. The header and blank lines are skipped. If any row
+    // is wrong nothing is added.
+    pub fn import_csv(&mut self, csv: &str) -> Result<usize, ImportError> {
+        let mut rows = Vec::new();
+        for (i, line) in csv.lines().enumerate() {
+            let at = |error| ImportError { line: i + 1, error };
+            if line.trim().is_empty() || (i == 0 && line.trim().eq_ignore_ascii_case("name,number")) {
+                continue;
+            }
+            let fields = csv_fields(line).map_err(at)?;
+            if fields.len() != 2 {
+                return Err(at(BookError::FieldCount(fields.len())));
+            }
+            if normalize_name(&fields[0]).is_empty() {
+                return Err(at(BookError::EmptyName));
+            }
+            let number: PhoneNumber = fields[1].parse().map_err(|e| at(BookError::from(e)))?;
+            rows.push((fields[0].clone(), number));
+        }
+        let mut added = 0;
+        for (name, number) in rows {
+            if self.add(&name, number).unwrap() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    pub fn from_csv(csv: &str) -> Result<PhoneBook, ImportError> {
+        let mut book = PhoneBook::new();
+        book.import_csv(csv)?;
+        Ok(book)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use responder::Voicemail;
+
+    fn n(s: &str) -> PhoneNumber {
+        s.parse().unwrap()
+    }
+
+    fn names(contacts: Vec<&Contact>) -> Vec<&str> {
+        contacts.into_iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn names_and_lookups() {
+        assert_eq!(normalize_name("  DANIEL   o'brien-smith "), "Daniel O'Brien-Smith");
+        assert_eq!(normalize_name("ronald McDonald"), "Ronald McDonald");
+
+        let mut book = PhoneBook::new();
+        assert!(book.add("daniel smith", n("798-1364")).unwrap());
+        assert!(book.add("Daniel  Smith", n("(555) 164-6743")).unwrap());
+        assert!(!book.add("DANIEL SMITH", n("798-1364")).unwrap());
+        book.add("Danielle Jones", n("435-8291")).unwrap();
+        book.add("Ashley", n("645-7689")).unwrap();
+        assert_eq!(book.add(" ", n("645-7689")), Err(BookError::EmptyName));
+        assert_eq!(book.len(), 3);
+        assert_eq!(book.get("daniel smith").unwrap().numbers.len(), 2);
+
+        assert_eq!(names(book.find_by_name("Dan")), ["Daniel Smith", "Danielle Jones"]);
+        assert_eq!(names(book.find_by_name("smi")), ["Daniel Smith"]);
+        assert_eq!(names(book.find_by_name("daniel  s")), ["Daniel Smith"]);
+        assert_eq!(names(book.find_by_number("13")), ["Daniel Smith"]);
+        assert_eq!(names(book.find_by_number("555-16")), ["Daniel Smith"]);
+        assert_eq!(names(book.find_by_number("76")), ["Ashley"]);
+        assert!(book.find_by_number("-").is_empty());
+
+        let voicemail = Voicemail::new();
+        assert!(book.call("ashley", &voicemail).is_some());
+        assert_eq!(book.call("Katie", &voicemail), None);
+        assert_eq!(voicemail.calls(), [n("645-7689")]);
+
+        assert!(book.remove("daniel smith").is_some());
+        assert!(book.find_by_name("smi").is_empty());
+        assert!(book.find_by_number("1364").is_empty());
+    }
+
+    #[test]
+    fn csv_and_merging() {
+        let csv = "name,number\n\
+                   Dan Smith,798-1364\n\
+                   \"Smith, Daniel\",(555) 164-6743\n\
+                   \n\
+                   daniel smith,798-1364\n\
+                   Daniel Smith,435-8291\n\
+                   Katie \"K\" Jones,956-1745\n";
+        let mut book = PhoneBook::from_csv(csv).unwrap();
+        assert_eq!(book.len(), 4);
+
+        let merged = book.merge_duplicates();
+        assert_eq!(merged, [("Dan Smith".to_string(), "Daniel Smith".to_string())]);
+        assert_eq!(book.len(), 3);
+        assert_eq!(names(book.find_by_number("1364")), ["Daniel Smith"]);
+
+        let out = book.to_csv();
+        assert_eq!(out, "name,number\n\
+                         Daniel Smith,798-1364\n\
+                         Daniel Smith,435-8291\n\
+                         \"Katie \"\"K\"\" Jones\",956-1745\n\
+                         \"Smith, Daniel\",(555) 164-6743\n");
+        let again = PhoneBook::from_csv(&out).unwrap();
+        assert_eq!(again.contacts(), book.contacts());
+
+        assert_eq!(book.import_csv("Fred,645-7689\nBob\n"),
+                   Err(ImportError { line: 2, error: BookError::FieldCount(1) }));
+        assert_eq!(book.import_csv("Fred,645-768"),
+                   Err(ImportError { line: 1, error: BookError::Number(NumberError::WrongLength(6)) }));
+        assert_eq!(book.import_csv("\"Fred,645-7689"),
+                   Err(ImportError { line: 1, error: BookError::UnclosedQuote }));
+        assert!(book.get("fred").is_none());
+    }
+
+    #[test]
+    fn merging_follows_chains() {
+        // Chris shares one number with C. Jones and the other with
+        // Christopher Jones, so all three are the same person.
+        let mut book = PhoneBook::from_csv("name,number\n\
+                                            Christopher Jones,435-8291\n\
+                                            C. Jones,798-1364\n\
+                                            Chris,798-1364\n\
+                                            Chris,435-8291\n\
+                                            Ashley,645-7689\n").unwrap();
+        let merged = book.merge_duplicates();
+        assert_eq!(merged, [("C. Jones".to_string(), "Christopher Jones".to_string()),
+                            ("Chris".to_string(), "Christopher Jones".to_string())]);
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.get("christopher jones").unwrap().numbers, [n("435-8291"), n("798-1364")]);
+        assert_eq!(names(book.find_by_number("1364")), ["Christopher Jones"]);
+        assert!(book.merge_duplicates().is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+// responder.rs
+// What happens at the other end of a call. The original example's `call`
+// was a `match` of canned replies; that is now `Canned`, one `Responder`
+// among others, and the phone book doesn't care which it is given.
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use number::PhoneNumber;
+
+pub trait Responder {
+    fn answer(&self, number: &PhoneNumber) -> String;
+}
+
+// The replies of the original `call`, and anything else added to them.
+pub struct Canned {
+    replies: HashMap<PhoneNumber, String>,
+    otherwise: String,
+}
+
+impl Canned {
+    pub fn new(otherwise: &str) -> Canned {
+        Canned { replies: HashMap::new(), otherwise: otherwise.to_string() }
+    }
+
+    pub fn reply(mut self, number: PhoneNumber, reply: &str) -> Canned {
+        self.replies.insert(number, reply.to_string());
+        self
+    }
+}
+
+impl Default for Canned {
+    fn default() -> Canned {
+        Canned::new("Hi! Who is this again?")
+            .reply("798-1364".parse().unwrap(),
+                   "We're sorry, the call cannot be completed as dialed. \
+                    Please hang up and try again.")
+            .reply("645-7689".parse().unwrap(),
+                   "Hello, this is Mr. Awesome's Pizza. My name is Fred. \
+                    What can I get for you today?")
+    }
+}
+
+impl Responder for Canned {
+    fn answer(&self, number: &PhoneNumber) -> String {
+        self.replies.get(number).unwrap_or(&self.otherwise).clone()
+    }
+}
+
+// Nobody ever picks up, but every call is remembered, which is what a test
+// wants.
+#[derive(Default)]
+pub struct Voicemail {
+    calls: RefCell<Vec<PhoneNumber>>,
+}
+
+impl Voicemail {
+    pub fn new() -> Voicemail {
+        Voicemail::default()
+    }
+
+    pub fn calls(&self) -> Vec<PhoneNumber> {
+        self.calls.borrow().clone()
+    }
+}
+
+impl Responder for Voicemail {
+    fn answer(&self, number: &PhoneNumber) -> String {
+        self.calls.borrow_mut().push(*number);
+        format!("You have reached {}. Please leave a message after the tone.", number)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn canned_and_voicemail() {
+        let canned = Canned::default();
+        assert!(canned.answer(&"645-7689".parse().unwrap()).contains("Pizza"));
+        assert_eq!(canned.answer(&"435-8291".parse().unwrap()), "Hi! Who is this again?");
+
+        let voicemail = Voicemail::new();
+        let number: PhoneNumber = "(555) 435-8291".parse().unwrap();
+        assert!(voicemail.answer(&number).starts_with("You have reached (555) 435-8291."));
+        assert_eq!(voicemail.calls(), [number]);
+    }
+}
@@ -0,0 +1,106 @@
+// trie.rs
+// A trie, or prefix tree: each node is one character further along a key,
+// so everything stored under keys starting "dan" is in the one subtree
+// reached by 'd', 'a', 'n'. Finding it takes as many steps as the prefix
+// has characters, however many keys there are.
+//
+// A key can hold several values, and a value can be stored under several
+// keys; the phone book files a contact under each word of the name.
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone)]
+pub struct Trie<V> {
+    children: BTreeMap<char, Trie<V>>,
+    values: BTreeSet<V>,
+}
+
+impl<V: Ord + Clone> Default for Trie<V> {
+    fn default() -> Trie<V> {
+        Trie::new()
+    }
+}
+
+impl<V: Ord + Clone> Trie<V> {
+    pub fn new() -> Trie<V> {
+        Trie { children: BTreeMap::new(), values: BTreeSet::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.children.is_empty()
+    }
+
+    pub fn insert(&mut self, key: &str, value: V) {
+        let mut node = self;
+        for c in key.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        node.values.insert(value);
+    }
+
+    // Takes `value` away from `key`, and any nodes left with nothing in
+    // them. Returns whether it was there.
+    pub fn remove(&mut self, key: &str, value: &V) -> bool {
+        let mut chars = key.chars();
+        match chars.next() {
+            None => self.values.remove(value),
+            Some(c) => {
+                let removed = match self.children.get_mut(&c) {
+                    Some(child) => child.remove(chars.as_str(), value),
+                    None => false,
+                };
+                if self.children.get(&c).is_some_and(|child| child.is_empty()) {
+                    self.children.remove(&c);
+                }
+                removed
+            }
+        }
+    }
+
+    // Every value stored under a key that starts with `prefix`, each once,
+    // in order.
+    pub fn with_prefix(&self, prefix: &str) -> BTreeSet<&V> {
+        let mut found = BTreeSet::new();
+        let mut node = self;
+        for c in prefix.chars() {
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => return found,
+            }
+        }
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            found.extend(node.values.iter());
+            stack.extend(node.children.values());
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prefixes() {
+        let mut trie = Trie::new();
+        trie.insert("dan", 1);
+        trie.insert("daniel", 2);
+        trie.insert("danielle", 3);
+        trie.insert("dave", 4);
+        trie.insert("smith", 2);
+
+        assert_eq!(trie.with_prefix("dan").into_iter().cloned().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(trie.with_prefix("da").len(), 4);
+        assert_eq!(trie.with_prefix("").len(), 4);
+        assert!(trie.with_prefix("x").is_empty());
+
+        assert!(trie.remove("danielle", &3));
+        assert!(!trie.remove("danielle", &3));
+        assert!(!trie.remove("dan", &2));
+        assert_eq!(trie.with_prefix("daniel").into_iter().cloned().collect::<Vec<_>>(), [2]);
+        for &(key, value) in &[("dan", 1), ("daniel", 2), ("dave", 4), ("smith", 2)] {
+            trie.remove(key, &value);
+        }
+        assert!(trie.is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+// checked.rs
+// `CheckedF64`, an `f64` that remembers the first thing that went wrong
+// with it. Each operation checks its inputs and its answer, and on a
+// failure the result holds the error and the name of the operation
+// instead of a number. Every operation after that passes the error along
+// untouched, the way `try!` returns early, so a whole expression can be
+// written out and checked once, at the end:
+//
+// (CheckedF64::new(x) / y).ln().sqrt().result()
+//
+// is `Ok` with the answer or `Err` saying which step failed and why.
+use std::error;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MathError {
+    DivisionByZero,
+    NegativeLogarithm,
+    // The logarithm of 0 (or -0) would be minus infinity.
+    ZeroLogarithm,
+    NegativeSquareRoot,
+    // Too big for an `f64`: the answer would have been infinite.
+    Overflow,
+    // No answer at all, as for `asin(2)`.
+    NaN,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            MathError::DivisionByZero => "division by zero",
+            MathError::NegativeLogarithm => "logarithm of negative number",
+            MathError::ZeroLogarithm => "logarithm of zero",
+            MathError::NegativeSquareRoot => "square root of negative number",
+            MathError::Overflow => "overflow",
+            MathError::NaN => "not a number",
+        })
+    }
+}
+
+impl error::Error for MathError {}
+
+// A `MathError` and the operation it came from: "ln", "div", ...
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpError {
+    pub op: &'static str,
+    pub error: MathError,
+}
+
+impl fmt::Display for OpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.op, self.error)
+    }
+}
+
+impl error::Error for OpError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CheckedF64(Result<f64, OpError>);
+
+// Infinite and NaN answers are errors whatever the operation; anything
+// finite is fine.
+fn finite(x: f64) -> Result<f64, MathError> {
+    if x.is_nan() {
+        Err(MathError::NaN)
+    } else if x.is_infinite() {
+        Err(MathError::Overflow)
+    } else {
+        Ok(x)
+    }
+}
+
+// The three steps of `op`, each on its own.
+pub fn div(x: f64, y: f64) -> Result<f64, MathError> {
+    if y == 0.0 { Err(MathError::DivisionByZero) } else { Ok(x / y) }
+}
+
+pub fn ln(x: f64) -> Result<f64, MathError> {
+    log_domain(x).map(f64::ln)
+}
+
+// What every logarithm asks of its argument. -0 is 0 here, as it is to
+// `==`, not negative.
+fn log_domain(x: f64) -> Result<f64, MathError> {
+    if x < 0.0 {
+        Err(MathError::NegativeLogarithm)
+    } else if x == 0.0 {
+        Err(MathError::ZeroLogarithm)
+    } else {
+        Ok(x)
+    }
+}
+
+pub fn sqrt(x: f64) -> Result<f64, MathError> {
+    if x < 0.0 { Err(MathError::NegativeSquareRoot) } else { Ok(x.sqrt()) }
+}
+
+impl CheckedF64 {
+    // An infinite or NaN `x` is an error from the start, from "new".
+    pub fn new(x: f64) -> CheckedF64 {
+        CheckedF64(finite(x).map_err(|error| OpError { op: "new", error }))
+    }
+
+    pub fn result(self) -> Result<f64, OpError> {
+        self.0
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.0.is_ok()
+    }
+
+    // `f` sees the number, if there is one, and its answer is checked too.
+    fn apply<F>(self, op: &'static str, f: F) -> CheckedF64
+        where F: FnOnce(f64) -> Result<f64, MathError>
+    {
+        CheckedF64(self.0.and_then(|x| {
+            f(x).and_then(finite).map_err(|error| OpError { op, error })
+        }))
+    }
+
+    // As `apply`, with two numbers. An error in `self` comes first.
+    fn apply2<F>(self, other: CheckedF64, op: &'static str, f: F) -> CheckedF64
+        where F: FnOnce(f64, f64) -> Result<f64, MathError>
+    {
+        match other.0 {
+            Ok(y) => self.apply(op, |x| f(x, y)),
+            Err(e) => CheckedF64(self.0.and(Err(e))),
+        }
+    }
+
+    pub fn sqrt(self) -> CheckedF64 {
+        self.apply("sqrt", sqrt)
+    }
+
+    // As the free `ln`: 0 fails with `ZeroLogarithm`.
+    pub fn ln(self) -> CheckedF64 {
+        self.log_with("ln", f64::ln)
+    }
+
+    pub fn log2(self) -> CheckedF64 {
+        self.log_with("log2", f64::log2)
+    }
+
+    pub fn log10(self) -> CheckedF64 {
+        self.log_with("log10", f64::log10)
+    }
+
+    pub fn log<B: Into<CheckedF64>>(self, base: B) -> CheckedF64 {
+        self.apply2(base.into(), "log", |x, base| {
+            log_domain(x)?;
+            if base < 0.0 {
+                Err(MathError::NegativeLogarithm)
+            } else if base == 1.0 {
+                Err(MathError::DivisionByZero)
+            } else {
+                Ok(x.log(base))
+            }
+        })
+    }
+
+    fn log_with(self, op: &'static str, log: fn(f64) -> f64) -> CheckedF64 {
+        self.apply(op, |x| log_domain(x).map(log))
+    }
+
+    // 0 to a negative power is 1 / 0.
+    pub fn powf<E: Into<CheckedF64>>(self, exponent: E) -> CheckedF64 {
+        self.apply2(exponent.into(), "powf", |x, n| {
+            if x == 0.0 && n < 0.0 { Err(MathError::DivisionByZero) } else { Ok(x.powf(n)) }
+        })
+    }
+
+    pub fn powi(self, n: i32) -> CheckedF64 {
+        self.apply("powi", |x| {
+            if x == 0.0 && n < 0 { Err(MathError::DivisionByZero) } else { Ok(x.powi(n)) }
+        })
+    }
+
+    pub fn exp(self) -> CheckedF64 {
+        self.apply("exp", |x| Ok(x.exp()))
+    }
+
+    // Outside -1 to 1 there is no answer, and these fail with `NaN`.
+    pub fn asin(self) -> CheckedF64 {
+        self.apply("asin", |x| Ok(x.asin()))
+    }
+
+    pub fn acos(self) -> CheckedF64 {
+        self.apply("acos", |x| Ok(x.acos()))
+    }
+
+    pub fn atan(self) -> CheckedF64 {
+        self.apply("atan", |x| Ok(x.atan()))
+    }
+
+    pub fn sin(self) -> CheckedF64 {
+        self.apply("sin", |x| Ok(x.sin()))
+    }
+
+    pub fn cos(self) -> CheckedF64 {
+        self.apply("cos", |x| Ok(x.cos()))
+    }
+
+    pub fn tan(self) -> CheckedF64 {
+        self.apply("tan", |x| Ok(x.tan()))
+    }
+
+    pub fn abs(self) -> CheckedF64 {
+        self.apply("abs", |x| Ok(x.abs()))
+    }
+}
+
+impl From<f64> for CheckedF64 {
+    fn from(x: f64) -> CheckedF64 {
+        CheckedF64::new(x)
+    }
+}
+
+impl From<CheckedF64> for Result<f64, OpError> {
+    fn from(x: CheckedF64) -> Result<f64, OpError> {
+        x.0
+    }
+}
+
+// The number, or the error.
+impl fmt::Display for CheckedF64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Ok(x) => write!(f, "{}", x),
+            Err(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// `CheckedF64 + CheckedF64`, `CheckedF64 + f64` and `f64 + CheckedF64`,
+// and the same for the other three.
+macro_rules! checked_op {
+    ($Op:ident, $method:ident, $name:expr, $f:expr) => {
+        impl<T: Into<CheckedF64>> $Op<T> for CheckedF64 {
+            type Output = CheckedF64;
+
+            fn $method(self, other: T) -> CheckedF64 {
+                self.apply2(other.into(), $name, $f)
+            }
+        }
+
+        impl $Op<CheckedF64> for f64 {
+            type Output = CheckedF64;
+
+            fn $method(self, other: CheckedF64) -> CheckedF64 {
+                CheckedF64::new(self).$method(other)
+            }
+        }
+    };
+}
+
+checked_op!(Add, add, "add", |x, y| Ok(x + y));
+checked_op!(Sub, sub, "sub", |x, y| Ok(x - y));
+checked_op!(Mul, mul, "mul", |x, y| Ok(x * y));
+checked_op!(Div, div, "div", div);
+
+impl Neg for CheckedF64 {
+    type Output = CheckedF64;
+
+    fn neg(self) -> CheckedF64 {
+        self.apply("neg", |x| Ok(-x))
+    }
+}
+
+// sqrt(ln(x / y)) the way the try! example had it: a statement for each
+// step, and `?` to return early from the first one that fails.
+pub fn op_(x: f64, y: f64) -> Result<f64, MathError> {
+    // if `div` "fails", then `DivisionByZero` will be `return`ed
+    let ratio = div(x, y)?;
+
+    // if `ln` "fails", then `NegativeLogarithm` will be `return`ed
+    let ln = ln(ratio)?;
+
+    sqrt(ln)
+}
+
+// The same with `CheckedF64`, which does the early return for itself.
+pub fn op(x: f64, y: f64) -> Result<f64, OpError> {
+    (CheckedF64::new(x) / y).ln().sqrt().result()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn failed(x: CheckedF64) -> (&'static str, MathError) {
+        let e = x.result().unwrap_err();
+        (e.op, e.error)
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(op(10.0, 1.0), Ok(10f64.ln().sqrt()));
+        assert_eq!(op(1.0, 0.0), Err(OpError { op: "div", error: MathError::DivisionByZero }));
+        assert_eq!(op(-1.0, 1.0), Err(OpError { op: "ln", error: MathError::NegativeLogarithm }));
+        assert_eq!(op(1.0, 10.0), Err(OpError { op: "sqrt", error: MathError::NegativeSquareRoot }));
+        assert_eq!(op(1.0, 10.0).unwrap_err().to_string(), "sqrt: square root of negative number");
+
+        let big = CheckedF64::new(1e300);
+        assert_eq!(failed(big * big), ("mul", MathError::Overflow));
+        assert_eq!(failed(CheckedF64::new(2.0).asin()), ("asin", MathError::NaN));
+        assert_eq!(failed(CheckedF64::new(0.0).ln()), ("ln", MathError::ZeroLogarithm));
+        assert_eq!(failed(CheckedF64::new(-0.0).log10()), ("log10", MathError::ZeroLogarithm));
+        assert_eq!(failed(CheckedF64::new(0.0).log(2.0)), ("log", MathError::ZeroLogarithm));
+        assert_eq!(failed(CheckedF64::new(0.0).powi(-1)), ("powi", MathError::DivisionByZero));
+        assert_eq!(failed(CheckedF64::new(-8.0).powf(1.0 / 3.0)), ("powf", MathError::NaN));
+        assert_eq!(failed(CheckedF64::new(f64::NAN)), ("new", MathError::NaN));
+        assert_eq!(failed(CheckedF64::new(8.0).log(1.0)), ("log", MathError::DivisionByZero));
+    }
+
+    #[test]
+    fn same_as_early_return() {
+        for &(x, y) in &[(10.0, 1.0), (1.0, 10.0), (1.0, 0.0), (-1.0, 1.0), (4.0, 2.0),
+                         (0.0, 1.0), (-0.0, 1.0)] {
+            assert_eq!(op_(x, y), op(x, y).map_err(|e| e.error), "op({}, {})", x, y);
+        }
+        assert_eq!(op_(-1.0, 0.0), Err(MathError::DivisionByZero));
+        assert_eq!(op_(0.0, 1.0), Err(MathError::ZeroLogarithm));
+    }
+
+    #[test]
+    fn first_error_wins() {
+        let x = CheckedF64::new(2.0);
+        assert_eq!((x * 3.0 - 1.0).result(), Ok(5.0));
+        assert_eq!((1.0 / x + x.powi(2)).result(), Ok(4.5));
+        assert_eq!(CheckedF64::new(8.0).log(2.0).result(), Ok(3.0));
+
+        // The division fails first; the square root never sees a number.
+        let bad = (x / 0.0 - 5.0).sqrt();
+        assert_eq!(failed(bad), ("div", MathError::DivisionByZero));
+        assert_eq!(failed((-x).sqrt() + bad), ("sqrt", MathError::NegativeSquareRoot));
+        assert_eq!(failed(x + bad), ("div", MathError::DivisionByZero));
+        assert_eq!(failed(x.ln().acos() * bad), ("div", MathError::DivisionByZero));
+        assert!(!bad.is_ok());
+        assert_eq!(bad.to_string(), "div: division by zero");
+    }
+}
@@ -0,0 +1,4 @@
+// lib.rs
+// `CheckedF64`, an `f64` whose operations fail without panicking, for
+// main.rs.
+pub mod checked;
@@ -3,69 +3,51 @@ Chaining results using match can get pretty untidy; luckily, the try! macro can
 be used to make things pretty again. The try! macro expands to a match 
 expression, where the Err(err) branch expands to an early return Err(err), and 
 the Ok(ok) branch expands to an ok expression.
-*/
 
-mod checked {
-    #[derive(Debug)]
-    enum MathError {
-        DivisionByZero,
-        NegativeLogarithm,
-        NegativeSquareRoot,
-    }
+try! (now the `?` operator) still needs every fallible step pulled out into a
+statement of its own:
+
+let ratio = try!(div(x, y));
+let ln = try!(ln(ratio));
+sqrt(ln)
+
+and the example used to `panic!` at the top when one of them failed. The
+`CheckedF64` of checked.rs does the early return for itself. It is an `f64`
+whose `+ - * /`, `sqrt`, `ln`, `log`, `powf`, `asin` and the rest check what
+they are given and what they get, and once one of them fails every later one
+passes that first error along. So the calculation reads as it would with
+plain `f64`s:
+
+(CheckedF64::new(x) / y).ln().sqrt().result()
+
+and one `Result` at the end says which operation failed, and why. `op` returns
+it, rather than panicking. `op_` is still there beside it, written with `?`,
+and the two give the same answers:
+
+$ cargo run
+op_(10, 1) = 1.5174271293851465
+op(10, 1) = 1.5174271293851465
+op_(1, 10) failed: square root of negative number
+op(1, 10) failed: sqrt: square root of negative number
+op_(1, 0) failed: division by zero
+op(1, 0) failed: div: division by zero
+op_(-1, 1) failed: logarithm of negative number
+op(-1, 1) failed: ln: logarithm of negative number
+*/
 
-    type MathResult = Result<f64, MathError>;
+extern crate trybang;
 
-    fn div(x: f64, y: f64) -> MathResult {
-        if y == 0.0 {
-            Err(MathError::DivisionByZero)
-        } else {
-            Ok(x / y)
-        }
-    }
+use trybang::checked;
 
-    fn sqrt(x: f64) -> MathResult {
-        if x < 0.0 {
-            Err(MathError::NegativeSquareRoot)
-        } else {
-            Ok(x.sqrt())
+fn main() {
+    for &(x, y) in &[(10.0, 1.0), (1.0, 10.0), (1.0, 0.0), (-1.0, 1.0)] {
+        match checked::op_(x, y) {
+            Ok(value) => println!("op_({}, {}) = {}", x, y, value),
+            Err(why) => println!("op_({}, {}) failed: {}", x, y, why),
         }
-    }
-
-    fn ln(x: f64) -> MathResult {
-        if x < 0.0 {
-            Err(MathError::NegativeLogarithm)
-        } else {
-            Ok(x.ln())
-        }
-    }
-
-    // Intermediate function
-    fn op_(x: f64, y: f64) -> MathResult {
-        // if `div` "fails", then `DivisionByZero` will be `return`ed
-        let ratio = try!(div(x, y));
-
-        // if `ln` "fails", then `NegativeLogarithm` will be `return`ed
-        let ln = try!(ln(ratio));
-
-        sqrt(ln)
-    }
-
-    pub fn op(x: f64, y: f64) {
-        match op_(x, y) {
-            Err(why) => panic!(match why {
-                MathError::NegativeLogarithm
-                    => "logarithm of negative number",
-                MathError::DivisionByZero
-                    => "division by zero",
-                MathError::NegativeSquareRoot
-                    => "square root of negative number",
-            }),
-            Ok(value) => println!("{}", value),
+        match checked::op(x, y) {
+            Ok(value) => println!("op({}, {}) = {}", x, y, value),
+            Err(why) => println!("op({}, {}) failed: {}", x, y, why),
         }
     }
 }
-
-fn main() {
-    checked::op(1.0, 10.0);
-}
-
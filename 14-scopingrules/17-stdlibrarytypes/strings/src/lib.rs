@@ -0,0 +1,6 @@
+// lib.rs
+// The `text` module: words, letters, pangrams and grapheme clusters, for
+// main.rs.
+extern crate rbe;
+
+pub mod text;
@@ -7,8 +7,28 @@ terminated.
 
 &str is a slice (&[u8]) that always points to a valid UTF-8 sequence, and can be 
 used to view into a String, just like &[T] is a view into Vec<T>.
+
+The used characters below come from sorting and deduplicating; counting how
+often each letter is used takes a Multiset from the rbe library, which
+keeps a count where a set would drop the duplicate.
+
+A `char` is one Unicode code point, which isn't always one character on the
+screen: "ë" can be an "e" and a combining diaeresis, and a flag is two code
+points. The `text` module in src/text looks at text the way a reader does. It
+splits it into grapheme clusters, the characters a reader sees, by the rules
+of Unicode Standard Annex #29, so reversing a string keeps accents and flags
+whole. It finds words in any script and folds case the Unicode way ("Straße"
+is "STRASSE"). It counts letters, words and pairs of letters, says which
+letters a would-be pangram is missing, and replaces whole words only, so
+turning dogs into cats leaves hotdogs alone.
 */
 
+extern crate rbe;
+extern crate strings;
+
+use rbe::multiset::Multiset;
+use strings::text;
+
 fn main() {
     // (all the type annotations are superfluous)
     // A reference to a string allocated in read only memory
@@ -41,13 +61,47 @@ fn main() {
     let trimmed_str: &str = string.trim_matches(chars_to_trim);
     println!("Used characters: {}", trimmed_str);
 
+    // Count the letters instead: a pangram has every one at least once
+    let letters: Multiset<char> = pangram.chars().filter(|c| c.is_alphabetic()).collect();
+    let common: Vec<String> = letters.most_common(4)
+        .iter()
+        .map(|&(c, n)| format!("{} x{}", c, n))
+        .collect();
+    println!("Letter usage: {} letters, {} different; most used {}",
+             letters.total_len(), letters.distinct_len(), common.join(", "));
+    println!("Every letter used: {}", text::is_pangram(pangram, text::ENGLISH));
+    let almost = "the quick brown fox jumped over the lazy dog";
+    println!("Missing from \"{}\": {:?}", almost, text::missing_letters(almost, text::ENGLISH));
+    println!("Russian pangram: {}",
+             text::is_pangram("Съешь же ещё этих мягких французских булок, да выпей чаю",
+                              text::RUSSIAN));
+
+    // Pairs of letters, and the words and how often they come
+    println!("Most common pairs of letters");
+    for (&(a, b), n) in text::bigram_frequencies(pangram).most_common(3) {
+        println!("> {}{} {}", a, b, text::bar('#', n));
+    }
+    let story = "The cat saw the other cat. THE END";
+    let words = text::word_frequencies(story);
+    println!("{} words; most common {:?}", text::word_count(story), words.most_common(2));
+
+    // Reversed by char, the accent lands on the wrong letter and the flag
+    // turns into another one; by grapheme cluster they stay whole
+    let noel = "Noe\u{308}l in 🇨🇦";
+    println!("{} reversed by char: {}", noel, noel.chars().rev().collect::<String>());
+    println!("{} reversed by grapheme: {}", noel, text::reverse(noel));
+
     // Heap allocate a string
-    let alice = String::from("I like dogs");
+    let alice = String::from("I like dogs and hotdogs");
     // Allocate new memory and store the modified string there
     let bob: String = alice.replace("dog", "cat");
 
+    // Only where "dogs" is a word of its own
+    let carol: String = text::replace_words(&alice, "dogs", "cats");
+
     println!("Alice says: {}", alice);
     println!("Bob says: {}", bob);
+    println!("Carol says: {}", carol);
 }
 
 /*
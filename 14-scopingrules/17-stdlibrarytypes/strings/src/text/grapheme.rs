@@ -0,0 +1,314 @@
+// grapheme.rs
+// Splitting a string into what a reader sees as characters. A Rust `char`
+// is one Unicode code point, and a character on the screen can be several:
+// "é" written as "e" and a combining acute accent, a flag made of two
+// regional indicator letters, a family emoji of four people joined by
+// zero width joiners, a Hangul syllable spelled out of its letters.
+// Reversing the `char`s of those pulls them apart; reversing the grapheme
+// clusters keeps them whole.
+//
+// The rules are the extended grapheme cluster rules of Unicode Standard
+// Annex #29, GB3 to GB13. What each code point is to those rules comes
+// from the table below, which covers the Latin, Greek, Cyrillic, Hebrew,
+// Arabic, Devanagari, Bengali, Thai, Hangul and CJK scripts and emoji, not
+// all of Unicode. Anything not in it is `Other`, which only ever starts a
+// cluster, so the worst a missing entry can do is split a cluster in two.
+// The Indic conjunct rule, GB9c, is left out.
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Class {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    // Hangul: leading consonant, vowel, trailing consonant, and the
+    // syllables made of the first two or all three.
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    ExtendedPictographic,
+    Other,
+}
+
+use self::Class::*;
+
+// (first, last, class), sorted and not overlapping.
+static TABLE: &[(u32, u32, Class)] = &[
+    (0x0000, 0x0009, Control),
+    (0x000A, 0x000A, Lf),
+    (0x000B, 0x000C, Control),
+    (0x000D, 0x000D, Cr),
+    (0x000E, 0x001F, Control),
+    (0x007F, 0x009F, Control),
+    (0x00A9, 0x00A9, ExtendedPictographic),
+    (0x00AD, 0x00AD, Control),
+    (0x00AE, 0x00AE, ExtendedPictographic),
+    (0x0300, 0x036F, Extend),
+    (0x0483, 0x0489, Extend),
+    (0x0591, 0x05BD, Extend),
+    (0x05BF, 0x05BF, Extend),
+    (0x05C1, 0x05C2, Extend),
+    (0x05C4, 0x05C5, Extend),
+    (0x05C7, 0x05C7, Extend),
+    (0x0600, 0x0605, Prepend),
+    (0x0610, 0x061A, Extend),
+    (0x061C, 0x061C, Control),
+    (0x064B, 0x065F, Extend),
+    (0x0670, 0x0670, Extend),
+    (0x06D6, 0x06DC, Extend),
+    (0x06DD, 0x06DD, Prepend),
+    (0x06DF, 0x06E4, Extend),
+    (0x06E7, 0x06E8, Extend),
+    (0x06EA, 0x06ED, Extend),
+    (0x070F, 0x070F, Prepend),
+    (0x0900, 0x0902, Extend),
+    (0x0903, 0x0903, SpacingMark),
+    (0x093A, 0x093A, Extend),
+    (0x093B, 0x093B, SpacingMark),
+    (0x093C, 0x093C, Extend),
+    (0x093E, 0x0940, SpacingMark),
+    (0x0941, 0x0948, Extend),
+    (0x0949, 0x094C, SpacingMark),
+    (0x094D, 0x094D, Extend),
+    (0x094E, 0x094F, SpacingMark),
+    (0x0951, 0x0957, Extend),
+    (0x0962, 0x0963, Extend),
+    (0x0981, 0x0981, Extend),
+    (0x0982, 0x0983, SpacingMark),
+    (0x09BC, 0x09BC, Extend),
+    (0x09BE, 0x09BE, Extend),
+    (0x09BF, 0x09C0, SpacingMark),
+    (0x09C1, 0x09C4, Extend),
+    (0x09C7, 0x09C8, SpacingMark),
+    (0x09CB, 0x09CC, SpacingMark),
+    (0x09CD, 0x09CD, Extend),
+    (0x09D7, 0x09D7, Extend),
+    (0x09E2, 0x09E3, Extend),
+    (0x0E31, 0x0E31, Extend),
+    (0x0E33, 0x0E33, SpacingMark),
+    (0x0E34, 0x0E3A, Extend),
+    (0x0E47, 0x0E4E, Extend),
+    (0x1100, 0x115F, L),
+    (0x1160, 0x11A7, V),
+    (0x11A8, 0x11FF, T),
+    (0x1AB0, 0x1AFF, Extend),
+    (0x1DC0, 0x1DFF, Extend),
+    (0x200B, 0x200B, Control),
+    (0x200C, 0x200C, Extend),
+    (0x200D, 0x200D, Zwj),
+    (0x200E, 0x200F, Control),
+    (0x2028, 0x202E, Control),
+    (0x203C, 0x203C, ExtendedPictographic),
+    (0x2049, 0x2049, ExtendedPictographic),
+    (0x2060, 0x206F, Control),
+    (0x20D0, 0x20F0, Extend),
+    (0x2122, 0x2122, ExtendedPictographic),
+    (0x2139, 0x2139, ExtendedPictographic),
+    (0x2194, 0x2199, ExtendedPictographic),
+    (0x21A9, 0x21AA, ExtendedPictographic),
+    (0x231A, 0x231B, ExtendedPictographic),
+    (0x2328, 0x2328, ExtendedPictographic),
+    (0x23CF, 0x23CF, ExtendedPictographic),
+    (0x23E9, 0x23F3, ExtendedPictographic),
+    (0x23F8, 0x23FA, ExtendedPictographic),
+    (0x24C2, 0x24C2, ExtendedPictographic),
+    (0x25AA, 0x25AB, ExtendedPictographic),
+    (0x25B6, 0x25B6, ExtendedPictographic),
+    (0x25C0, 0x25C0, ExtendedPictographic),
+    (0x25FB, 0x25FE, ExtendedPictographic),
+    (0x2600, 0x27BF, ExtendedPictographic),
+    (0x2934, 0x2935, ExtendedPictographic),
+    (0x2B05, 0x2B07, ExtendedPictographic),
+    (0x2B1B, 0x2B1C, ExtendedPictographic),
+    (0x2B50, 0x2B50, ExtendedPictographic),
+    (0x2B55, 0x2B55, ExtendedPictographic),
+    (0x302A, 0x302F, Extend),
+    (0x3030, 0x3030, ExtendedPictographic),
+    (0x303D, 0x303D, ExtendedPictographic),
+    (0x3099, 0x309A, Extend),
+    (0x3297, 0x3297, ExtendedPictographic),
+    (0x3299, 0x3299, ExtendedPictographic),
+    (0xA960, 0xA97C, L),
+    (0xD7B0, 0xD7C6, V),
+    (0xD7CB, 0xD7FB, T),
+    (0xFE00, 0xFE0F, Extend),
+    (0xFE20, 0xFE2F, Extend),
+    (0xFEFF, 0xFEFF, Control),
+    (0xFF9E, 0xFF9F, Extend),
+    (0xFFF0, 0xFFFB, Control),
+    (0x1F000, 0x1F0FF, ExtendedPictographic),
+    (0x1F10D, 0x1F10F, ExtendedPictographic),
+    (0x1F12F, 0x1F12F, ExtendedPictographic),
+    (0x1F16C, 0x1F171, ExtendedPictographic),
+    (0x1F17E, 0x1F17F, ExtendedPictographic),
+    (0x1F18E, 0x1F18E, ExtendedPictographic),
+    (0x1F191, 0x1F19A, ExtendedPictographic),
+    (0x1F1AD, 0x1F1E5, ExtendedPictographic),
+    (0x1F1E6, 0x1F1FF, RegionalIndicator),
+    (0x1F201, 0x1F20F, ExtendedPictographic),
+    (0x1F21A, 0x1F21A, ExtendedPictographic),
+    (0x1F22F, 0x1F22F, ExtendedPictographic),
+    (0x1F232, 0x1F23A, ExtendedPictographic),
+    (0x1F23C, 0x1F23F, ExtendedPictographic),
+    (0x1F249, 0x1F3FA, ExtendedPictographic),
+    // Skin tones: they change the emoji before them.
+    (0x1F3FB, 0x1F3FF, Extend),
+    (0x1F400, 0x1F53D, ExtendedPictographic),
+    (0x1F546, 0x1F64F, ExtendedPictographic),
+    (0x1F680, 0x1F6FF, ExtendedPictographic),
+    (0x1F774, 0x1F77F, ExtendedPictographic),
+    (0x1F7D5, 0x1F7FF, ExtendedPictographic),
+    (0x1F80C, 0x1F80F, ExtendedPictographic),
+    (0x1F848, 0x1F84F, ExtendedPictographic),
+    (0x1F85A, 0x1F85F, ExtendedPictographic),
+    (0x1F888, 0x1F88F, ExtendedPictographic),
+    (0x1F8AE, 0x1F8FF, ExtendedPictographic),
+    (0x1F90C, 0x1F93A, ExtendedPictographic),
+    (0x1F93C, 0x1F945, ExtendedPictographic),
+    (0x1F947, 0x1FAFF, ExtendedPictographic),
+    (0x1FC00, 0x1FFFD, ExtendedPictographic),
+    (0xE0000, 0xE001F, Control),
+    // Tags, as in the flags of England, Scotland and Wales.
+    (0xE0020, 0xE007F, Extend),
+    (0xE0100, 0xE01EF, Extend),
+];
+
+// The precomposed Hangul syllables, 가 to 힣, are worked out rather than
+// listed: every 28th one, starting with the first, has no final consonant.
+const SYLLABLES: (u32, u32) = (0xAC00, 0xD7A3);
+
+fn class(c: char) -> Class {
+    let c = c as u32;
+    if c >= SYLLABLES.0 && c <= SYLLABLES.1 {
+        return if (c - SYLLABLES.0).is_multiple_of(28) { Lv } else { Lvt };
+    }
+    TABLE.binary_search_by(|&(first, last, _)| {
+            if last < c {
+                Ordering::Less
+            } else if first > c {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .map(|i| TABLE[i].2)
+        .unwrap_or(Other)
+}
+
+// Whether a combining mark or the like, which belongs with the character
+// before it. Used by the word splitting in mod.rs.
+pub fn is_extend(c: char) -> bool {
+    matches!(class(c), Extend | Zwj | SpacingMark)
+}
+
+// What is known about the cluster so far, for the rules that look back
+// further than one code point.
+#[derive(Default)]
+struct State {
+    // Regional indicators at the end of it. A flag is a pair of them.
+    regional: usize,
+    // An emoji, any number of `Extend`s and then a ZWJ, so an emoji next
+    // joins on.
+    emoji_zwj: bool,
+    // An emoji and then only `Extend`s.
+    emoji: bool,
+}
+
+impl State {
+    fn push(&mut self, c: Class) {
+        self.regional = if c == RegionalIndicator { self.regional + 1 } else { 0 };
+        self.emoji_zwj = self.emoji && c == Zwj;
+        self.emoji = c == ExtendedPictographic || (self.emoji && c == Extend);
+    }
+}
+
+// Whether there is a break between `before` and `after`.
+fn boundary(before: Class, after: Class, state: &State) -> bool {
+    match (before, after) {
+        // GB3, GB4, GB5
+        (Cr, Lf) => false,
+        (Cr, _) | (Lf, _) | (Control, _) => true,
+        (_, Cr) | (_, Lf) | (_, Control) => true,
+        // GB6, GB7, GB8: Hangul syllables
+        (L, L) | (L, V) | (L, Lv) | (L, Lvt) => false,
+        (Lv, V) | (V, V) | (Lv, T) | (V, T) => false,
+        (Lvt, T) | (T, T) => false,
+        // GB9, GB9a, GB9b
+        (_, Extend) | (_, Zwj) | (_, SpacingMark) | (Prepend, _) => false,
+        // GB11: emoji sequences
+        (Zwj, ExtendedPictographic) => !state.emoji_zwj,
+        // GB12, GB13: flags, two at a time
+        (RegionalIndicator, RegionalIndicator) => state.regional.is_multiple_of(2),
+        // GB999
+        _ => true,
+    }
+}
+
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut before = class(first);
+        let mut state = State::default();
+        state.push(before);
+        let mut end = self.rest.len();
+        for (i, c) in chars {
+            let after = class(c);
+            if boundary(before, after, &state) {
+                end = i;
+                break;
+            }
+            state.push(after);
+            before = after;
+        }
+        let (cluster, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(cluster)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split(s: &str) -> Vec<&str> {
+        graphemes(s).collect()
+    }
+
+    #[test]
+    fn clusters() {
+        assert_eq!(split("e\u{301}a\r\nb"), ["e\u{301}", "a", "\r\n", "b"]);
+        // Flags: Ireland, then France, then one left over.
+        assert_eq!(split("🇮🇪🇫🇷🇩"), ["🇮🇪", "🇫🇷", "🇩"]);
+        // Family, a thumbs up with a skin tone, and the flag of Scotland.
+        let family = "👨\u{200D}👩\u{200D}👧\u{200D}👦";
+        assert_eq!(split(family).len(), 1);
+        assert_eq!(split("👍🏽!"), ["👍🏽", "!"]);
+        assert_eq!(split("🏴\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}").len(), 1);
+        // A ZWJ not after an emoji doesn't join the next one on.
+        assert_eq!(split("a\u{200D}👍"), ["a\u{200D}", "👍"]);
+        // Hangul: a precomposed syllable, then the same spelled out.
+        assert_eq!(split("한\u{1112}\u{1161}\u{11AB}"), ["한", "\u{1112}\u{1161}\u{11AB}"]);
+        // Devanagari: "नमस्ते", with its vowel signs and virama attached.
+        assert_eq!(split("नमस्ते"), ["न", "म", "स्", "ते"]);
+        assert_eq!(split("ก้า"), ["ก้", "า"]);
+        assert_eq!(split(""), Vec::<&str>::new());
+    }
+}
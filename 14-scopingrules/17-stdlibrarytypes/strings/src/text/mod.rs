@@ -0,0 +1,233 @@
+// text/mod.rs
+// Looking at what a piece of text says rather than at its bytes: its
+// words and letters, how often each comes up, whether it is a pangram,
+// with every letter of an alphabet in it. None of it assumes English: a
+// letter is whatever `char::is_alphabetic` says, and case is folded the
+// Unicode way, so "STRASSE" and "straße" are the same word.
+//
+// Text is taken as it comes, not normalized: "é" as one code point and
+// "é" as "e" and a combining accent are different words here.
+use std::iter;
+
+use rbe::multiset::Multiset;
+
+pub mod grapheme;
+
+pub use self::grapheme::graphemes;
+
+pub const ENGLISH: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const GERMAN: &str = "abcdefghijklmnopqrstuvwxyzäöüß";
+pub const RUSSIAN: &str = "абвгдеёжзийклмнопрстуфхцчшщъыьэюя";
+pub const GREEK: &str = "αβγδεζηθικλμνξοπρστυφχψω";
+
+// The few letters whose folded form isn't just their lower case: the sharp
+// s and the ligatures fold to more than one letter, and the final sigma and
+// long s to the usual ones.
+static FOLDS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('ẞ', "ss"),
+    ('ς', "σ"),
+    ('ſ', "s"),
+    ('ﬀ', "ff"),
+    ('ﬁ', "fi"),
+    ('ﬂ', "fl"),
+    ('ﬃ', "ffi"),
+    ('ﬄ', "ffl"),
+    ('ﬅ', "st"),
+    ('ﬆ', "st"),
+];
+
+// A letter on its own, for counting letters: lower case, but a final sigma
+// is a sigma and a long s an s. `ß` stays as it is.
+pub fn fold_char(c: char) -> char {
+    match c {
+        'ς' => 'σ',
+        'ſ' => 's',
+        c => c.to_lowercase().next().unwrap_or(c),
+    }
+}
+
+// For comparing words without regard to case: "Straße" and "STRASSE" both
+// fold to "strasse", and "ΣΟΦΟΣ" and "σοφος" to "σοφοσ".
+pub fn fold_case(s: &str) -> String {
+    let mut folded = String::with_capacity(s.len());
+    for c in s.chars() {
+        match FOLDS.iter().find(|&&(from, _)| from == c) {
+            Some(&(_, to)) => folded.push_str(to),
+            None => folded.extend(c.to_lowercase()),
+        }
+    }
+    folded
+}
+
+// Letters, digits and the marks that go with them; `_` too, as in
+// identifiers.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || grapheme::is_extend(c)
+}
+
+// An apostrophe inside a word, as in "don't" and "l’homme", keeps it one
+// word.
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+// Where each word starts and ends, in bytes.
+fn word_spans(s: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next_is_word = chars.peek().is_some_and(|&(_, next)| is_word_char(next));
+        let in_word = is_word_char(c) || (start.is_some() && is_apostrophe(c) && next_is_word);
+        match (in_word, start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                spans.push((from, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        spans.push((from, s.len()));
+    }
+    spans
+}
+
+// The words of `s`, in order, as they are written.
+pub fn words(s: &str) -> Vec<&str> {
+    word_spans(s).into_iter().map(|(from, to)| &s[from..to]).collect()
+}
+
+pub fn word_count(s: &str) -> usize {
+    word_spans(s).len()
+}
+
+// Each word, case folded, with how many times it is used.
+pub fn word_frequencies(s: &str) -> Multiset<String> {
+    words(s).into_iter().map(fold_case).collect()
+}
+
+// Each letter, folded with `fold_char`, with how many times it is used.
+pub fn letter_frequencies(s: &str) -> Multiset<char> {
+    s.chars().filter(|c| c.is_alphabetic()).map(fold_char).collect()
+}
+
+// Each pair of letters next to each other in a word: "the" is "th", "he".
+pub fn bigram_frequencies(s: &str) -> Multiset<(char, char)> {
+    let mut bigrams = Multiset::new();
+    for word in words(s) {
+        let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).map(fold_char).collect();
+        bigrams.extend(letters.windows(2).map(|pair| (pair[0], pair[1])));
+    }
+    bigrams
+}
+
+// The letters of `alphabet` that `s` doesn't use, in the alphabet's order.
+pub fn missing_letters(s: &str, alphabet: &str) -> Vec<char> {
+    let used = letter_frequencies(s);
+    alphabet.chars()
+        .map(fold_char)
+        .filter(|c| !used.contains(c))
+        .collect()
+}
+
+pub fn is_pangram(s: &str, alphabet: &str) -> bool {
+    missing_letters(s, alphabet).is_empty()
+}
+
+// Backwards by what a reader sees as characters, so accents stay on their
+// letters and flags and emoji stay whole.
+pub fn reverse(s: &str) -> String {
+    let clusters: Vec<&str> = graphemes(s).collect();
+    clusters.into_iter().rev().collect()
+}
+
+// Every `from` that is a whole word, replaced with `to`: "dog" in "hot dog"
+// is, "dog" in "hotdogs" isn't. Case matters, as it does for
+// `str::replace`.
+pub fn replace_words(s: &str, from: &str, to: &str) -> String {
+    if from.is_empty() {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut copied = 0;
+    let mut search = 0;
+    while let Some(found) = s[search..].find(from) {
+        let start = search + found;
+        let end = start + from.len();
+        let before = s[..start].chars().next_back();
+        let after = s[end..].chars().next();
+        if before.is_none_or(|c| !is_word_char(c)) && after.is_none_or(|c| !is_word_char(c)) {
+            out.push_str(&s[copied..start]);
+            out.push_str(to);
+            copied = end;
+            search = end;
+        } else {
+            // Try again from the next character, not after the whole match:
+            // "aa" isn't a word in "aaa", but could be in "aaa aa".
+            search = start + s[start..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    out.push_str(&s[copied..]);
+    out
+}
+
+// `n` copies of `c`: a bar for a chart of frequencies.
+pub fn bar(c: char, n: usize) -> String {
+    iter::repeat_n(c, n).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pangrams() {
+        assert!(is_pangram("The quick brown fox jumps over the lazy dog", ENGLISH));
+        assert_eq!(missing_letters("The quick brown fox jumped over the lazy dog", ENGLISH), ['s']);
+        assert!(is_pangram("Victor jagt zwölf Boxkämpfer quer über den großen Sylter Deich", GERMAN));
+        assert!(is_pangram("Съешь же ещё этих мягких французских булок, да выпей чаю", RUSSIAN));
+        // A final sigma counts as a sigma.
+        assert!(is_pangram("ΦΩΣ φως", "σφω") && is_pangram("φως", "σ"));
+        assert_eq!(missing_letters("ΑΒΓ δεζ", "αβγδεζη"), ['η']);
+    }
+
+    #[test]
+    fn counting() {
+        assert_eq!(fold_case("Straße"), fold_case("STRASSE"));
+        assert_eq!(fold_case("ΣΟΦΟΣ σοφος"), "σοφοσ σοφοσ");
+        assert_eq!(fold_case("ﬁne İ"), "fine i\u{307}");
+
+        let s = "Don't panic: l’homme n'a pas PANIQUÉ, the café didn't panic";
+        assert_eq!(words(s), ["Don't", "panic", "l’homme", "n'a", "pas", "PANIQUÉ", "the", "café",
+                              "didn't", "panic"]);
+        assert_eq!(words("'quoted' end-to-end e\u{301}t\u{301}e\u{301}"),
+                   ["quoted", "end", "to", "end", "e\u{301}t\u{301}e\u{301}"]);
+        assert_eq!(word_count("  один, два;три  "), 3);
+        assert_eq!(word_frequencies("Der der DER die").most_common(1), [(&"der".to_string(), 3)]);
+
+        let letters = letter_frequencies("Ἀλέξανδρος ΑΛΕΞ");
+        assert_eq!(letters.count(&'λ'), 2);
+        assert_eq!(letters.count(&'σ'), 1);
+        let bigrams = bigram_frequencies("Ähre ähnlich, äh");
+        assert_eq!(bigrams.count(&('ä', 'h')), 3);
+        assert_eq!(bigrams.count(&('h', 'ä')), 0);
+    }
+
+    #[test]
+    fn editing() {
+        assert_eq!(reverse("noe\u{308}l 🇫🇷!"), "!🇫🇷 le\u{308}on");
+        assert_eq!(reverse("👍🏽한"), "한👍🏽");
+
+        assert_eq!(replace_words("I like dogs and hotdogs", "dogs", "cats"), "I like cats and hotdogs");
+        assert_eq!(replace_words("dog, hotdog, dog", "dog", "cat"), "cat, hotdog, cat");
+        assert_eq!(replace_words("aaa aa", "aa", "b"), "aaa b");
+        assert_eq!(replace_words("Café café cafés", "café", "bar"), "Café bar cafés");
+        // A combining accent after it makes it a different word.
+        assert_eq!(replace_words("cafe\u{301} cafe", "cafe", "bar"), "cafe\u{301} bar");
+        assert_eq!(replace_words("Ёлка, ёлки", "ёлки", "ели"), "Ёлка, ели");
+        assert_eq!(bar('#', 3), "###");
+    }
+}
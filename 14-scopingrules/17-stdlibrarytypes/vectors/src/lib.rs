@@ -0,0 +1,4 @@
+// lib.rs
+// `MyVec`, a vector built from scratch on `std::alloc`, for main.rs.
+pub mod rawvec;
+pub mod myvec;
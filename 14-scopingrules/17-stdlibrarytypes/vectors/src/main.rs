@@ -5,8 +5,21 @@ words: a pointer to the data, its length, and its capacity. The capacity
 indicates how much memory is reserved for the vector. The vector can grow as 
 long as the length is smaller than the capacity. When this threshold needs to be 
 surpassed, the vector is reallocated with a larger capacity.
+
+src/myvec.rs builds the same thing from scratch: `MyVec<T>`, on the raw
+allocation calls of `std::alloc` and the raw pointers of
+20-unsafeoperations/pointer.rs. src/rawvec.rs holds the pointer and the
+capacity and doubles the capacity when it runs out; `MyVec` adds the length
+and everything else, `push`, `pop`, `insert`, `remove`, `drain`, slices
+through `Deref` and an owning iterator. A `// SAFETY:` comment over each
+unsafe block says what it relies on, and the tests check that every value is
+dropped exactly once, even when a `clone` panics half way through.
 */
 
+extern crate vectors;
+
+use vectors::myvec::MyVec;
+
 fn main() {
     // Iterators can be collected into vectors
     let collected_iterator: Vec<i32> = (0..10).collect();
@@ -34,6 +47,17 @@ fn main() {
     // `pop` removes the last element from the vector and returns it
     println!("Pop last element: {:?}", xs.pop());
 
+    // The same from scratch: watch the capacity double as it fills
+    let mut ys: MyVec<i32> = MyVec::new();
+    for i in 1..=9 {
+        ys.push(i);
+        println!("MyVec: {:?}, capacity {}", ys, ys.capacity());
+    }
+    ys.insert(0, 0);
+    println!("Insert 0 at the front, remove {}: {:?}", ys.remove(5), ys);
+    let drained: Vec<i32> = ys.drain(..4).collect();
+    println!("Drain the first four, {:?}, leaving {:?}", drained, ys);
+
     // Out of bounds indexing yields a panic
     println!("Fourth element: {}", xs[3]);
 }
@@ -0,0 +1,563 @@
+// myvec.rs
+// `MyVec<T>`, a `Vec<T>` written out by hand: a `RawVec` for the memory
+// and a length saying how many of its places, from the start, hold values.
+// Everything unsafe here comes down to keeping that one promise, the first
+// `len` places are values and the rest are not, at every point where
+// code we don't control can run: a `clone`, a `drop`, the caller's loop
+// over an iterator. Any of those can panic, and a panic must leave behind
+// nothing dropped twice and nothing read that isn't there. Leaking is
+// allowed; it is safe, if untidy.
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+use std::ptr;
+use std::slice;
+
+use rawvec::RawVec;
+
+pub struct MyVec<T> {
+    buf: RawVec<T>,
+    len: usize,
+}
+
+impl<T> Default for MyVec<T> {
+    fn default() -> MyVec<T> {
+        MyVec::new()
+    }
+}
+
+impl<T> MyVec<T> {
+    pub fn new() -> MyVec<T> {
+        MyVec { buf: RawVec::new(), len: 0 }
+    }
+
+    pub fn with_capacity(cap: usize) -> MyVec<T> {
+        MyVec { buf: RawVec::with_capacity(cap), len: 0 }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(self.len, additional);
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.len == self.buf.capacity() {
+            self.buf.grow();
+        }
+        // SAFETY: `len < cap` now, so place `len` is in the allocation, and
+        // it holds no value to be overwritten.
+        unsafe { ptr::write(self.buf.ptr().add(self.len), value) }
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        // The length goes down first: the value read out is the caller's
+        // now, and must not be dropped again with the vector.
+        self.len -= 1;
+        // SAFETY: the old `len` was at least 1, so place `len` holds a value.
+        Some(unsafe { ptr::read(self.buf.ptr().add(self.len)) })
+    }
+
+    // Puts `value` at `index`, moving everything after it along one.
+    // Panics if `index` is past the end.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "insertion index {} is out of bounds (len {})", index, self.len);
+        if self.len == self.buf.capacity() {
+            self.buf.grow();
+        }
+        // SAFETY: `index <= len < cap`, so the `len - index` values from
+        // `index` on can move up one and still be in the allocation, and
+        // place `index` is then free to write.
+        unsafe {
+            let at = self.buf.ptr().add(index);
+            ptr::copy(at, at.add(1), self.len - index);
+            ptr::write(at, value);
+        }
+        self.len += 1;
+    }
+
+    // Takes out the value at `index`, moving everything after it back one.
+    // Panics if there is no value there.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "removal index {} is out of bounds (len {})", index, self.len);
+        self.len -= 1;
+        // SAFETY: `index` was below the old `len`, so place `index` holds a
+        // value, and so do the `len - index` places after it that move back
+        // over it. Once moved, the value read out is the only copy.
+        unsafe {
+            let at = self.buf.ptr().add(index);
+            let value = ptr::read(at);
+            ptr::copy(at.add(1), at, self.len - index);
+            value
+        }
+    }
+
+    // Drops everything after the first `len`.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            // SAFETY: `len < self.len <= cap`, so `len` is in the allocation.
+            let tail = ptr::slice_from_raw_parts_mut(unsafe { self.buf.ptr().add(len) }, self.len - len);
+            // If one of the drops panics, the rest are still dropped and
+            // the length already says they're gone.
+            self.len = len;
+            // SAFETY: `len..self.len` held values, and nothing counts them
+            // any more to drop them again.
+            unsafe { ptr::drop_in_place(tail) }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    // Removes the values in `range` and hands them out one at a time; the
+    // values after the range move back to close the gap when the `Drain`
+    // is dropped, and any the caller didn't take are dropped then too.
+    // Panics if the range isn't within the vector.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("drain range starts after usize::MAX"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("drain range ends after usize::MAX"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end && end <= self.len,
+                "drain range {}..{} is out of bounds (len {})", start, end, self.len);
+        let tail = self.len - end;
+        // Until the `Drain` is dropped the vector claims only what comes
+        // before the range. If the `Drain` is forgotten rather than
+        // dropped, the rest is leaked, never dropped twice.
+        self.len = start;
+        Drain { vec: self, start, next: start, end, tail_start: end, tail }
+    }
+}
+
+impl<T: Clone> MyVec<T> {
+    // Clones each of `values` onto the end. The length goes up after each
+    // clone, so if one panics the vector holds the ones cloned so far, and
+    // they are dropped with it like any others.
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        self.reserve(values.len());
+        for value in values {
+            // SAFETY: `reserve` made room for all of `values`, so
+            // `len < cap` at each write.
+            unsafe { ptr::write(self.buf.ptr().add(self.len), value.clone()) }
+            self.len += 1;
+        }
+    }
+}
+
+impl<T> Drop for MyVec<T> {
+    // Drops the values; the `RawVec` then frees the memory.
+    fn drop(&mut self) {
+        // SAFETY: the first `len` places hold values, and only the vector
+        // drops them.
+        unsafe { ptr::drop_in_place(&mut self[..]) }
+    }
+}
+
+impl<T> Deref for MyVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: the first `len` places hold values, and the pointer is
+        // non-null and aligned even when nothing is allocated.
+        unsafe { slice::from_raw_parts(self.buf.ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for MyVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: as for `deref`, and `&mut self` means no other borrow of
+        // the values is alive.
+        unsafe { slice::from_raw_parts_mut(self.buf.ptr(), self.len) }
+    }
+}
+
+impl<T: Clone> Clone for MyVec<T> {
+    fn clone(&self) -> MyVec<T> {
+        let mut copy = MyVec::with_capacity(self.len);
+        copy.extend_from_slice(self);
+        copy
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MyVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for MyVec<T> {
+    fn eq(&self, other: &MyVec<T>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T> FromIterator<T> for MyVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> MyVec<T> {
+        let mut vec = MyVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T> Extend<T> for MyVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+// Takes the values out of the vector, first to last (or last to first).
+// Positions are kept as indexes rather than pointers, which for a
+// zero-sized `T` would never move.
+pub struct IntoIter<T> {
+    buf: RawVec<T>,
+    // The values not yet handed out are those at `start..end`.
+    start: usize,
+    end: usize,
+}
+
+impl<T> IntoIterator for MyVec<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        // Take the buffer out without running `MyVec`'s `Drop`, which
+        // would drop the values the iterator is about to hand out.
+        let vec = mem::ManuallyDrop::new(self);
+        // SAFETY: `vec` is never dropped, so this is the only `RawVec` left
+        // to free the memory.
+        let buf = unsafe { ptr::read(&vec.buf) };
+        IntoIter { buf, start: 0, end: vec.len }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        // SAFETY: the old `start` was below `end`, so it holds a value not
+        // yet handed out, and `start` has moved past it.
+        Some(unsafe { ptr::read(self.buf.ptr().add(self.start - 1)) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.end - self.start;
+        (left, Some(left))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: `start <= end` still, so place `end` holds a value not
+        // yet handed out, and `end` no longer covers it.
+        Some(unsafe { ptr::read(self.buf.ptr().add(self.end)) })
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+// The partial drop: only the values not handed out are still the
+// iterator's to drop. Then the `RawVec` frees the memory.
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        // SAFETY: `start <= end <= cap`, so `start` is in the allocation.
+        let left = ptr::slice_from_raw_parts_mut(unsafe { self.buf.ptr().add(self.start) },
+                                                 self.end - self.start);
+        // SAFETY: `start..end` are the values not handed out, dropped here
+        // once.
+        unsafe { ptr::drop_in_place(left) }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MyVec<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut MyVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> slice::IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+pub struct Drain<'a, T: 'a> {
+    vec: &'a mut MyVec<T>,
+    // Where the range started, which is where the tail goes back to.
+    start: usize,
+    // The values not yet handed out are those at `next..end`.
+    next: usize,
+    end: usize,
+    // The values after the range: where they are, and how many.
+    tail_start: usize,
+    tail: usize,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        // SAFETY: the old `next` was below `end`, in the drained range, whose
+        // values the vector's length no longer counts.
+        Some(unsafe { ptr::read(self.vec.buf.ptr().add(self.next - 1)) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.end - self.next;
+        (left, Some(left))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: `next <= end` still, so place `end` is a value of the
+        // drained range not yet handed out.
+        Some(unsafe { ptr::read(self.vec.buf.ptr().add(self.end)) })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        let ptr = self.vec.buf.ptr();
+        // A drop that panics here leaves the vector at `start` long, and
+        // the tail leaked.
+        // SAFETY: `next <= end <= cap`, so `next` is in the allocation.
+        let left = ptr::slice_from_raw_parts_mut(unsafe { ptr.add(self.next) }, self.end - self.next);
+        self.next = self.end;
+        // SAFETY: `next..end` were the values of the drained range not
+        // handed out, dropped here once since `next` has moved up first.
+        unsafe { ptr::drop_in_place(left) }
+        // SAFETY: the `tail` values from `tail_start` are still in place,
+        // and `start <= tail_start`, so they fit back from `start`;
+        // `ptr::copy` allows the two to overlap.
+        unsafe { ptr::copy(ptr.add(self.tail_start), ptr.add(self.start), self.tail) }
+        self.vec.len = self.start + self.tail;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    // Counts its drops in a shared cell, and panics when cloned if told to.
+    struct Counted {
+        id: usize,
+        drops: Rc<Cell<usize>>,
+        panic_on_clone: bool,
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Counted {
+            if self.panic_on_clone {
+                panic!("cloning {}", self.id);
+            }
+            Counted { id: self.id, drops: self.drops.clone(), panic_on_clone: false }
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    fn counted(n: usize, drops: &Rc<Cell<usize>>) -> MyVec<Counted> {
+        (0..n).map(|id| Counted { id, drops: drops.clone(), panic_on_clone: false }).collect()
+    }
+
+    fn ids(v: &[Counted]) -> Vec<usize> {
+        v.iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn editing() {
+        let mut v: MyVec<String> = MyVec::new();
+        assert_eq!(v.capacity(), 0);
+        for word in "one two three four five".split(' ') {
+            v.push(word.to_string());
+        }
+        assert_eq!((v.len(), v.capacity()), (5, 8));
+        v.insert(0, "zero".to_string());
+        v.insert(6, "six".to_string());
+        assert_eq!(v.remove(3), "three");
+        assert_eq!(v.pop().as_deref(), Some("six"));
+        assert_eq!(v.join(" "), "zero one two four five");
+
+        // Through `Deref`, all of the slice methods
+        v.sort();
+        assert_eq!(v[0], "five");
+        assert!(v.contains(&"two".to_string()));
+
+        let drained: Vec<String> = v.drain(1..3).collect();
+        assert_eq!(drained, ["four", "one"]);
+        assert_eq!(format!("{:?}", v), "[\"five\", \"two\", \"zero\"]");
+        assert_eq!(v.drain(..).rev().collect::<Vec<_>>(), ["zero", "two", "five"]);
+        assert!(v.is_empty() && v.pop().is_none());
+
+        let numbers: MyVec<i32> = (1..=100).collect();
+        assert_eq!(numbers.iter().sum::<i32>(), 5050);
+        assert_eq!(numbers.clone(), numbers);
+        let mut odd = 0;
+        for n in &numbers {
+            odd += n % 2;
+        }
+        assert_eq!(odd, 50);
+        assert_eq!(numbers.into_iter().rev().take(2).collect::<Vec<_>>(), [100, 99]);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut v = MyVec::new();
+        assert_eq!(v.capacity(), usize::MAX);
+        for _ in 0..1000 {
+            v.push(());
+        }
+        v.insert(500, ());
+        v.remove(0);
+        assert_eq!(v.pop(), Some(()));
+        assert_eq!(v.drain(10..20).count(), 10);
+        assert_eq!(v.len(), 989);
+        assert_eq!(v.into_iter().count(), 989);
+
+        let drops = Rc::new(Cell::new(0));
+        struct Unit<'a>(&'a Cell<usize>);
+        impl<'a> Drop for Unit<'a> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+        let mut units: MyVec<Unit> = (0..10).map(|_| Unit(&drops)).collect();
+        drop(units.drain(2..5));
+        assert_eq!((units.len(), drops.get()), (7, 3));
+        let mut iter = units.into_iter();
+        iter.next();
+        drop(iter);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn dropping() {
+        let drops = Rc::new(Cell::new(0));
+        drop(counted(10, &drops));
+        assert_eq!(drops.get(), 10);
+
+        // An iterator only part used drops the rest, and only the rest.
+        drops.set(0);
+        let mut iter = counted(10, &drops).into_iter();
+        let first = iter.next().unwrap();
+        let last = iter.next_back().unwrap();
+        assert_eq!((first.id, last.id, iter.len()), (0, 9, 8));
+        drop(iter);
+        assert_eq!(drops.get(), 8);
+        drop((first, last));
+        assert_eq!(drops.get(), 10);
+
+        // A drain only part used drops its range and keeps the tail.
+        drops.set(0);
+        let mut v = counted(10, &drops);
+        {
+            let mut drain = v.drain(2..7);
+            assert_eq!(drain.next().map(|c| c.id), Some(2));
+            assert_eq!(drain.next_back().map(|c| c.id), Some(6));
+        }
+        assert_eq!(ids(&v), [0, 1, 7, 8, 9]);
+        assert_eq!(drops.get(), 5);
+
+        // A forgotten drain leaks the range and the tail, and drops
+        // nothing twice.
+        mem::forget(v.drain(1..2));
+        assert_eq!(ids(&v), [0]);
+        v.truncate(0);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn panic_during_clone() {
+        let drops = Rc::new(Cell::new(0));
+        let mut v = counted(5, &drops);
+        v[3].panic_on_clone = true;
+
+        // Clones 0, 1 and 2, then panics: the copy so far is dropped, and
+        // only it.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| v.clone()));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 3);
+        assert_eq!(ids(&v), [0, 1, 2, 3, 4]);
+
+        // The same part way through extending a vector that already has
+        // values in it: it keeps them and the clones made before the panic.
+        let mut w = counted(2, &drops);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| w.extend_from_slice(&v)));
+        assert!(result.is_err());
+        assert_eq!(ids(&w), [0, 1, 0, 1, 2]);
+        drop(w);
+        drop(v);
+        assert_eq!(drops.get(), 3 + 5 + 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index 2 is out of bounds (len 1)")]
+    fn insert_out_of_bounds() {
+        let mut v = MyVec::new();
+        v.push(1);
+        v.insert(2, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "drain range starts after usize::MAX")]
+    fn drain_start_overflow() {
+        let mut v: MyVec<i32> = MyVec::new();
+        v.drain((Bound::Excluded(usize::MAX), Bound::Unbounded));
+    }
+
+    #[test]
+    #[should_panic(expected = "drain range ends after usize::MAX")]
+    fn drain_end_overflow() {
+        let mut v: MyVec<i32> = MyVec::new();
+        v.drain(0..=usize::MAX);
+    }
+}
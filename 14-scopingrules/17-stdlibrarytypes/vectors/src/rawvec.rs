@@ -0,0 +1,108 @@
+// rawvec.rs
+// The memory half of a vector: a pointer to room for `cap` values of `T`,
+// got from `std::alloc` and given back to it on drop. `RawVec` knows
+// nothing about which of those places hold values; that is `MyVec`'s job,
+// in myvec.rs. Keeping the two apart means the allocation is freed
+// exactly once however `MyVec` and its iterators finish with the values.
+//
+// A zero-sized type needs no memory at all. For one, the pointer is left
+// dangling (non-null and aligned, which is all a zero-sized read or write
+// asks for), nothing is ever allocated, and the capacity is `usize::MAX`,
+// so the vector never tries to grow.
+use std::alloc::{self, Layout};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+pub struct RawVec<T> {
+    ptr: NonNull<T>,
+    cap: usize,
+    // Says the `RawVec` owns `T`s, for the drop checker.
+    _owns: PhantomData<T>,
+}
+
+// A `RawVec` is no more shared than the `T`s it holds.
+unsafe impl<T: Send> Send for RawVec<T> {}
+unsafe impl<T: Sync> Sync for RawVec<T> {}
+
+impl<T> Default for RawVec<T> {
+    fn default() -> RawVec<T> {
+        RawVec::new()
+    }
+}
+
+impl<T> RawVec<T> {
+    pub fn new() -> RawVec<T> {
+        let cap = if mem::size_of::<T>() == 0 { usize::MAX } else { 0 };
+        RawVec { ptr: NonNull::dangling(), cap, _owns: PhantomData }
+    }
+
+    pub fn with_capacity(cap: usize) -> RawVec<T> {
+        let mut buf = RawVec::new();
+        if cap > buf.cap {
+            buf.grow_to(cap);
+        }
+        buf
+    }
+
+    pub fn ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    // Doubles the capacity, from 4 at the least. Doubling is what makes
+    // `push` amortized O(1): each value is moved on average at most once
+    // more however long the vector gets.
+    pub fn grow(&mut self) {
+        let cap = if self.cap == 0 { 4 } else { self.cap.checked_mul(2).expect("capacity overflow") };
+        self.grow_to(cap);
+    }
+
+    // Room for at least `len + additional`.
+    pub fn reserve(&mut self, len: usize, additional: usize) {
+        let needed = len.checked_add(additional).expect("capacity overflow");
+        if needed > self.cap {
+            self.grow_to(needed.max(self.cap.saturating_mul(2)));
+        }
+    }
+
+    fn grow_to(&mut self, cap: usize) {
+        // Only reached for zero-sized types when `usize::MAX` isn't enough.
+        assert!(mem::size_of::<T>() != 0, "capacity overflow");
+        // `Layout::array` refuses anything over `isize::MAX` bytes, which
+        // is as far as pointer offsets can go.
+        let layout = Layout::array::<T>(cap).expect("capacity overflow");
+        let ptr = if self.cap == 0 {
+            // SAFETY: `cap > self.cap`, so `cap > 0`, and `T` isn't
+            // zero-sized, so `layout` isn't either.
+            unsafe { alloc::alloc(layout) }
+        } else {
+            let old = Layout::array::<T>(self.cap).unwrap();
+            // SAFETY: `self.cap != 0`, so `ptr` came from `alloc` or
+            // `realloc` with `old`, and the new size is neither zero nor
+            // over `isize::MAX`, as `Layout::array` checked.
+            unsafe { alloc::realloc(self.ptr.as_ptr() as *mut u8, old, layout.size()) }
+        };
+        self.ptr = match NonNull::new(ptr as *mut T) {
+            Some(ptr) => ptr,
+            None => alloc::handle_alloc_error(layout),
+        };
+        self.cap = cap;
+    }
+}
+
+// Frees the memory only. Whatever values are still in it must have been
+// dropped or moved out already.
+impl<T> Drop for RawVec<T> {
+    fn drop(&mut self) {
+        if self.cap != 0 && mem::size_of::<T>() != 0 {
+            let layout = Layout::array::<T>(self.cap).unwrap();
+            // SAFETY: `cap != 0` and `T` isn't zero-sized, so `ptr` was
+            // allocated, with this very layout.
+            unsafe { alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout) }
+        }
+    }
+}
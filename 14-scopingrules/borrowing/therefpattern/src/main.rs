@@ -4,8 +4,10 @@ keyword can be used to take references to the fields of a struct/tuple. The
 example below shows a few instances where this can be useful:
 */
 
-#[derive(Clone, Copy)]
-struct Point { x: i32, y: i32 }
+extern crate rbe;
+
+// A `Copy` struct with two fields; here they are `i32`s.
+use rbe::geometry::Point;
 
 fn main() {
     let c = 'Q';
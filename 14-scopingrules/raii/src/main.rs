@@ -7,8 +7,24 @@ destructor is called and its owned resources are freed.
 This behavior shields against resource leak bugs, so you'll never have to 
 manually free memory or worry about memory leaks again! Here's a quick 
 showcase:
+
+notes.txt has valgrind's view of it. This program also checks for itself: rbe's
+`Counting` is its global allocator, so every allocation and free is counted,
+and an `AllocSnapshot` around the loop below sees each box freed.
+
+$ cargo run
+1000 boxes: 1000 allocations of 4000 bytes, 1000 frees of 4000 bytes, net +0 bytes
 */
 
+extern crate rbe;
+
+use std::alloc::System;
+
+use rbe::allocation::{AllocSnapshot, Counting};
+
+#[global_allocator]
+static ALLOC: Counting = Counting::new(System);
+
 fn create_box() {
     // Allocate an integer on the heap
     let _box1 = Box::new(3i32);
@@ -30,8 +46,13 @@ fn main() {
 
     // Creating lots of boxes just for fun
     // There's no need to manually free memory!
-    for _ in 0u32..1_000 {
-        create_box();
+    {
+        // Prints what the loop allocated and freed when it goes out of scope
+        let boxes = AllocSnapshot::report("1000 boxes");
+        for _ in 0u32..1_000 {
+            create_box();
+        }
+        boxes.assert_no_leak();
     }
 
     // `_box2` is destroyed here, and memory gets freed
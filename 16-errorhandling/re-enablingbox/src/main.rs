@@ -31,58 +31,17 @@ By implementing this, our previous example would be just as valid when the error
 type is Box<Error> as it was before with DoubleError.
 */
 
+extern crate rbe;
+
 use std::error;
-use std::fmt;
-use std::num::ParseIntError;
+
+// `DoubleError` comes from rbe/src/errors.rs. It implements `error::Error`
+// there, which is all it takes for `try!` to box it.
+use rbe::errors::DoubleError;
 
 // Change the alias to `Box<error::Error>`.
 type Result<T> = std::result::Result<T, Box<error::Error>>;
 
-#[derive(Debug)]
-enum DoubleError {
-    EmptyVec,
-    Parse(ParseIntError),
-}
-
-impl From<ParseIntError> for DoubleError {
-    fn from(err: ParseIntError) -> DoubleError {
-        DoubleError::Parse(err)
-    }
-}
-
-impl fmt::Display for DoubleError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            DoubleError::EmptyVec =>
-                write!(f, "please use a vector with at least one element"),
-            DoubleError::Parse(ref e) => e.fmt(f),
-        }
-    }
-}
-
-impl error::Error for DoubleError {
-    fn description(&self) -> &str {
-        match *self {
-            // A very short description of the error. Doesn't need to be the
-            // same as `Display`.
-            DoubleError::EmptyVec => "empty vectors not allowed",
-            // This already impls `Error`, so defer to its own implementation.
-            DoubleError::Parse(ref e) => e.description(),
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
-        match *self {
-            // No underlying cause so return `None`.
-            DoubleError::EmptyVec => None,
-            // The cause is the underlying implementation error type. Is implicitly
-            // cast to the trait object `&error::Error`. This works because the
-            // underlying type already implements the `Error` trait.
-            DoubleError::Parse(ref e) => Some(e),
-        }
-    }
-}
-
 fn double_first(vec: Vec<&str>) -> Result<i32> {
     let first = try!(vec.first().ok_or(DoubleError::EmptyVec));
     let parsed = try!(first.parse::<i32>());
@@ -18,35 +18,14 @@ convert automatically. This means, if we rewrite this example with try! when
 From::from is implemented for our error type, the map_err will go away:
 */
 
-use std::num::ParseIntError;
-use std::fmt;
+extern crate rbe;
 
-type Result<T> = std::result::Result<T, DoubleError>;
-
-#[derive(Debug)]
-enum DoubleError {
-    EmptyVec,
-    Parse(ParseIntError),
-}
+// `DoubleError` comes from rbe/src/errors.rs, which also implements
+// `From<ParseIntError>` for it. `try!` calls that conversion whenever a
+// `ParseIntError` needs to become a `DoubleError`.
+use rbe::errors::DoubleError;
 
-// Implement the conversion from `ParseIntError` to `DoubleError`. This will be
-// automatically called by `try!` if a `ParseIntError` needs converting into
-// a `DoubleError`.
-impl From<ParseIntError> for DoubleError {
-    fn from(err: ParseIntError) -> DoubleError {
-        DoubleError::Parse(err)
-    }
-}
-
-impl fmt::Display for DoubleError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            DoubleError::EmptyVec =>
-                write!(f, "please use a vector with at least one element"),
-            DoubleError::Parse(ref e) => e.fmt(f),
-        }
-    }
-}
+type Result<T> = std::result::Result<T, DoubleError>;
 
 // The same structure as before but rather than chain all `Results`
 // and `Options` along, we `try!` to get the inner value out immediately.
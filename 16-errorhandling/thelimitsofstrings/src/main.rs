@@ -14,41 +14,15 @@ nicely fulfills the first two but not the second two:
 This makes String errors both difficult to react to and verbose to create. In fact, a nice looking error message has nothing to do with how the type is structured. It is simply a consequence of Display being implemented for the type. It should not be necessary to pollute logic heavy code with String formatting simply for nice error messages.
 */
 
-use std::num::ParseIntError;
-use std::fmt;
+extern crate rbe;
 
-type Result<T> = std::result::Result<T, DoubleError>;
-
-#[derive(Debug)]
-// Define our error types. These may be customized however is useful for our error
-// handling cases. Now we will be able to defer to the underlying tools error
-// implementation, write our own errors, or something in between.
-enum DoubleError {
-    // We don't require any extra info to detail this error.
-    EmptyVec,
-    // We will defer to the parse error implementation for their error. Supplying extra
-    // info would require adding more data to the type.
-    Parse(ParseIntError),
-}
+// Our error type, shared by the error handling examples, is `DoubleError`
+// in rbe/src/errors.rs. Its `fmt::Display` is implemented there too, apart
+// from where the errors are generated, so the display style doesn't clutter
+// the logic below.
+use rbe::errors::DoubleError;
 
-// How the type is displayed is completely separate from where the errors are generated.
-// We do not need to be concerned that the display style will clutter the complex logic
-// our utility requires. They are separate matters which are handled separately.
-//
-// We don't store extra info about the errors. If we had desired, for example, to state
-// which string failed to parse then we can't without modifying our types to carry that
-// information accordingly.
-impl fmt::Display for DoubleError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            DoubleError::EmptyVec =>
-                write!(f, "please use a vector with at least one element"),
-            // This is a wrapper so defer to the underlying types' own implementation
-            // of `fmt`.
-            DoubleError::Parse(ref e) => e.fmt(f),
-        }
-    }
-}
+type Result<T> = std::result::Result<T, DoubleError>;
 
 fn double_first(vec: Vec<&str>) -> Result<i32> {
     vec.first()
@@ -2,8 +2,22 @@
 Rust provides a Foreign Function Interface (FFI) to C libraries. Foreign 
 functions must be declared inside an extern block annotated with a #[link] 
 attribute containing the name of the foreign library.
+
+A struct passed to C must be laid out the way C lays it out. `#[repr(C)]` asks
+for that, and rbe's `layout_of!` shows what was got: C's `float complex` is
+two floats, real part first, 8 bytes in all.
+
+$ cargo run
+struct Complex: 8 bytes, align 4, 0 bytes padding
+offset  size  align  field
+     0     4      4  re: f32
+     4     4      4  im: f32
+the square root of -1+0i is 0+1i
 */
 
+#[macro_use]
+extern crate rbe;
+
 use std::fmt;
 
 // this extern block links to the libm library
@@ -15,6 +29,12 @@ extern {
 }
 
 fn main() {
+    // Check `Complex` is what `csqrtf` expects before handing it one
+    let layout = layout_of!(Complex { re, im });
+    print!("{}", layout);
+    let offsets: Vec<usize> = layout.fields.iter().map(|f| f.offset).collect();
+    assert_eq!((layout.size, offsets), (8, vec![0, 4]));
+
     // z = -1 + 0i
     let z = Complex { re: -1., im: 0. };
 
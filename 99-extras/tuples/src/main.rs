@@ -13,9 +13,10 @@ fn reverse(pair: (i32, bool)) -> (bool, i32) {
     (boolean, integer)
 }
 
-// The following struct is for the activity.
-#[derive(Debug)]
-struct Matrix(f32, f32, f32, f32);
+extern crate rbe;
+
+// The struct for the activity, from rbe/src/geometry.rs.
+use rbe::geometry::Matrix;
 
 fn main() {
     // A tuple with a bunch of different types
@@ -0,0 +1,307 @@
+// allocation.rs
+// `Counting`, an allocator that hands every request on to another one,
+// `System` unless told otherwise, and counts as it goes. A program makes it
+// the allocator for everything with
+//
+// #[global_allocator]
+// static ALLOC: Counting = Counting::new(System);
+//
+// and then `ALLOC.stats()` says how much is on the heap, the most there
+// has been, and how many allocations and frees there have been, and
+// `ALLOC.histogram()` says how big they were.
+//
+// An `AllocSnapshot` counts from when it is made, in the current thread
+// only, so that tests running side by side don't see each other's
+// allocations. Memory freed by a different thread from the one that
+// allocated it shows up as a leak in one and too much freed in the other.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Bucket i counts allocations of more than 2^(i-1) and at most 2^i bytes;
+// the last counts everything bigger too.
+const BUCKETS: usize = 24;
+
+pub struct Counting<A = System> {
+    inner: A,
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    reallocations: AtomicUsize,
+    allocated: AtomicUsize,
+    live: AtomicUsize,
+    peak: AtomicUsize,
+    sizes: [AtomicUsize; BUCKETS],
+}
+
+// The same counts for this thread. Plain `Cell`s with constant starting
+// values need no allocation to set up, which matters inside an allocator.
+thread_local! {
+    static THREAD: Cell<ThreadCounts> = const { Cell::new(ThreadCounts::ZERO) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ThreadCounts {
+    allocations: usize,
+    deallocations: usize,
+    allocated: usize,
+    freed: usize,
+}
+
+impl ThreadCounts {
+    const ZERO: ThreadCounts = ThreadCounts { allocations: 0, deallocations: 0, allocated: 0, freed: 0 };
+
+    fn now() -> ThreadCounts {
+        THREAD.try_with(Cell::get).unwrap_or(ThreadCounts::ZERO)
+    }
+}
+
+fn on_thread<F: FnOnce(&mut ThreadCounts)>(f: F) {
+    // After the thread's locals are gone there is nothing to count into.
+    let _ = THREAD.try_with(|cell| {
+        let mut counts = cell.get();
+        f(&mut counts);
+        cell.set(counts);
+    });
+}
+
+fn bucket(size: usize) -> usize {
+    let bits = (usize::BITS - size.saturating_sub(1).leading_zeros()) as usize;
+    bits.min(BUCKETS - 1)
+}
+
+impl<A> Counting<A> {
+    pub const fn new(inner: A) -> Counting<A> {
+        Counting {
+            inner,
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            reallocations: AtomicUsize::new(0),
+            allocated: AtomicUsize::new(0),
+            live: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            sizes: [const { AtomicUsize::new(0) }; BUCKETS],
+        }
+    }
+
+    // Over every thread, since the program started.
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+            reallocations: self.reallocations.load(Ordering::Relaxed),
+            allocated_bytes: self.allocated.load(Ordering::Relaxed),
+            live_bytes: self.live.load(Ordering::Relaxed),
+            peak_bytes: self.peak.load(Ordering::Relaxed),
+        }
+    }
+
+    // (largest size, how many) for each size of allocation there has been,
+    // smallest first: (8, 10) is ten allocations of 5 to 8 bytes.
+    pub fn histogram(&self) -> Vec<(usize, usize)> {
+        self.sizes.iter()
+            .enumerate()
+            .map(|(i, n)| (1 << i, n.load(Ordering::Relaxed)))
+            .filter(|&(_, n)| n > 0)
+            .collect()
+    }
+
+    fn allocated(&self, size: usize) {
+        self.allocated.fetch_add(size, Ordering::Relaxed);
+        let live = self.live.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(live, Ordering::Relaxed);
+        self.sizes[bucket(size)].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Counting<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = self.inner.alloc(layout);
+        if !p.is_null() {
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+            self.allocated(layout.size());
+            on_thread(|t| {
+                t.allocations += 1;
+                t.allocated += layout.size();
+            });
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = self.inner.alloc_zeroed(layout);
+        if !p.is_null() {
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+            self.allocated(layout.size());
+            on_thread(|t| {
+                t.allocations += 1;
+                t.allocated += layout.size();
+            });
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+        on_thread(|t| {
+            t.deallocations += 1;
+            t.freed += layout.size();
+        });
+    }
+
+    // Counted as the old block freed and a new one allocated, which is
+    // what it may well be.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let p = self.inner.realloc(ptr, layout, new_size);
+        if !p.is_null() {
+            self.reallocations.fetch_add(1, Ordering::Relaxed);
+            self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+            self.allocated(new_size);
+            on_thread(|t| {
+                t.allocated += new_size;
+                t.freed += layout.size();
+            });
+        }
+        p
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub reallocations: usize,
+    // Everything ever allocated, freed or not.
+    pub allocated_bytes: usize,
+    // On the heap now.
+    pub live_bytes: usize,
+    // The most there has been on the heap at once.
+    pub peak_bytes: usize,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} allocations, {} frees, {} reallocations; {} bytes live, {} at most",
+               self.allocations, self.deallocations, self.reallocations,
+               self.live_bytes, self.peak_bytes)
+    }
+}
+
+// What this thread allocated and freed between a snapshot and now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllocDelta {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub allocated_bytes: usize,
+    pub freed_bytes: usize,
+}
+
+impl AllocDelta {
+    // Allocated less freed: more than 0 is still on the heap.
+    pub fn net_bytes(&self) -> isize {
+        self.allocated_bytes as isize - self.freed_bytes as isize
+    }
+}
+
+impl fmt::Display for AllocDelta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} allocations of {} bytes, {} frees of {} bytes, net {:+} bytes",
+               self.allocations, self.allocated_bytes, self.deallocations, self.freed_bytes,
+               self.net_bytes())
+    }
+}
+
+// Counts from when it is made. `delta` says what has happened so far; a
+// snapshot made with `report` also prints it when it goes out of scope:
+//
+// {
+//     let _scope = AllocSnapshot::report("boxes");
+//     ...
+// }   // boxes: 1000 allocations of 4000 bytes, 1000 frees of 4000 bytes, net +0 bytes
+//
+// All zeros unless a `Counting` is the global allocator.
+pub struct AllocSnapshot {
+    start: ThreadCounts,
+    label: Option<&'static str>,
+}
+
+impl Default for AllocSnapshot {
+    fn default() -> AllocSnapshot {
+        AllocSnapshot::new()
+    }
+}
+
+impl AllocSnapshot {
+    pub fn new() -> AllocSnapshot {
+        AllocSnapshot { start: ThreadCounts::now(), label: None }
+    }
+
+    pub fn report(label: &'static str) -> AllocSnapshot {
+        AllocSnapshot { start: ThreadCounts::now(), label: Some(label) }
+    }
+
+    pub fn delta(&self) -> AllocDelta {
+        let now = ThreadCounts::now();
+        AllocDelta {
+            allocations: now.allocations - self.start.allocations,
+            deallocations: now.deallocations - self.start.deallocations,
+            allocated_bytes: now.allocated - self.start.allocated,
+            freed_bytes: now.freed - self.start.freed,
+        }
+    }
+
+    // Panics, saying what happened, if anything allocated since the
+    // snapshot is still on the heap.
+    pub fn assert_no_leak(&self) {
+        let delta = self.delta();
+        assert!(delta.net_bytes() <= 0, "leaked {} bytes: {}", delta.net_bytes(), delta);
+    }
+}
+
+impl Drop for AllocSnapshot {
+    fn drop(&mut self) {
+        if let Some(label) = self.label {
+            println!("{}: {}", label, self.delta());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem;
+
+    #[global_allocator]
+    static ALLOC: Counting = Counting::new(System);
+
+    #[test]
+    fn counting() {
+        assert_eq!((bucket(0), bucket(1), bucket(8), bucket(9), bucket(usize::MAX)), (0, 0, 3, 4, BUCKETS - 1));
+
+        let before = ALLOC.stats();
+        let snapshot = AllocSnapshot::new();
+        let boxes: Vec<Box<[u8; 100]>> = (0..10).map(|_| Box::new([0; 100])).collect();
+        let delta = snapshot.delta();
+        assert_eq!(delta.allocations, 11);
+        assert_eq!(delta.allocated_bytes, 1000 + 10 * mem::size_of::<usize>());
+        assert!(ALLOC.stats().peak_bytes >= 1000);
+        assert!(ALLOC.stats().allocations >= before.allocations + 11);
+        assert!(ALLOC.histogram().iter().any(|&(size, n)| size == 128 && n >= 10));
+
+        // The histogram's own Vec was allocated and freed in between.
+        drop(boxes);
+        snapshot.assert_no_leak();
+        assert_eq!(snapshot.delta().deallocations, 12);
+
+        let mut grow = Vec::with_capacity(1);
+        grow.push(1u64);
+        grow.reserve(1000);
+        let leaked = AllocSnapshot::new();
+        mem::forget(Box::new(7u32));
+        assert_eq!(leaked.delta().net_bytes(), 4);
+        mem::forget(grow);
+        assert_eq!(snapshot.delta().net_bytes(), 4 + 1001 * 8);
+    }
+}
@@ -0,0 +1,123 @@
+// collections.rs
+// The linked `List` of 03-customtypes/enums/tc_list, made generic over its
+// elements.
+use std::fmt;
+use std::mem;
+
+use self::List::*;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum List<T> {
+    // Cons: Tuple struct that wraps an element and a pointer to the next node
+    Cons(T, Box<List<T>>),
+    // Nil: A node that signifies the end of the linked list
+    #[default]
+    Nil,
+}
+
+impl<T> List<T> {
+    // Create an empty list
+    pub fn new() -> List<T> {
+        Nil
+    }
+
+    // Consume a list, and return the same list with a new element at its front
+    pub fn prepend(self, elem: T) -> List<T> {
+        Cons(elem, Box::new(self))
+    }
+
+    // Walks the list rather than recursing, so that long lists don't
+    // overflow the stack.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Nil => true,
+            Cons(..) => false,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self }
+    }
+}
+
+impl<T: fmt::Display> List<T> {
+    // Return representation of the list as a (heap allocated) string
+    pub fn stringify(&self) -> String {
+        self.to_string()
+    }
+}
+
+// The drop the compiler writes would recurse down the list, one call for
+// each node, and a long enough list would overflow the stack. This takes
+// the nodes after the first off one at a time instead, each with a `Nil`
+// tail by the time it is dropped.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        if let Cons(_, ref mut tail) = *self {
+            let mut next = mem::replace(&mut **tail, Nil);
+            while let Cons(_, ref mut tail) = next {
+                next = mem::replace(&mut **tail, Nil);
+            }
+        }
+    }
+}
+
+// `3, 2, 1, Nil`
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for elem in self.iter() {
+            write!(f, "{}, ", elem)?;
+        }
+        write!(f, "Nil")
+    }
+}
+
+pub struct Iter<'a, T: 'a> {
+    next: &'a List<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match *self.next {
+            Cons(ref head, ref tail) => {
+                self.next = tail;
+                Some(head)
+            }
+            Nil => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prepend_and_walk() {
+        let mut list = List::new();
+        assert!(list.is_empty());
+        assert_eq!(list.stringify(), "Nil");
+
+        list = list.prepend(1u32).prepend(2).prepend(3);
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+        assert_eq!(list.stringify(), "3, 2, 1, Nil");
+        assert_eq!(list.iter().cloned().collect::<Vec<u32>>(), [3, 2, 1]);
+    }
+
+    #[test]
+    fn long_lists_drop() {
+        let mut list = List::new();
+        for i in 0..1_000_000u32 {
+            list = list.prepend(i);
+        }
+        assert_eq!(list.len(), 1_000_000);
+        drop(list);
+    }
+}
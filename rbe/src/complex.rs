@@ -0,0 +1,98 @@
+// complex.rs
+// The `Complex` of 01-helloworld/formattedprint/display, with the arithmetic
+// that makes it a number.
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub real: f64,
+    pub imaginary: f64,
+}
+
+impl Complex {
+    pub fn new(real: f64, imaginary: f64) -> Complex {
+        Complex { real, imaginary }
+    }
+
+    pub fn i() -> Complex {
+        Complex::new(0.0, 1.0)
+    }
+
+    pub fn conjugate(&self) -> Complex {
+        Complex::new(self.real, -self.imaginary)
+    }
+
+    // The absolute value, |z|.
+    pub fn norm(&self) -> f64 {
+        self.real.hypot(self.imaginary)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(real: f64) -> Complex {
+        Complex::new(real, 0.0)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.real + other.real, self.imaginary + other.imaginary)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.real - other.real, self.imaginary - other.imaginary)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    // (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(self.real * other.real - self.imaginary * other.imaginary,
+                     self.real * other.imaginary + self.imaginary * other.real)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.real, -self.imaginary)
+    }
+}
+
+// `4 + 6i`, and `4 - 6i` rather than `4 + -6i`.
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.imaginary.is_sign_negative() {
+            write!(f, "{} - {}i", self.real, -self.imaginary)
+        } else {
+            write!(f, "{} + {}i", self.real, self.imaginary)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_display() {
+        let z = Complex::new(4.0, 6.0);
+        assert_eq!(z.to_string(), "4 + 6i");
+        assert_eq!(z.conjugate().to_string(), "4 - 6i");
+        assert_eq!(Complex::i() * Complex::i(), Complex::from(-1.0));
+        assert_eq!(z * z.conjugate(), Complex::from(52.0));
+        assert_eq!(z + -z, Complex::default());
+        assert_eq!(z - Complex::from(4.0), Complex::new(0.0, 6.0));
+        assert_eq!(Complex::new(3.0, -4.0).norm(), 5.0);
+    }
+}
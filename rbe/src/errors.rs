@@ -0,0 +1,71 @@
+// errors.rs
+// The `DoubleError` that 16-errorhandling builds up one step at a time, in
+// its finished form: it can be displayed, converted from the error it
+// wraps, and boxed as a `Box<dyn Error>`.
+use std::error;
+use std::fmt;
+use std::num::ParseIntError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DoubleError {
+    // We don't require any extra info to detail this error.
+    EmptyVec,
+    // We defer to the parse error implementation for their error.
+    Parse(ParseIntError),
+}
+
+// Called by `try!` and `?` when a `ParseIntError` needs converting into a
+// `DoubleError`, so that no `map_err` is needed.
+impl From<ParseIntError> for DoubleError {
+    fn from(err: ParseIntError) -> DoubleError {
+        DoubleError::Parse(err)
+    }
+}
+
+impl fmt::Display for DoubleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DoubleError::EmptyVec =>
+                write!(f, "please use a vector with at least one element"),
+            // This is a wrapper so defer to the underlying type's own
+            // implementation of `fmt`.
+            DoubleError::Parse(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for DoubleError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            // No underlying cause so return `None`.
+            DoubleError::EmptyVec => None,
+            // The cause is the underlying implementation error type.
+            DoubleError::Parse(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::error::Error;
+
+    fn double_first(vec: Vec<&str>) -> Result<i32, DoubleError> {
+        let first = vec.first().ok_or(DoubleError::EmptyVec)?;
+        Ok(2 * first.parse::<i32>()?)
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(double_first(vec!["21"]), Ok(42));
+        assert_eq!(double_first(vec![]), Err(DoubleError::EmptyVec));
+        assert!(DoubleError::EmptyVec.source().is_none());
+
+        let err = double_first(vec!["tofu"]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid digit found in string");
+        assert!(err.source().is_some());
+
+        let boxed: Box<dyn Error> = From::from(DoubleError::EmptyVec);
+        assert_eq!(boxed.to_string(), "please use a vector with at least one element");
+    }
+}
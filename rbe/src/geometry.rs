@@ -0,0 +1,184 @@
+// geometry.rs
+// Points, rectangles and matrices, as used by 03-customtypes/structures,
+// 08-functions/methods, boxstackheap, therefpattern and the tuples examples.
+use std::fmt;
+
+// A point in the plane. The coordinates are `f64` unless asked otherwise,
+// so `Point` alone is a `Point<f64>` and `Point { x: 0, y: 0 }` is a
+// `Point<i32>`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point<T = f64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    // A static method, taking two arguments. Static methods don't need to
+    // be called by an instance; they are generally used as constructors.
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl Point {
+    pub fn origin() -> Point {
+        Point { x: 0.0, y: 0.0 }
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+// A rectangle given by two opposite corners, in either order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    pub p1: Point,
+    pub p2: Point,
+}
+
+impl Rectangle {
+    pub fn new(p1: Point, p2: Point) -> Rectangle {
+        Rectangle { p1, p2 }
+    }
+
+    pub fn width(&self) -> f64 {
+        (self.p1.x - self.p2.x).abs()
+    }
+
+    pub fn height(&self) -> f64 {
+        (self.p1.y - self.p2.y).abs()
+    }
+
+    // An instance method. `&self` is sugar for `self: &Self`, where `Self`
+    // is the type of the caller object, here `Rectangle`.
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        2.0 * (self.width() + self.height())
+    }
+
+    // This method requires the caller object to be mutable: `&mut self`
+    // desugars to `self: &mut Self`.
+    pub fn translate(&mut self, x: f64, y: f64) {
+        self.p1.x += x;
+        self.p2.x += x;
+
+        self.p1.y += y;
+        self.p2.y += y;
+    }
+
+    // Left, bottom, right and top, whichever corners `p1` and `p2` are.
+    fn edges(&self) -> (f64, f64, f64, f64) {
+        (self.p1.x.min(self.p2.x), self.p1.y.min(self.p2.y),
+         self.p1.x.max(self.p2.x), self.p1.y.max(self.p2.y))
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new((self.p1.x + self.p2.x) / 2.0, (self.p1.y + self.p2.y) / 2.0)
+    }
+
+    // Points on the edges are inside.
+    pub fn contains(&self, p: &Point) -> bool {
+        let (left, bottom, right, top) = self.edges();
+        left <= p.x && p.x <= right && bottom <= p.y && p.y <= top
+    }
+
+    // Whether all of `other` is inside this one.
+    pub fn encloses(&self, other: &Rectangle) -> bool {
+        self.contains(&other.p1) && self.contains(&other.p2)
+    }
+
+    // Rectangles that only touch along an edge or at a corner intersect.
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        let (l1, b1, r1, t1) = self.edges();
+        let (l2, b2, r2, t2) = other.edges();
+        l1 <= r2 && l2 <= r1 && b1 <= t2 && b2 <= t1
+    }
+
+    // How far `p` is from the nearest point of the rectangle: 0 if it is
+    // inside.
+    pub fn distance_to(&self, p: &Point) -> f64 {
+        let (left, bottom, right, top) = self.edges();
+        let dx = (left - p.x).max(p.x - right).max(0.0);
+        let dy = (bottom - p.y).max(p.y - top).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{} - {}]", self.p1, self.p2)
+    }
+}
+
+// A 2x2 matrix as a tuple struct, row by row:
+//
+// ( .0, .1 )
+// ( .2, .3 )
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix(pub f32, pub f32, pub f32, pub f32);
+
+impl Matrix {
+    pub fn transpose(self) -> Matrix {
+        Matrix(self.0, self.2, self.1, self.3)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.0 * self.3 - self.1 * self.2
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "( {}, {} )\n( {}, {} )", self.0, self.1, self.2, self.3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rectangles() {
+        let mut r = Rectangle::new(Point::new(3.0, 4.0), Point::origin());
+        assert_eq!(r.area(), 12.0);
+        assert_eq!(r.perimeter(), 14.0);
+        assert_eq!(r.p1.distance(&r.p2), 5.0);
+        assert!(r.contains(&Point::new(3.0, 0.0)));
+
+        r.translate(1.0, 1.0);
+        assert_eq!(r.p2, Point::new(1.0, 1.0));
+        assert_eq!(r.area(), 12.0);
+        assert!(!r.contains(&Point::origin()));
+        assert_eq!(r.to_string(), "[(4, 5) - (1, 1)]");
+
+        let s = Rectangle::new(Point::new(2.0, 2.0), Point::new(1.0, 1.0));
+        assert_eq!(s.center(), Point::new(1.5, 1.5));
+        assert!(r.encloses(&s) && !s.encloses(&r));
+        assert!(s.intersects(&Rectangle::new(Point::new(2.0, 0.0), Point::new(5.0, 1.0))));
+        assert!(!s.intersects(&Rectangle::new(Point::new(2.1, 0.0), Point::new(5.0, 1.0))));
+        assert_eq!(s.distance_to(&Point::new(1.5, 1.5)), 0.0);
+        assert_eq!(s.distance_to(&Point::new(5.0, 6.0)), 5.0);
+
+        let p = Point { x: 1, y: -2 };
+        assert_eq!(p.to_string(), "(1, -2)");
+    }
+
+    #[test]
+    fn matrices() {
+        let m = Matrix(1.1, 1.2, 2.1, 2.2);
+        assert_eq!(m.transpose(), Matrix(1.1, 2.1, 1.2, 2.2));
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(Matrix(1.0, 2.0, 3.0, 4.0).determinant(), -2.0);
+        assert_eq!(m.to_string(), "( 1.1, 1.2 )\n( 2.1, 2.2 )");
+    }
+}
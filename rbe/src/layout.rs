@@ -0,0 +1,291 @@
+// layout.rs
+// Where the bytes of a struct go. `mem::size_of` says how big a struct is,
+// but not where each field sits in it or how much of it is padding: the
+// gaps the compiler leaves so that every field starts at a multiple of its
+// alignment. `layout_of!` finds out, given the struct and its fields:
+//
+// let layout = layout_of!(Complex { re, im });
+// println!("{}", layout);
+//
+// struct Complex: 8 bytes, align 4, 0 bytes padding
+// offset  size  align  field
+//      0     4      4  re: f32
+//      4     4      4  im: f32
+//
+// Each offset is measured, not worked out: the macro takes the address of
+// each field of an uninitialized value with `ptr::addr_of!`, which makes a
+// raw pointer without reading the field or making a reference to it, so it
+// is sound even though nothing is there yet.
+//
+// A plain Rust struct is laid out however the compiler likes, and it
+// already puts the fields in a good order. A `#[repr(C)]` struct, as is
+// needed to hand one to C, keeps the order it was written in, and there
+// `suggestion` is worth a look: it puts the most aligned fields first,
+// which leaves the least padding.
+use std::any;
+use std::cmp::Reverse;
+use std::fmt;
+use std::mem;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    // With the module paths taken off: `Vec<String>`, not
+    // `alloc::vec::Vec<alloc::string::String>`.
+    pub type_name: String,
+    pub offset: usize,
+    pub size: usize,
+    pub align: usize,
+}
+
+impl FieldLayout {
+    // `field` must point into the value `base` points to. Used by
+    // `layout_of!`; the pointers are only compared, never read.
+    pub fn of<T, F>(name: &'static str, base: *const T, field: *const F) -> FieldLayout {
+        FieldLayout {
+            name,
+            type_name: short_type_name(any::type_name::<F>()),
+            offset: field as usize - base as usize,
+            size: mem::size_of::<F>(),
+            align: mem::align_of::<F>(),
+        }
+    }
+
+    fn end(&self) -> usize {
+        self.offset + self.size
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeLayout {
+    pub name: String,
+    pub size: usize,
+    pub align: usize,
+    // In the order they are in memory.
+    pub fields: Vec<FieldLayout>,
+}
+
+impl TypeLayout {
+    // Panics if a field is named twice, whose bytes would then be counted
+    // twice.
+    pub fn new(name: &str, size: usize, align: usize, mut fields: Vec<FieldLayout>) -> TypeLayout {
+        for (i, field) in fields.iter().enumerate() {
+            assert!(fields[..i].iter().all(|f| f.name != field.name),
+                    "layout of {}: field `{}` named twice", name, field.name);
+        }
+        fields.sort_by_key(|f| f.offset);
+        TypeLayout { name: name.to_string(), size, align, fields }
+    }
+
+    // Bytes that belong to no field.
+    pub fn padding(&self) -> usize {
+        self.size - self.fields.iter().map(|f| f.size).sum::<usize>()
+    }
+
+    // The same fields laid out one after another as `#[repr(C)]` would,
+    // most aligned first, if that is smaller; `None` if nothing would be
+    // saved.
+    pub fn suggestion(&self) -> Option<TypeLayout> {
+        let mut order = self.fields.clone();
+        // Stable, so fields of the same alignment keep their order.
+        order.sort_by_key(|f| Reverse(f.align));
+        let mut offset = 0;
+        for field in &mut order {
+            field.offset = round_up(offset, field.align);
+            offset = field.end();
+        }
+        let size = round_up(offset, self.align);
+        if size < self.size {
+            Some(TypeLayout { name: self.name.clone(), size, align: self.align, fields: order })
+        } else {
+            None
+        }
+    }
+
+    // {"type": ..., "size": ..., "align": ..., "padding": ..., "fields": [...],
+    //  "suggested_order": [...], "suggested_size": ...}, the last two only
+    // if there is a suggestion, all on one line.
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = self.fields
+            .iter()
+            .map(|f| format!("{{\"name\":{},\"type\":{},\"offset\":{},\"size\":{},\"align\":{}}}",
+                             json_string(f.name), json_string(&f.type_name),
+                             f.offset, f.size, f.align))
+            .collect();
+        let mut json = format!("{{\"type\":{},\"size\":{},\"align\":{},\"padding\":{},\"fields\":[{}]",
+                               json_string(&self.name), self.size, self.align, self.padding(),
+                               fields.join(","));
+        if let Some(better) = self.suggestion() {
+            let names: Vec<String> = better.fields.iter().map(|f| json_string(f.name)).collect();
+            json.push_str(&format!(",\"suggested_order\":[{}],\"suggested_size\":{}",
+                                   names.join(","), better.size));
+        }
+        json.push('}');
+        json
+    }
+}
+
+// A table with a line for each field and for each gap between them, and
+// the suggested order after it if there is one.
+impl fmt::Display for TypeLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "struct {}: {} bytes, align {}, {} bytes padding",
+                 self.name, self.size, self.align, self.padding())?;
+        writeln!(f, "offset  size  align  field")?;
+        let mut end = 0;
+        for field in &self.fields {
+            if field.offset > end {
+                writeln!(f, "{:>6}  {:>4}         (padding)", end, field.offset - end)?;
+            }
+            writeln!(f, "{:>6}  {:>4}  {:>5}  {}: {}",
+                     field.offset, field.size, field.align, field.name, field.type_name)?;
+            end = end.max(field.end());
+        }
+        if self.size > end {
+            writeln!(f, "{:>6}  {:>4}         (padding)", end, self.size - end)?;
+        }
+        if let Some(better) = self.suggestion() {
+            let names: Vec<&str> = better.fields.iter().map(|f| f.name).collect();
+            writeln!(f, "as #[repr(C)] in the order {} it would be {} bytes, {} bytes padding",
+                     names.join(", "), better.size, better.padding())?;
+        }
+        Ok(())
+    }
+}
+
+// `layout_of!(Type { field, field, ... })` is the `TypeLayout` of `Type`,
+// with the fields named. The list is checked against the struct by a
+// pattern that is compiled but never run, so leaving a field out, naming
+// one twice or naming one `Type` hasn't got won't compile.
+#[macro_export]
+macro_rules! layout_of {
+    ($t:path { $($field:ident),* $(,)? }) => {{
+        let _every_field = |value: &$t| {
+            let $t { $($field: _),* } = value;
+        };
+        let value = ::std::mem::MaybeUninit::<$t>::uninit();
+        let base: *const $t = value.as_ptr();
+        let fields = vec![
+            $(
+                // `addr_of!` neither reads the field nor makes a reference
+                // to it, so it's fine that there's nothing there.
+                $crate::layout::FieldLayout::of(stringify!($field), base,
+                                                unsafe { ::std::ptr::addr_of!((*base).$field) })
+            ),*
+        ];
+        $crate::layout::TypeLayout::new(stringify!($t),
+                                        ::std::mem::size_of::<$t>(),
+                                        ::std::mem::align_of::<$t>(),
+                                        fields)
+    }};
+}
+
+fn round_up(n: usize, align: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+// `alloc::vec::Vec<alloc::string::String>` to `Vec<String>`: each path
+// loses everything up to its last `::`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    let mut path = String::new();
+    for c in name.chars().chain(Some(' ')) {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            short.push_str(path.rsplit("::").next().unwrap_or(""));
+            path.clear();
+            short.push(c);
+        }
+    }
+    short.pop();
+    short
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Padded {
+        flag: u8,
+        count: u64,
+        tag: u8,
+        id: u32,
+    }
+
+    #[allow(dead_code)]
+    struct Rust {
+        flag: u8,
+        names: Vec<String>,
+        tag: u8,
+    }
+
+    #[test]
+    fn measuring() {
+        let layout = layout_of!(Padded { flag, count, tag, id });
+        let offsets: Vec<(&str, usize)> = layout.fields.iter().map(|f| (f.name, f.offset)).collect();
+        assert_eq!(offsets, [("flag", 0), ("count", 8), ("tag", 16), ("id", 20)]);
+        assert_eq!((layout.size, layout.align, layout.padding()), (24, 8, 10));
+
+        let better = layout.suggestion().unwrap();
+        let order: Vec<&str> = better.fields.iter().map(|f| f.name).collect();
+        assert_eq!(order, ["count", "id", "flag", "tag"]);
+        assert_eq!((better.size, better.padding()), (16, 2));
+
+        // Already in a good order, whatever order it's written in.
+        let layout = layout_of!(Rust { flag, names, tag, });
+        assert_eq!(layout.fields[0].type_name, "Vec<String>");
+        assert_eq!(layout.size, mem::size_of::<Vec<String>>() + 8);
+        assert_eq!(layout.suggestion(), None);
+        assert_eq!(short_type_name("&[core::option::Option<u8>; 3]"), "&[Option<u8>; 3]");
+    }
+
+    #[test]
+    fn reporting() {
+        let layout = layout_of!(Padded { flag, count, tag, id });
+        assert_eq!(layout.to_string(), "\
+struct Padded: 24 bytes, align 8, 10 bytes padding
+offset  size  align  field
+     0     1      1  flag: u8
+     1     7         (padding)
+     8     8      8  count: u64
+    16     1      1  tag: u8
+    17     3         (padding)
+    20     4      4  id: u32
+as #[repr(C)] in the order count, id, flag, tag it would be 16 bytes, 2 bytes padding
+");
+        assert_eq!(layout.to_json(), "{\"type\":\"Padded\",\"size\":24,\"align\":8,\"padding\":10,\"fields\":[\
+{\"name\":\"flag\",\"type\":\"u8\",\"offset\":0,\"size\":1,\"align\":1},\
+{\"name\":\"count\",\"type\":\"u64\",\"offset\":8,\"size\":8,\"align\":8},\
+{\"name\":\"tag\",\"type\":\"u8\",\"offset\":16,\"size\":1,\"align\":1},\
+{\"name\":\"id\",\"type\":\"u32\",\"offset\":20,\"size\":4,\"align\":4}],\
+\"suggested_order\":[\"count\",\"id\",\"flag\",\"tag\"],\"suggested_size\":16}");
+        assert_eq!(json_string("a\"b\\\n"), "\"a\\\"b\\\\\\u000a\"");
+    }
+
+    #[test]
+    #[should_panic(expected = "layout of Padded: field `tag` named twice")]
+    fn field_named_twice() {
+        // `layout_of!` won't compile this; `new` has to check for itself.
+        let layout = layout_of!(Padded { flag, count, tag, id });
+        let mut fields = layout.fields.clone();
+        fields.push(layout.fields[2].clone());
+        TypeLayout::new("Padded", layout.size, layout.align, fields);
+    }
+}
@@ -0,0 +1,62 @@
+/*
+Several of the examples need the same few types: a `Point` and a `Rectangle`
+made from two of them, a `Complex` number, a 2x2 `Matrix`, the `DoubleError`
+of the error handling chapter and the `List` of the enums chapter. Each
+example used to define its own copy, and the copies drifted apart.
+
+This is the one real library crate in the tree, built by cargo rather than
+by hand with `rustc` as in 11-crates/crates. The examples depend on it by
+path, asking only for the parts they use:
+
+[dependencies]
+rbe = { path = "../../rbe", features = ["geometry"] }
+
+and link it the 2015 way:
+
+extern crate rbe;
+use rbe::geometry::{Point, Rectangle};
+
+The one macro, `layout_of!`, needs `#[macro_use]` on the `extern crate`.
+
+Features:
+
+geometry     Point, Rectangle, Matrix
+complex      Complex
+errors       DoubleError
+collections  List, Multiset
+quadtree     QuadTree, a spatial index of rectangles (and geometry, random)
+allocation   Counting, an allocator that counts, and AllocSnapshot
+layout       layout_of!, the offsets, sizes and padding of a struct
+random       Lcg, the same made-up numbers on every run
+
+A change here reaches every example at once, and `cargo test --workspace`
+from the top of the tree builds and tests all of them.
+*/
+
+#[cfg(feature = "geometry")]
+pub mod geometry;
+
+#[cfg(feature = "complex")]
+pub mod complex;
+
+#[cfg(feature = "errors")]
+pub mod errors;
+
+#[cfg(feature = "collections")]
+pub mod collections;
+
+#[cfg(feature = "collections")]
+pub mod multiset;
+
+#[cfg(feature = "quadtree")]
+pub mod quadtree;
+
+#[cfg(feature = "allocation")]
+pub mod allocation;
+
+#[cfg(feature = "layout")]
+#[macro_use]
+pub mod layout;
+
+#[cfg(feature = "random")]
+pub mod random;
@@ -0,0 +1,273 @@
+// multiset.rs
+// A `Multiset`, or bag: a set that keeps count. Inserting a value a
+// `HashSet` already has changes nothing; inserting it into a `Multiset`
+// adds one to its count. It is a `HashMap` from each value to how many
+// there are, and a value whose count falls to zero is taken out of the
+// map, so two bags with the same counts are equal.
+//
+// Used by the hashset and strings examples.
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Multiset<T: Hash + Eq> {
+    counts: HashMap<T, usize>,
+    // The sum of `counts`, kept so `total_len` needn't add them up.
+    total: usize,
+}
+
+impl<T: Hash + Eq> Default for Multiset<T> {
+    fn default() -> Multiset<T> {
+        Multiset::new()
+    }
+}
+
+impl<T: Hash + Eq> Multiset<T> {
+    pub fn new() -> Multiset<T> {
+        Multiset { counts: HashMap::new(), total: 0 }
+    }
+
+    // How many copies of `value` there are; 0 for none.
+    pub fn count<Q>(&self, value: &Q) -> usize
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.counts.get(value).cloned().unwrap_or(0)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.counts.contains_key(value)
+    }
+
+    // Every copy, counted: {a, a, b} is 3.
+    pub fn total_len(&self) -> usize {
+        self.total
+    }
+
+    // Different values only: {a, a, b} is 2.
+    pub fn distinct_len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    // Returns how many there were before.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.insert_n(value, 1)
+    }
+
+    pub fn insert_n(&mut self, value: T, n: usize) -> usize {
+        if n == 0 {
+            return self.count(&value);
+        }
+        self.total += n;
+        let count = self.counts.entry(value).or_insert(0);
+        *count += n;
+        *count - n
+    }
+
+    // Returns whether there was one to remove.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.remove_n(value, 1) == 1
+    }
+
+    // Takes away up to `n` copies, and returns how many it took.
+    pub fn remove_n<Q>(&mut self, value: &Q, n: usize) -> usize
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let (taken, left) = match self.counts.get_mut(value) {
+            Some(count) => {
+                let taken = n.min(*count);
+                *count -= taken;
+                (taken, *count)
+            }
+            None => return 0,
+        };
+        if left == 0 {
+            self.counts.remove(value);
+        }
+        self.total -= taken;
+        taken
+    }
+
+    // Each value with its count, in no particular order.
+    pub fn counts(&self) -> hash_map::Iter<'_, T, usize> {
+        self.counts.iter()
+    }
+
+    // Each value as many times as it is in the bag, copies together.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { counts: self.counts.iter(), value: None, left: 0 }
+    }
+
+    // The `k` values with the highest counts, highest first. Equal counts
+    // are in the values' order, so the answer is the same every time.
+    pub fn most_common(&self, k: usize) -> Vec<(&T, usize)>
+        where T: Ord
+    {
+        let mut all: Vec<(&T, usize)> = self.counts.iter().map(|(v, &n)| (v, n)).collect();
+        all.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        all.truncate(k);
+        all
+    }
+
+    // Every value in either, each as many times as in whichever has more.
+    pub fn union(&self, other: &Multiset<T>) -> Multiset<T>
+        where T: Clone
+    {
+        let mut out = self.clone();
+        for (value, &n) in &other.counts {
+            let have = out.count(value);
+            if n > have {
+                out.insert_n(value.clone(), n - have);
+            }
+        }
+        out
+    }
+
+    // Both bags poured into one: the counts added.
+    pub fn sum(&self, other: &Multiset<T>) -> Multiset<T>
+        where T: Clone
+    {
+        let mut out = self.clone();
+        for (value, &n) in &other.counts {
+            out.insert_n(value.clone(), n);
+        }
+        out
+    }
+
+    // Every value in both, as many times as in whichever has fewer.
+    pub fn intersection(&self, other: &Multiset<T>) -> Multiset<T>
+        where T: Clone
+    {
+        let mut out = Multiset::new();
+        for (value, &n) in &self.counts {
+            out.insert_n(value.clone(), n.min(other.count(value)));
+        }
+        out
+    }
+
+    // What's left of this one when `other` is taken out, stopping at 0.
+    pub fn difference(&self, other: &Multiset<T>) -> Multiset<T>
+        where T: Clone
+    {
+        let mut out = Multiset::new();
+        for (value, &n) in &self.counts {
+            out.insert_n(value.clone(), n.saturating_sub(other.count(value)));
+        }
+        out
+    }
+
+    // Whether `other` has at least as many of everything.
+    pub fn is_subset(&self, other: &Multiset<T>) -> bool {
+        self.counts.iter().all(|(value, &n)| n <= other.count(value))
+    }
+}
+
+pub struct Iter<'a, T: 'a> {
+    counts: hash_map::Iter<'a, T, usize>,
+    // The value being repeated, and how many more times.
+    value: Option<&'a T>,
+    left: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.left == 0 {
+            let (value, &n) = self.counts.next()?;
+            self.value = Some(value);
+            self.left = n;
+        }
+        self.left -= 1;
+        self.value
+    }
+}
+
+impl<'a, T: Hash + Eq> IntoIterator for &'a Multiset<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for Multiset<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Multiset<T> {
+        let mut bag = Multiset::new();
+        bag.extend(iter);
+        bag
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for Multiset<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+// `{'a': 2, 'b': 1}`
+impl<T: Hash + Eq + fmt::Debug> fmt::Debug for Multiset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.counts.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bag(s: &str) -> Multiset<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn counting() {
+        let mut b = bag("mississippi");
+        assert_eq!(b.total_len(), 11);
+        assert_eq!(b.distinct_len(), 4);
+        assert_eq!(b.count(&'s'), 4);
+        assert_eq!(b.count(&'x'), 0);
+        assert_eq!(b.most_common(2), [(&'i', 4), (&'s', 4)]);
+
+        assert_eq!(b.insert('m'), 1);
+        assert_eq!(b.insert_n('x', 3), 0);
+        assert_eq!(b.remove_n(&'s', 10), 4);
+        assert!(!b.contains(&'s') && !b.remove(&'s'));
+        assert!(b.remove(&'x'));
+        assert_eq!(b.total_len(), 11 + 1 + 3 - 4 - 1);
+
+        let mut letters: Vec<char> = b.iter().cloned().collect();
+        letters.sort();
+        assert_eq!(letters.into_iter().collect::<String>(), "iiiimmppxx");
+        assert_eq!(format!("{:?}", bag("aa")), "{'a': 2}");
+
+        let words: Multiset<String> = "a rose is a rose".split(' ').map(String::from).collect();
+        assert_eq!(words.count("rose"), 2);
+    }
+
+    #[test]
+    fn algebra() {
+        let (a, b) = (bag("aaabbc"), bag("abbbbd"));
+        assert_eq!(a.union(&b), bag("aaabbbbcd"));
+        assert_eq!(a.sum(&b), bag("aaaabbbbbbcd"));
+        assert_eq!(a.intersection(&b), bag("abb"));
+        assert_eq!(a.difference(&b), bag("aac"));
+        assert_eq!(b.difference(&a), bag("bbd"));
+        assert_eq!(a.sum(&b).total_len(), a.total_len() + b.total_len());
+        assert!(a.intersection(&b).is_subset(&a) && !a.is_subset(&b));
+        assert!(a.difference(&a).is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+// quadtree.rs
+// A `QuadTree` finds the things in part of the plane without looking at
+// everything else. Each item is stored with its bounding `Rectangle`. A
+// node holds up to `capacity` items; one more and it splits into four
+// quarters, and every item that fits wholly inside a quarter moves down
+// into it. Items that straddle a line between quarters stay where they are.
+// A search then only visits the quarters that could hold an answer.
+//
+// The quarters are owned by their parent through a `Box`, as in
+// boxstackheap: a node has a fixed size whether it has children or not, and
+// dropping the root drops the whole tree.
+use std::mem;
+use std::slice;
+
+use geometry::{Point, Rectangle};
+
+#[derive(Debug)]
+struct Node<T> {
+    bounds: Rectangle,
+    items: Vec<(Rectangle, T)>,
+    children: Option<Box<[Node<T>; 4]>>,
+    // Items here and in all the children below.
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct QuadTree<T> {
+    root: Node<T>,
+    capacity: usize,
+    max_depth: usize,
+}
+
+impl<T> Node<T> {
+    fn new(bounds: Rectangle) -> Node<T> {
+        Node { bounds, items: Vec::new(), children: None, count: 0 }
+    }
+
+    // The child that `bounds` fits inside, if any. An item on the line
+    // between two goes to the first, both going in and coming out.
+    fn child_for(&mut self, bounds: &Rectangle) -> Option<&mut Node<T>> {
+        self.children.as_mut()?.iter_mut().find(|child| child.bounds.encloses(bounds))
+    }
+
+    fn insert(&mut self, bounds: Rectangle, item: T, depth: usize, capacity: usize, max_depth: usize) {
+        self.count += 1;
+        if self.children.is_some() {
+            match self.child_for(&bounds) {
+                Some(child) => child.insert(bounds, item, depth + 1, capacity, max_depth),
+                None => self.items.push((bounds, item)),
+            }
+            return;
+        }
+        self.items.push((bounds, item));
+        if self.items.len() > capacity && depth < max_depth {
+            self.split(depth, capacity, max_depth);
+        }
+    }
+
+    // Each quarter runs from one corner of this node to its center.
+    fn split(&mut self, depth: usize, capacity: usize, max_depth: usize) {
+        let (p1, p2, center) = (self.bounds.p1, self.bounds.p2, self.bounds.center());
+        self.children = Some(Box::new([
+            Node::new(Rectangle::new(p1, center)),
+            Node::new(Rectangle::new(Point::new(p2.x, p1.y), center)),
+            Node::new(Rectangle::new(Point::new(p1.x, p2.y), center)),
+            Node::new(Rectangle::new(p2, center)),
+        ]));
+        for (bounds, item) in mem::take(&mut self.items) {
+            match self.child_for(&bounds) {
+                Some(child) => child.insert(bounds, item, depth + 1, capacity, max_depth),
+                None => self.items.push((bounds, item)),
+            }
+        }
+    }
+
+    fn remove(&mut self, bounds: &Rectangle, item: &T, capacity: usize) -> Option<T>
+        where T: PartialEq
+    {
+        let removed = match self.items.iter().position(|(b, t)| b == bounds && t == item) {
+            Some(i) => Some(self.items.remove(i).1),
+            None => self.child_for(bounds).and_then(|child| child.remove(bounds, item, capacity)),
+        };
+        if removed.is_some() {
+            self.count -= 1;
+            // Few enough left to be one node again.
+            if self.count <= capacity {
+                if let Some(children) = self.children.take() {
+                    for child in *children {
+                        child.gather(&mut self.items);
+                    }
+                }
+            }
+        }
+        removed
+    }
+
+    // Moves every item of this node and its children into `into`.
+    fn gather(self, into: &mut Vec<(Rectangle, T)>) {
+        into.extend(self.items);
+        if let Some(children) = self.children {
+            for child in *children {
+                child.gather(into);
+            }
+        }
+    }
+
+    fn query<'a>(&'a self, area: &Rectangle, found: &mut Vec<&'a T>) {
+        found.extend(self.items.iter().filter(|&(b, _)| b.intersects(area)).map(|(_, t)| t));
+        if let Some(ref children) = self.children {
+            for child in children.iter().filter(|child| child.count > 0 && child.bounds.intersects(area)) {
+                child.query(area, found);
+            }
+        }
+    }
+
+    // Looks at the nearer children first, and not at all at a child that
+    // is further away than the best found so far.
+    fn nearest<'a>(&'a self, p: &Point, best: &mut Option<(&'a T, f64)>) {
+        for (bounds, item) in &self.items {
+            let distance = bounds.distance_to(p);
+            if best.is_none_or(|(_, d)| distance < d) {
+                *best = Some((item, distance));
+            }
+        }
+        if let Some(ref children) = self.children {
+            let mut order: Vec<(f64, &Node<T>)> = children.iter()
+                .filter(|child| child.count > 0)
+                .map(|child| (child.bounds.distance_to(p), child))
+                .collect();
+            order.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (distance, child) in order {
+                if best.is_some_and(|(_, d)| distance >= d) {
+                    break;
+                }
+                child.nearest(p, best);
+            }
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self.children {
+            Some(ref children) => 1 + children.iter().map(|child| child.depth()).max().unwrap(),
+            None => 0,
+        }
+    }
+}
+
+impl<T> QuadTree<T> {
+    // Up to 8 items in a node, and nodes up to 8 levels below the root.
+    pub fn new(bounds: Rectangle) -> QuadTree<T> {
+        QuadTree::with_limits(bounds, 8, 8)
+    }
+
+    // A node with more than `capacity` items splits, unless it is already
+    // `max_depth` levels down. The depth limit stops many items at the same
+    // spot from splitting forever.
+    pub fn with_limits(bounds: Rectangle, capacity: usize, max_depth: usize) -> QuadTree<T> {
+        assert!(capacity > 0, "a node must hold at least one item");
+        QuadTree { root: Node::new(bounds), capacity, max_depth }
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.root.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.count == 0
+    }
+
+    // How many levels there are below the root.
+    pub fn depth(&self) -> usize {
+        self.root.depth()
+    }
+
+    // An item has to fit inside the tree's bounds; one that doesn't is
+    // given back.
+    pub fn insert(&mut self, bounds: Rectangle, item: T) -> Result<(), T> {
+        if !self.root.bounds.encloses(&bounds) {
+            return Err(item);
+        }
+        self.root.insert(bounds, item, 0, self.capacity, self.max_depth);
+        Ok(())
+    }
+
+    // Takes out an item equal to `item` with exactly these bounds. Nodes
+    // left with few enough items merge back into their parent.
+    pub fn remove(&mut self, bounds: &Rectangle, item: &T) -> Option<T>
+        where T: PartialEq
+    {
+        self.root.remove(bounds, item, self.capacity)
+    }
+
+    // Every item whose bounds meet `area`, touching included.
+    pub fn query(&self, area: &Rectangle) -> Vec<&T> {
+        let mut found = Vec::new();
+        self.root.query(area, &mut found);
+        found
+    }
+
+    // The item whose bounds come closest to `p`, and how close. An item
+    // `p` is inside of is 0 away.
+    pub fn nearest(&self, p: &Point) -> Option<(&T, f64)> {
+        let mut best = None;
+        self.root.nearest(p, &mut best);
+        best
+    }
+
+    // Every item with its bounds, a node at a time.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { nodes: vec![&self.root], items: [].iter() }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a QuadTree<T> {
+    type Item = (&'a Rectangle, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T: 'a> {
+    // Nodes still to visit.
+    nodes: Vec<&'a Node<T>>,
+    // What is left of the items of the node being visited.
+    items: slice::Iter<'a, (Rectangle, T)>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a Rectangle, &'a T);
+
+    fn next(&mut self) -> Option<(&'a Rectangle, &'a T)> {
+        loop {
+            if let Some((bounds, item)) = self.items.next() {
+                return Some((bounds, item));
+            }
+            let node = self.nodes.pop()?;
+            if let Some(ref children) = node.children {
+                self.nodes.extend(children.iter());
+            }
+            self.items = node.items.iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use random::Lcg;
+
+    fn square(x: f64, y: f64, side: f64) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Point::new(x + side, y + side))
+    }
+
+    // Some small squares scattered over 0..100, the same ones every run.
+    fn scattered(n: usize) -> Vec<(Rectangle, usize)> {
+        let mut rng = Lcg::new(12345);
+        let mut next = move || rng.next_f64();
+        (0..n).map(|i| (square(next() * 98.0, next() * 98.0, next() * 2.0), i)).collect()
+    }
+
+    #[test]
+    fn agrees_with_a_linear_search() {
+        let items = scattered(500);
+        let mut tree = QuadTree::with_limits(square(0.0, 0.0, 100.0), 4, 6);
+        for &(bounds, i) in &items {
+            tree.insert(bounds, i).unwrap();
+        }
+        assert_eq!(tree.len(), 500);
+        assert!(tree.depth() > 1 && tree.depth() <= 6);
+
+        let mut all: Vec<usize> = tree.iter().map(|(_, &i)| i).collect();
+        all.sort();
+        assert_eq!(all, (0..500).collect::<Vec<_>>());
+
+        let area = Rectangle::new(Point::new(20.0, 30.0), Point::new(45.0, 40.0));
+        let mut found: Vec<usize> = tree.query(&area).into_iter().cloned().collect();
+        found.sort();
+        let expected: Vec<usize> = items.iter().filter(|&&(b, _)| b.intersects(&area)).map(|&(_, i)| i).collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+
+        for p in &[Point::new(50.0, 50.0), Point::new(0.0, 99.0), Point::new(-10.0, 3.0)] {
+            let (&i, distance) = tree.nearest(p).unwrap();
+            let closest = items.iter().map(|&(b, _)| b.distance_to(p)).fold(f64::INFINITY, f64::min);
+            assert_eq!(distance, closest);
+            assert_eq!(items[i].0.distance_to(p), closest);
+        }
+
+        // Taking nearly everything out merges the nodes back together.
+        for &(bounds, i) in &items[..497] {
+            assert_eq!(tree.remove(&bounds, &i), Some(i));
+        }
+        assert_eq!(tree.remove(&items[0].0, &0), None);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.depth(), 0);
+        assert_eq!(tree.iter().count(), 3);
+    }
+
+    #[test]
+    fn limits() {
+        let mut tree = QuadTree::with_limits(square(0.0, 0.0, 10.0), 1, 3);
+        assert!(tree.is_empty());
+        assert_eq!(tree.nearest(&Point::origin()), None);
+        assert_eq!(tree.insert(square(9.0, 9.0, 2.0), "too big"), Err("too big"));
+
+        // Ten items at one spot would split forever but for `max_depth`.
+        for _ in 0..10 {
+            tree.insert(square(1.0, 1.0, 0.0), "here").unwrap();
+        }
+        assert_eq!(tree.depth(), 3);
+        // Straddles the center, so stays in the root.
+        tree.insert(square(4.0, 4.0, 2.0), "middle").unwrap();
+        assert_eq!(tree.query(&square(5.0, 5.0, 0.0)), [&"middle"]);
+        assert_eq!(tree.nearest(&Point::new(9.0, 5.0)), Some((&"middle", 3.0)));
+        assert_eq!(tree.query(&square(0.0, 0.0, 1.0)).len(), 10);
+    }
+}
@@ -0,0 +1,63 @@
+// random.rs
+// `Lcg`, a linear congruential generator with Knuth's MMIX constants. The
+// same seed gives the same numbers on every run, which is what examples
+// and tests want from scattered data; it is nowhere near good enough for
+// anything that has to be unpredictable.
+//
+// The low bits of an LCG repeat quickly, so only the top 31 bits of the
+// state are ever handed out.
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Lcg {
+        Lcg { state: seed }
+    }
+
+    // A number below 2^31.
+    pub fn next_u31(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.state >> 33) as u32
+    }
+
+    // A number from 0 up to but not including 1.
+    pub fn next_f64(&mut self) -> f64 {
+        f64::from(self.next_u31()) / f64::from(1u32 << 31)
+    }
+
+    // A number below `n`, which must be more than 0. Slightly biased
+    // towards small numbers unless `n` divides 2^31.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Lcg::below(0)");
+        self.next_u31() as usize % n
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repeatable() {
+        let mut a = Lcg::new(0);
+        assert_eq!((a.next_u31(), a.next_u31()), (167951807, 218396424));
+
+        let mut b = Lcg::new(2024);
+        let mut c = b.clone();
+        for _ in 0..100 {
+            assert_eq!(b.next_u31(), c.next_u31());
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Lcg::new(7);
+        for n in 1..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert!(rng.below(n) < n);
+        }
+    }
+}